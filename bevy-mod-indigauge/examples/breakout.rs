@@ -2,7 +2,6 @@
 
 use std::env;
 
-use bevy::log::BoxedLayer;
 use bevy::{
   log::LogPlugin,
   math::bounding::{Aabb2d, BoundingCircle, BoundingVolume, IntersectsVolume},
@@ -69,20 +68,10 @@ enum GameState {
   Paused,
 }
 
-#[cfg(feature = "tracing")]
-pub fn indigauge_layer(_app: &mut App) -> Option<BoxedLayer> {
-  Some(Box::new(bevy_mod_indigauge::tracing::default_bevy_indigauge_layer()))
-}
-
-#[cfg(not(feature = "tracing"))]
-pub fn indigauge_layer(_app: &mut App) -> Option<BoxedLayer> {
-  None
-}
-
 fn main() {
   App::new()
     .add_plugins(DefaultPlugins.set(LogPlugin {
-      custom_layer: indigauge_layer,
+      custom_layer: indigauge_log_layer,
       ..default()
     }))
     .insert_state(GameState::default())
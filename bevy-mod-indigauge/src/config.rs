@@ -1,7 +1,37 @@
-use indigauge_core::types::{IndigaugeConfig, IndigaugeLogLevel, IndigaugeMode};
+#[cfg(feature = "otlp")]
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
+#[cfg(not(target_family = "wasm"))]
+use indigauge_core::consent::effective_consent;
+use indigauge_core::event::QueuedEvent;
+#[cfg(feature = "tracing")]
+use indigauge_core::filter::FilterHandle;
+#[cfg(not(target_family = "wasm"))]
+use indigauge_core::ndjson::{NdjsonRecordKind, NdjsonWriter};
+#[cfg(feature = "otlp")]
+use indigauge_core::otlp::{OtlpExporter, new_session_id};
+use indigauge_core::schema::SchemaRegistry;
+use indigauge_core::sink::{EventSink, EventSinks, SinkOptions};
+use indigauge_core::state::SessionHandle;
+use indigauge_core::types::{EventPayload, IndigaugeConfig, IndigaugeLogLevel, IndigaugeMode, TelemetryConsent};
+
+#[cfg(not(target_family = "wasm"))]
+use bevy::log::warn;
 use bevy::prelude::*;
 
+/// Name of the sink that hands events to the Indigauge ingest, which sends them in batches and retries failed ones.
+pub const INGEST_SINK: &str = "indigauge";
+
+/// Name of the sink writing events to the [`BevyIndigaugeEventFile`] in [`IndigaugeMode::Dev`].
+pub const EVENT_FILE_SINK: &str = "event_file";
+
+/// Name of the sink printing events to the console in [`IndigaugeMode::Dev`].
+pub const CONSOLE_SINK: &str = "console";
+
+/// Name of the sink feeding the developer overlay.
+pub const DEV_OVERLAY_SINK: &str = "dev_overlay";
+
 /// Bevy resource wrapper around the shared Indigauge SDK config.
 #[derive(Resource, Clone, Deref)]
 pub struct BevyIndigaugeConfig(pub IndigaugeConfig);
@@ -20,3 +50,208 @@ pub struct BevyIndigaugeLogLevel(pub IndigaugeLogLevel);
 /// Bevy resource wrapper for SDK runtime mode.
 #[derive(Resource, Default, Clone, Deref, Debug)]
 pub struct BevyIndigaugeMode(pub IndigaugeMode);
+
+/// Bevy resource mirroring the telemetry consent given by the player.
+///
+/// Change it by triggering [`SetTelemetryConsentEvent`](crate::consent::SetTelemetryConsentEvent), which also
+/// persists the choice and purges telemetry the new consent no longer allows.
+#[derive(Resource, Default, Clone, Copy, Deref, Debug)]
+pub struct BevyTelemetryConsent(pub TelemetryConsent);
+
+/// Bevy resource holding the event schemas set with
+/// [`IndigaugePlugin::schema_registry`](crate::plugin::IndigaugePlugin::schema_registry).
+#[derive(Resource, Clone, Deref)]
+pub struct BevySchemaRegistry {
+  #[deref]
+  pub registry: SchemaRegistry,
+  /// Whether nonconforming metadata keys are removed before events are sent.
+  pub strip_nonconforming: bool,
+}
+
+/// Bevy resource holding the [`FilterHandle`] of a tracing layer, to change its level directives at runtime.
+///
+/// Insert it in the `LogPlugin::custom_layer` function with [`IndigaugeLayer::filter_handle`].
+///
+/// [`IndigaugeLayer::filter_handle`]: indigauge_core::tracing::IndigaugeLayer::filter_handle
+#[cfg(feature = "tracing")]
+#[derive(Resource, Clone, Deref, Debug)]
+pub struct BevyIndigaugeLogFilter(pub FilterHandle);
+
+/// Bevy resource holding the NDJSON file set with [`IndigaugePlugin::event_file`].
+///
+/// Session starts, ends, metadata and feedback are written here directly; events reach the file through the
+/// [`EVENT_FILE_SINK`] registered in [`BevyIndigaugeEventSinks`].
+///
+/// [`IndigaugePlugin::event_file`]: crate::plugin::IndigaugePlugin::event_file
+#[cfg(not(target_family = "wasm"))]
+#[derive(Resource, Deref, Debug)]
+pub struct BevyIndigaugeEventFile(pub Arc<Mutex<NdjsonWriter>>);
+
+/// The [`EVENT_FILE_SINK`], writing events to the file shared with the [`BevyIndigaugeEventFile`].
+#[cfg(not(target_family = "wasm"))]
+pub(crate) struct EventFileSink {
+  pub writer: Arc<Mutex<NdjsonWriter>>,
+  pub warn_failures: bool,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl EventSink for EventFileSink {
+  fn send(&mut self, session: SessionHandle, events: &[EventPayload]) {
+    if !effective_consent().allows_session() {
+      return;
+    }
+
+    let mut writer = self.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    for event in events {
+      if let Err(error) = writer.write(NdjsonRecordKind::Event, Some(session), event) {
+        if self.warn_failures {
+          warn!(message = "Failed to write event file", ?error);
+        }
+        return;
+      }
+    }
+  }
+
+  fn flush(&mut self) {
+    let _ = self
+      .writer
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .flush();
+  }
+}
+
+/// The [`INGEST_SINK`], handing the events that pass its filter to the batches of their session.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct IngestSink(Arc<Mutex<Vec<QueuedEvent>>>);
+
+impl IngestSink {
+  fn take(&self) -> Vec<QueuedEvent> {
+    std::mem::take(&mut *self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+  }
+}
+
+impl EventSink for IngestSink {
+  fn send(&mut self, session: SessionHandle, events: &[EventPayload]) {
+    self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).extend(
+      events
+        .iter()
+        .map(|event| QueuedEvent::new(event.clone()).with_session(session)),
+    );
+  }
+}
+
+/// Bevy resource holding the [`EventSinks`] registered with [`IndigaugePlugin::sink`] or [`Self::register`].
+///
+/// Validated events are dispatched to the sinks as they are taken from the queue, in [`IndigaugeMode::Live`] and
+/// [`IndigaugeMode::Dev`]. Buffered events are sent when the session ends and when the game exits.
+///
+/// Every destination is a sink with its own [`SinkOptions`], which [`IndigaugePlugin::sink_options`] or
+/// [`Self::set_options`] change: the ingest ([`INGEST_SINK`]), the event file ([`EVENT_FILE_SINK`]), the console
+/// output ([`CONSOLE_SINK`]) and the developer overlay ([`DEV_OVERLAY_SINK`]). The OTLP export receives the batches
+/// of the ingest.
+///
+/// [`IndigaugePlugin::sink`]: crate::plugin::IndigaugePlugin::sink
+/// [`IndigaugePlugin::sink_options`]: crate::plugin::IndigaugePlugin::sink_options
+#[derive(Resource, Deref, Debug, Default)]
+pub struct BevyIndigaugeEventSinks {
+  #[deref]
+  sinks: Mutex<EventSinks>,
+  ingest: IngestSink,
+}
+
+impl BevyIndigaugeEventSinks {
+  /// Creates the resource for the sinks, registering the [`INGEST_SINK`] unless a sink is registered under its name.
+  pub(crate) fn new(mut sinks: EventSinks) -> Self {
+    let ingest = IngestSink::default();
+    if sinks.options(INGEST_SINK).is_none() {
+      sinks.register(INGEST_SINK, ingest.clone(), SinkOptions::new());
+    }
+    Self {
+      sinks: Mutex::new(sinks),
+      ingest,
+    }
+  }
+
+  /// Registers a sink under a name, replacing the sink already registered under it.
+  pub fn register(&self, name: impl Into<String>, sink: impl EventSink, options: SinkOptions) {
+    self.sinks().register(name, sink, options);
+  }
+
+  /// Flushes and removes the sink registered under a name. Returns `true` if there was one.
+  pub fn remove(&self, name: &str) -> bool {
+    self.sinks().remove(name)
+  }
+
+  /// Replaces the options of the sink registered under a name. Returns `true` if there was one.
+  pub fn set_options(&self, name: &str, options: SinkOptions) -> bool {
+    self.sinks().set_options(name, options)
+  }
+
+  pub(crate) fn dispatch(&self, event: &QueuedEvent) {
+    self.sinks().dispatch(event.session(), event.payload());
+  }
+
+  /// Returns `true` if the [`INGEST_SINK`] is registered and its filter passes the event.
+  pub(crate) fn ingest_accepts(&self, event: &QueuedEvent) -> bool {
+    self
+      .sinks()
+      .options(INGEST_SINK)
+      .is_some_and(|options| options.accepts(event.payload()))
+  }
+
+  /// Takes the events the [`INGEST_SINK`] has received.
+  pub(crate) fn take_ingested(&self) -> Vec<QueuedEvent> {
+    self.ingest.take()
+  }
+
+  pub(crate) fn retain(&self, filter: impl FnMut(SessionHandle, &EventPayload) -> bool) {
+    self.sinks().retain(filter);
+  }
+
+  pub(crate) fn flush_due(&self) {
+    self.sinks().flush_due();
+  }
+
+  pub(crate) fn flush_all(&self) {
+    self.sinks().flush_all();
+  }
+
+  fn sinks(&self) -> std::sync::MutexGuard<'_, EventSinks> {
+    self.sinks.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+/// Bevy resource holding the OTLP exporter set with [`IndigaugePlugin::otlp`].
+///
+/// [`IndigaugePlugin::otlp`]: crate::plugin::IndigaugePlugin::otlp
+#[cfg(feature = "otlp")]
+#[derive(Resource, Deref, Debug)]
+pub struct BevyIndigaugeOtlp {
+  #[deref]
+  pub exporter: OtlpExporter,
+  /// The `session.id` exported for each session, generated on its first export.
+  session_ids: Mutex<HashMap<SessionHandle, String>>,
+}
+
+#[cfg(feature = "otlp")]
+impl BevyIndigaugeOtlp {
+  /// Creates the resource for an exporter.
+  pub fn new(exporter: OtlpExporter) -> Self {
+    Self {
+      exporter,
+      session_ids: Mutex::default(),
+    }
+  }
+
+  /// Returns the `session.id` exported for a session.
+  pub fn session_id(&self, session: SessionHandle) -> String {
+    self
+      .session_ids
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .entry(session)
+      .or_insert_with(new_session_id)
+      .clone()
+  }
+}
@@ -0,0 +1,143 @@
+use bevy::log::{info, warn};
+use bevy::prelude::*;
+use indigauge_core::consent::{effective_consent, set_consent, store_consent};
+use indigauge_core::event::QueuedEvent;
+use indigauge_core::http::delete_player_id;
+use indigauge_core::state::{
+  SessionHandle, drain_pending_events, reset_session, track_pending_event, unregister_session,
+};
+use indigauge_core::types::{IndigaugeLogLevel, TelemetryConsent};
+
+use crate::{
+  config::{BevyIndigaugeConfig, BevyIndigaugeLogLevel, BevyTelemetryConsent},
+  event::resources::EventQueueReceiver,
+  event::systems::route_events,
+  session::resources::{PendingSession, PlayerSessions, SessionApiKey, SessionStartRequest},
+  session::systems::send_end_session,
+  utils::BevyIndigauge,
+};
+
+/// Event that records the telemetry consent given by the player.
+///
+/// The consent is persisted next to the player id (native only) and applied immediately: queued events it no
+/// longer allows are purged, revoking consent ends every active session and deletes the offline event queue (wasm
+/// only), and the stored player id is deleted when the consent no longer allows it.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SetTelemetryConsentEvent(pub TelemetryConsent);
+
+/// Plugin that applies consent changes.
+pub(crate) struct ConsentPlugin;
+
+impl Plugin for ConsentPlugin {
+  fn build(&self, app: &mut App) {
+    app.add_observer(observe_set_consent);
+  }
+}
+
+fn observe_set_consent(
+  event: On<SetTelemetryConsentEvent>,
+  mut commands: Commands,
+  mut consent: ResMut<BevyTelemetryConsent>,
+  config: Res<BevyIndigaugeConfig>,
+  log_level: Res<BevyIndigaugeLogLevel>,
+) {
+  let new_consent = event.event().0;
+  set_consent(new_consent);
+  consent.0 = new_consent;
+
+  if let Err(error) = store_consent(config.game_name(), new_consent)
+    && **log_level <= IndigaugeLogLevel::Warn
+  {
+    warn!(message = "Failed to persist telemetry consent", ?error);
+  }
+
+  if !effective_consent().allows_player_id()
+    && let Err(error) = delete_player_id(config.game_name())
+    && **log_level <= IndigaugeLogLevel::Warn
+  {
+    warn!(message = "Failed to delete player id", ?error);
+  }
+
+  if **log_level <= IndigaugeLogLevel::Info {
+    info!(message = "Telemetry consent updated", consent = new_consent.as_str());
+  }
+
+  commands.run_system_cached(purge_disallowed_telemetry);
+}
+
+/// Drops queued telemetry the current consent no longer allows, deletes the event file unless consent is granted,
+/// and ends all sessions and deletes the offline queue (wasm only) if consent was revoked.
+fn purge_disallowed_telemetry(
+  mut commands: Commands,
+  mut ig: BevyIndigauge,
+  mut players: ResMut<PlayerSessions>,
+  receiver: Option<Res<EventQueueReceiver>>,
+  session_key: Option<Res<SessionApiKey>>,
+  pending: Option<ResMut<PendingSession>>,
+) {
+  let consent = effective_consent();
+  let allowed = |event: &QueuedEvent| consent.allows_level(event.payload().level());
+
+  if let Some(receiver) = receiver {
+    let events = receiver
+      .try_iter()
+      .filter(|event| event.validate().is_ok() && allowed(event));
+    route_events(events, ig.event_sinks.as_deref(), &mut ig.buffered_events, &mut players);
+  }
+
+  ig.buffered_events.events.retain(allowed);
+  if let Some(sinks) = &ig.event_sinks {
+    sinks.retain(|_, event| consent.allows_level(event.level()));
+  }
+  for session in players.sessions.values_mut() {
+    session.events.retain(allowed);
+    if let Some(pending) = session.pending.as_mut() {
+      pending
+        .replay_events
+        .retain(|event| consent.allows_level(event.level()));
+    }
+  }
+  if let Some(mut pending) = pending {
+    pending
+      .replay_events
+      .retain(|event| consent.allows_level(event.level()));
+  }
+  for event in drain_pending_events().into_iter().filter(allowed) {
+    track_pending_event(event);
+  }
+
+  #[cfg(not(target_family = "wasm"))]
+  if !consent.allows_player_id()
+    && let Some(event_file) = &ig.event_file
+    && let Err(error) = event_file
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .clear()
+    && **ig.log_level <= IndigaugeLogLevel::Warn
+  {
+    warn!(message = "Failed to delete event file", ?error);
+  }
+
+  if consent.allows_session() {
+    return;
+  }
+
+  #[cfg(target_family = "wasm")]
+  indigauge_core::web::clear_offline_events(ig.config.game_name());
+
+  for (handle, session) in players.sessions.drain() {
+    unregister_session(handle);
+    if let Some(token) = session.token {
+      send_end_session(&mut ig, handle, &token);
+    }
+  }
+
+  if let Some(key) = session_key {
+    send_end_session(&mut ig, SessionHandle::DEFAULT, &key);
+  }
+
+  reset_session();
+  commands.remove_resource::<SessionApiKey>();
+  commands.remove_resource::<SessionStartRequest>();
+  commands.remove_resource::<PendingSession>();
+}
@@ -3,15 +3,17 @@ use std::time::Duration;
 use bevy::{prelude::*, time::common_conditions::on_timer};
 
 use crate::{
+  config::BevyIndigaugeEventSinks,
   event::{
     resources::{BufferedEvents, EventQueueReceiver},
     systems::*,
   },
-  session::resources::SessionApiKey,
+  session::resources::{PlayerSessions, SessionApiKey},
+  session::systems::{any_session_active, flush_player_sessions, maybe_flush_player_sessions},
 };
 
 pub(crate) mod resources;
-mod systems;
+pub(crate) mod systems;
 
 /// Plugin responsible for consuming queued events and flushing them periodically.
 pub struct EventsPlugin {
@@ -31,10 +33,18 @@ impl Plugin for EventsPlugin {
       Update,
       (
         handle_queued_events.run_if(resource_exists::<EventQueueReceiver>),
-        maybe_flush_events.run_if(resource_changed::<BufferedEvents>),
-        flush_events.run_if(on_timer(self.flush_interval)),
+        flush_event_sinks.run_if(resource_exists::<BevyIndigaugeEventSinks>),
+        (
+          maybe_flush_events.run_if(resource_changed::<BufferedEvents>),
+          flush_events.run_if(on_timer(self.flush_interval)),
+        )
+          .run_if(resource_exists::<SessionApiKey>),
+        (
+          maybe_flush_player_sessions.run_if(resource_changed::<PlayerSessions>),
+          flush_player_sessions.run_if(on_timer(self.flush_interval)),
+        ),
       )
-        .run_if(resource_exists::<SessionApiKey>),
+        .run_if(any_session_active),
     );
   }
 }
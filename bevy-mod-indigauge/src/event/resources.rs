@@ -1,9 +1,11 @@
+use std::fmt;
 use std::ops::{Deref, DerefMut};
 
 use bevy::prelude::*;
 use crossbeam_channel::Receiver;
 
 use indigauge_core::event::QueuedEvent;
+use indigauge_core::utils::Instant;
 
 /// Resource wrapper around the incoming queued-event channel receiver.
 #[derive(Resource)]
@@ -37,3 +39,41 @@ impl DerefMut for EventQueueReceiver {
 pub struct BufferedEvents {
   pub events: Vec<QueuedEvent>,
 }
+
+/// Outcome of an event batch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum FlushStatus {
+  /// No batch was sent yet.
+  #[default]
+  Idle,
+  /// The batch was sent and no response arrived yet.
+  Pending,
+  /// The ingest accepted the batch.
+  Sent,
+  /// The batch was handled locally, in [`IndigaugeMode::Dev`](indigauge_core::types::IndigaugeMode::Dev).
+  Local,
+  /// The batch could not be delivered.
+  Failed(String),
+}
+
+impl fmt::Display for FlushStatus {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Idle => f.write_str("idle"),
+      Self::Pending => f.write_str("pending"),
+      Self::Sent => f.write_str("sent"),
+      Self::Local => f.write_str("local (dev mode)"),
+      Self::Failed(reason) => write!(f, "failed: {reason}"),
+    }
+  }
+}
+
+/// Resource holding the outcome of the last event batch of the default session.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LastEventFlush {
+  /// When the batch was sent.
+  pub at: Option<Instant>,
+  /// Number of events in the batch.
+  pub count: usize,
+  pub status: FlushStatus,
+}
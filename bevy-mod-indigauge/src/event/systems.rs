@@ -1,13 +1,15 @@
 use bevy::prelude::*;
-use indigauge_core::types::IndigaugeLogLevel;
+use indigauge_core::event::QueuedEvent;
+use indigauge_core::state::{SessionHandle, untrack_pending_events};
+use indigauge_core::types::{IndigaugeLogLevel, IndigaugeMode};
 
 use crate::{
-  config::BevyIndigaugeLogLevel,
+  config::{BevyIndigaugeEventSinks, BevyIndigaugeLogLevel, BevyIndigaugeMode, BevySchemaRegistry},
   event::resources::{BufferedEvents, EventQueueReceiver},
-  session::resources::SessionApiKey,
+  session::resources::{PlayerSessions, SessionApiKey},
   utils::BevyIndigauge,
 };
-use bevy::log::error;
+use bevy::log::{error, warn};
 
 /// Flushes buffered events immediately when batch size threshold is reached.
 pub fn maybe_flush_events(mut ig: BevyIndigauge, session_key: Option<Res<SessionApiKey>>) {
@@ -23,21 +25,38 @@ pub fn flush_events(mut ig: BevyIndigauge, session_key: Option<Res<SessionApiKey
   if let Some(key) = session_key
     && ig.flush_events(&key) == 0
   {
-    ig.send_heartbeat(&key);
+    ig.send_heartbeat(SessionHandle::DEFAULT, &key);
   }
 }
 
-/// Moves events from the queue receiver into the in-memory batch buffer.
+/// Sends the events buffered for the registered sinks whose flush interval has elapsed.
+pub fn flush_event_sinks(
+  sinks: Res<BevyIndigaugeEventSinks>,
+  mut buffered_events: ResMut<BufferedEvents>,
+  mut players: ResMut<PlayerSessions>,
+) {
+  sinks.flush_due();
+  buffer_ingested(&sinks, &mut buffered_events, &mut players);
+}
+
+/// Moves events from the queue receiver into the in-memory batch buffer of their session.
 pub fn handle_queued_events(
   receiver: Res<EventQueueReceiver>,
   mut buffered_events: ResMut<BufferedEvents>,
+  mut players: ResMut<PlayerSessions>,
   log_level: Res<BevyIndigaugeLogLevel>,
+  mode: Res<BevyIndigaugeMode>,
+  schemas: Option<Res<BevySchemaRegistry>>,
+  sinks: Option<Res<BevyIndigaugeEventSinks>>,
 ) {
-  for event in receiver.try_iter() {
+  let mut events = Vec::new();
+  for mut event in receiver.try_iter() {
+    if let Some(schemas) = &schemas {
+      check_event_schema(schemas, &mut event, **mode == IndigaugeMode::Dev, &log_level);
+    }
+
     match event.validate() {
-      Ok(_) => {
-        buffered_events.events.push(event);
-      },
+      Ok(_) => events.push(event),
       Err(error) => {
         if **log_level <= IndigaugeLogLevel::Error {
           error!(message = "Invalid event", ?error);
@@ -45,4 +64,78 @@ pub fn handle_queued_events(
       },
     }
   }
+
+  route_events(events, sinks.as_deref(), &mut buffered_events, &mut players);
+}
+
+/// Dispatches validated events to the registered sinks, and buffers the events the ingest sink receives in the batch
+/// of their session. Without sinks, every event is buffered.
+pub(crate) fn route_events(
+  events: impl IntoIterator<Item = QueuedEvent>,
+  sinks: Option<&BevyIndigaugeEventSinks>,
+  buffered_events: &mut BufferedEvents,
+  players: &mut PlayerSessions,
+) {
+  let Some(sinks) = sinks else {
+    for event in events {
+      buffer_event(event, buffered_events, players);
+    }
+    return;
+  };
+
+  // Events the ingest filters out are never sent, so they must not be replayed after a crash either.
+  let mut rejected = Vec::new();
+  for event in events {
+    if event.session().is_default() && !sinks.ingest_accepts(&event) {
+      rejected.push(event.payload().idempotency_key().to_string());
+    }
+    sinks.dispatch(&event);
+  }
+  if !rejected.is_empty() {
+    untrack_pending_events(&rejected.iter().map(String::as_str).collect::<Vec<_>>());
+  }
+
+  buffer_ingested(sinks, buffered_events, players);
+}
+
+/// Buffers the events the ingest sink has received in the batch of their session.
+pub(crate) fn buffer_ingested(
+  sinks: &BevyIndigaugeEventSinks,
+  buffered_events: &mut BufferedEvents,
+  players: &mut PlayerSessions,
+) {
+  for event in sinks.take_ingested() {
+    buffer_event(event, buffered_events, players);
+  }
+}
+
+fn buffer_event(event: QueuedEvent, buffered_events: &mut BufferedEvents, players: &mut PlayerSessions) {
+  if event.session().is_default() {
+    buffered_events.events.push(event);
+  } else {
+    players.push(event);
+  }
+}
+
+/// Warns about metadata that does not match the registered schema (Dev mode) and strips nonconforming keys.
+fn check_event_schema(
+  schemas: &BevySchemaRegistry,
+  event: &mut QueuedEvent,
+  warn_violations: bool,
+  log_level: &BevyIndigaugeLogLevel,
+) {
+  let payload = event.payload_mut();
+
+  if warn_violations && **log_level <= IndigaugeLogLevel::Warn {
+    for violation in schemas.validate(payload.event_type(), payload.metadata()) {
+      warn!(message = "Event does not match its schema", event_type = payload.event_type(), %violation);
+    }
+  }
+
+  if schemas.strip_nonconforming {
+    let event_type = payload.event_type().to_string();
+    if let Some(metadata) = payload.metadata_mut() {
+      schemas.strip(&event_type, metadata);
+    }
+  }
 }
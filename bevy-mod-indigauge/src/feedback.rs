@@ -6,7 +6,7 @@ use crate::{feedback::resources::*, session::resources::SessionApiKey};
 
 pub mod components;
 #[cfg(feature = "feedback_egui")]
-mod egui;
+pub(crate) mod egui;
 #[cfg(all(feature = "feedback", not(feature = "feedback_egui")))]
 pub mod helpers;
 pub(crate) mod observers;
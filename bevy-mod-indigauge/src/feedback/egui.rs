@@ -252,7 +252,7 @@ fn styled_button(
   .inner
 }
 
-fn to_egui_color(color: Color) -> egui::Color32 {
+pub(crate) fn to_egui_color(color: Color) -> egui::Color32 {
   let [r, g, b, a] = color.to_srgba().to_u8_array();
   egui::Color32::from_rgba_unmultiplied(r, g, b, a)
 }
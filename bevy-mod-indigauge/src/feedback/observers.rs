@@ -1,4 +1,4 @@
-use std::{ops::Deref, time::Instant};
+use std::ops::Deref;
 
 use crate::{
   feedback::components::FeedbackPanel,
@@ -9,12 +9,15 @@ use crate::{
   utils::BevyIndigauge,
 };
 use bevy::log::error;
+use bevy::platform::time::Instant;
 use bevy::{
   prelude::*,
   render::view::screenshot::{Screenshot, ScreenshotCaptured},
   state::state::FreelyMutableState,
 };
 use image::{ColorType, ImageEncoder, codecs::png::PngEncoder};
+use indigauge_core::consent::effective_consent;
+use indigauge_core::redact::redact_text;
 use indigauge_core::types::{FeedbackPayload, IdResponse};
 
 #[cfg(all(feature = "feedback", not(feature = "feedback_egui")))]
@@ -127,14 +130,15 @@ pub(crate) fn submit_feedback(
   message: String,
 ) {
   if let Some(start_instant) = indigauge_core::state::get_session_start_instant() {
-    let elapsed_ms = Instant::now().duration_since(*start_instant).as_millis();
-
-    let msg = message
-      .replace("\r\n", "\n")
-      .replace('\r', "\n")
-      .replace("  ", " ")
-      .trim()
-      .to_string();
+    let elapsed_ms = Instant::now().duration_since(start_instant).as_millis();
+
+    let msg = redact_text(
+      message
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace("  ", " ")
+        .trim(),
+    );
 
     if msg.len().lt(&2) {
       form.error = Some("Feedback cannot be less than 2 characters".to_string());
@@ -168,6 +172,10 @@ fn maybe_take_screenshot(
     && let Ok(feedback_id) = trigger.event().deserialize_json::<IdResponse>()
   {
     commands.remove_resource::<TakeScreenshot>();
+    if !effective_consent().allows_screenshots() {
+      return;
+    }
+
     commands.spawn(Screenshot::primary_window()).observe(
       move |trigger: On<ScreenshotCaptured>, mut ig: BevyIndigauge, api_key: Res<SessionApiKey>| {
         let img = trigger.event().deref().clone();
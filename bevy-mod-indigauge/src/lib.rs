@@ -3,26 +3,58 @@
 pub(crate) mod utils;
 
 pub(crate) mod config;
+pub(crate) mod consent;
 pub(crate) mod event;
 pub(crate) mod http_runtime;
+#[cfg(feature = "feedback")]
+pub(crate) mod overlay;
 
 #[cfg(feature = "feedback")]
 pub(crate) mod feedback;
 
 pub mod plugin;
+pub(crate) mod remote_config;
 pub(crate) mod session;
 
 #[cfg(feature = "tracing")]
 pub mod tracing;
 
+/// Re-export of the core crate for `#[ig(crate = "bevy_mod_indigauge::indigauge_core")]` on derived events.
+#[cfg(feature = "derive")]
+pub use indigauge_core;
+
 pub mod prelude {
   pub use indigauge_core::prelude::{
-    IndigaugeLogLevel, IndigaugeMode, enqueue_ig_event, ig_debug, ig_error, ig_event, ig_info, ig_trace, ig_warn,
+    EventNamingPolicy, IndigaugeEvent, IndigaugeLogLevel, IndigaugeMode, MetadataSchema, SessionHandle,
+    enqueue_ig_event, enqueue_ig_event_for, ig_debug, ig_debug_for, ig_emit, ig_emit_for, ig_error, ig_error_for,
+    ig_event, ig_event_for, ig_info, ig_info_for, ig_trace, ig_trace_for, ig_warn, ig_warn_for,
     validate_event_type_compile_time,
   };
 
-  pub use crate::config::{BevyIndigaugeLogLevel, BevyIndigaugeMode};
+  #[cfg(not(target_family = "wasm"))]
+  pub use crate::config::BevyIndigaugeEventFile;
+  #[cfg(feature = "tracing")]
+  pub use crate::config::BevyIndigaugeLogFilter;
+  #[cfg(feature = "otlp")]
+  pub use crate::config::BevyIndigaugeOtlp;
+  pub use crate::config::{
+    BevyIndigaugeEventSinks, BevyIndigaugeLogLevel, BevyIndigaugeMode, BevySchemaRegistry, BevyTelemetryConsent,
+    CONSOLE_SINK, DEV_OVERLAY_SINK, EVENT_FILE_SINK, INGEST_SINK,
+  };
+  pub use crate::consent::SetTelemetryConsentEvent;
+  pub use crate::event::resources::{FlushStatus, LastEventFlush};
+  pub use crate::remote_config::{BevyRemoteConfig, RemoteConfigChangedEvent};
+  pub use crate::{ig_flag, ig_variant};
+  pub use indigauge_core::console::{ConsolePrinter, ConsoleVerbosity};
+  pub use indigauge_core::ndjson::NdjsonConfig;
+  #[cfg(feature = "otlp")]
+  pub use indigauge_core::otlp::{OtlpConfig, OtlpMode};
+  pub use indigauge_core::redact::Redactor;
+  pub use indigauge_core::remote_config::RemoteConfig;
+  pub use indigauge_core::schema::{SchemaRegistry, SchemaViolation};
+  pub use indigauge_core::sink::{EventSink, SinkOptions};
   pub use indigauge_core::state::enqueue;
+  pub use indigauge_core::types::TelemetryConsent;
 
   #[cfg(feature = "feedback")]
   pub use crate::feedback::observers::{switch_state_on_feedback_despawn, switch_state_on_feedback_spawn};
@@ -31,11 +63,19 @@ pub mod prelude {
     resources::{FeedbackKeyCodeToggle, FeedbackPanelProps, FeedbackPanelStyles},
     types::{FeedbackCategory, FeedbackSpawnPosition},
   };
-  pub use crate::plugin::IndigaugePlugin;
+  #[cfg(feature = "feedback")]
+  pub use crate::overlay::resources::{
+    DEV_OVERLAY_MAX_EVENTS, DevOverlayEntry, DevOverlayKeyCodeToggle, DevOverlayState,
+  };
+  pub use crate::plugin::{IndigaugePlugin, indigauge_log_layer};
   pub use crate::session::observers::switch_state_after_session_init;
   pub use crate::session::systems::{end_session, start_default_session};
   pub use crate::session::{
-    events::{IndigaugeInitDoneEvent, StartSessionEvent},
-    resources::EmptySessionMeta,
+    components::PlayerSession,
+    events::{
+      IdentifyPlayerEvent, IndigaugeInitDoneEvent, ResetPlayerIdEvent, SessionOnlineEvent, SessionRenewedEvent,
+      StartSessionEvent,
+    },
+    resources::{EmptySessionMeta, PendingSession, PlayerSessions, SessionApiKey},
   };
 }
@@ -0,0 +1,182 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use indigauge_core::console::ConsolePrinter;
+use indigauge_core::sink::SinkOptions;
+use indigauge_core::state::dropped_event_count;
+use indigauge_core::types::{EventPayload, IndigaugeLogLevel, IndigaugeMode};
+use serde::Serialize;
+
+use crate::{
+  config::{BevyIndigaugeEventSinks, BevyIndigaugeMode, BevyTelemetryConsent, DEV_OVERLAY_SINK},
+  event::resources::{BufferedEvents, EventQueueReceiver, FlushStatus, LastEventFlush},
+  feedback::resources::FeedbackPanelStyles,
+  overlay::resources::*,
+  session::resources::{PendingSession, PlayerSessions, SessionApiKey},
+};
+
+#[cfg(feature = "feedback_egui")]
+mod egui;
+pub mod resources;
+#[cfg(not(feature = "feedback_egui"))]
+mod systems;
+
+/// Plugin that shows the live event stream and the SDK state in a developer overlay.
+pub(crate) struct DevOverlayPlugin<M> {
+  meta: PhantomData<M>,
+}
+
+impl<M> Default for DevOverlayPlugin<M> {
+  fn default() -> Self {
+    Self { meta: PhantomData }
+  }
+}
+
+impl<M> Plugin for DevOverlayPlugin<M>
+where
+  M: Resource + Serialize,
+{
+  fn build(&self, app: &mut App) {
+    let feed = DevOverlayFeed::default();
+    if let Some(sinks) = app.world().get_resource::<BevyIndigaugeEventSinks>() {
+      let received = feed.clone();
+      sinks.register(
+        DEV_OVERLAY_SINK,
+        move |session, events: &[EventPayload]| received.push(session, events),
+        SinkOptions::new(),
+      );
+    }
+
+    app
+      .init_resource::<DevOverlayKeyCodeToggle>()
+      .init_resource::<DevOverlayState>()
+      .insert_resource(feed)
+      .add_systems(
+        Update,
+        (
+          collect_overlay_events,
+          toggle_overlay_with_key.run_if(resource_exists::<ButtonInput<KeyCode>>),
+          update_overlay_metadata::<M>.run_if(resource_exists_and_changed::<M>),
+        ),
+      );
+
+    #[cfg(not(feature = "feedback_egui"))]
+    app.add_systems(
+      Update,
+      (
+        systems::handle_namespace_buttons,
+        systems::sync_overlay_panel.run_if(resource_changed::<DevOverlayState>),
+        systems::update_overlay_panel,
+      )
+        .chain()
+        .after(collect_overlay_events),
+    );
+
+    #[cfg(feature = "feedback_egui")]
+    app.add_systems(Update, egui::draw_dev_overlay.after(collect_overlay_events));
+  }
+}
+
+/// Moves the events received by the overlay sink into the [`DevOverlayState`].
+fn collect_overlay_events(feed: Res<DevOverlayFeed>, mut state: ResMut<DevOverlayState>) {
+  let entries = feed.take();
+  if !entries.is_empty() {
+    entries.into_iter().for_each(|entry| state.push(entry));
+  }
+}
+
+/// Toggles the overlay via the configured keybind.
+fn toggle_overlay_with_key(
+  keys: Res<ButtonInput<KeyCode>>,
+  toggle_button: Res<DevOverlayKeyCodeToggle>,
+  mut state: ResMut<DevOverlayState>,
+) {
+  if keys.just_pressed(toggle_button.0) {
+    state.visible = !state.visible;
+  }
+}
+
+/// Mirrors the session metadata resource in the [`DevOverlayState`].
+fn update_overlay_metadata<M>(metadata: Res<M>, mut state: ResMut<DevOverlayState>)
+where
+  M: Resource + Serialize,
+{
+  state.set_session_metadata(serde_json::to_value(&*metadata).ok());
+}
+
+/// SDK state shown next to the event stream.
+#[derive(SystemParam)]
+pub(crate) struct DevOverlayStats<'w> {
+  mode: Res<'w, BevyIndigaugeMode>,
+  consent: Res<'w, BevyTelemetryConsent>,
+  receiver: Option<Res<'w, EventQueueReceiver>>,
+  buffered_events: Res<'w, BufferedEvents>,
+  last_flush: Res<'w, LastEventFlush>,
+  session_key: Option<Res<'w, SessionApiKey>>,
+  pending_session: Option<Res<'w, PendingSession>>,
+  players: Res<'w, PlayerSessions>,
+}
+
+impl DevOverlayStats<'_> {
+  /// Returns the rows of the overlay header as label and value.
+  pub(crate) fn rows(&self) -> Vec<(&'static str, String)> {
+    let mode = match **self.mode {
+      IndigaugeMode::Live => "live",
+      IndigaugeMode::Dev => "dev",
+      IndigaugeMode::Disabled => "disabled",
+    };
+    let session = match (&self.session_key, &self.pending_session) {
+      (Some(key), _) => format!("active ({}…)", key.0.chars().take(8).collect::<String>()),
+      (None, Some(pending)) => format!("starting ({} failed attempts)", pending.failed_attempts),
+      (None, None) => "none".to_string(),
+    };
+    let last_flush = match (self.last_flush.at, &self.last_flush.status) {
+      (Some(at), status) => {
+        format!("{status}, {} events {:.0}s ago", self.last_flush.count, at.elapsed().as_secs_f32())
+      },
+      (None, status) => status.to_string(),
+    };
+
+    vec![
+      ("Mode", mode.to_string()),
+      ("Session", session),
+      ("Player sessions", self.players.sessions.len().to_string()),
+      ("Consent", self.consent.as_str().to_string()),
+      (
+        "Queue",
+        format!(
+          "{} queued, {} buffered",
+          self.receiver.as_ref().map_or(0, |receiver| receiver.len()),
+          self.buffered_events.events.len()
+        ),
+      ),
+      ("Dropped", dropped_event_count().to_string()),
+      ("Last flush", last_flush),
+    ]
+  }
+
+  /// Returns `true` if the last flush failed.
+  pub(crate) fn flush_failed(&self) -> bool {
+    matches!(self.last_flush.status, FlushStatus::Failed(_))
+  }
+}
+
+/// Printer formatting the overlay lines, without colour codes.
+const EVENT_LINE_PRINTER: ConsolePrinter = ConsolePrinter::plain();
+
+/// Formats an event as one line: elapsed time, level, session, type, compact metadata and location.
+pub(crate) fn event_line(entry: &DevOverlayEntry) -> String {
+  EVENT_LINE_PRINTER.format(entry.session, &entry.event)
+}
+
+/// Returns the colour of an event level.
+pub(crate) fn level_color(level: IndigaugeLogLevel, styles: &FeedbackPanelStyles) -> Color {
+  match level {
+    IndigaugeLogLevel::Trace | IndigaugeLogLevel::Debug => styles.text_secondary,
+    IndigaugeLogLevel::Info => styles.text_primary,
+    IndigaugeLogLevel::Warn => styles.warning,
+    IndigaugeLogLevel::Error | IndigaugeLogLevel::Fatal => styles.error,
+    IndigaugeLogLevel::Silent => styles.border,
+  }
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+use crate::{
+  feedback::{egui::to_egui_color, resources::FeedbackPanelStyles},
+  overlay::{DevOverlayStats, event_line, level_color, resources::DevOverlayState},
+};
+
+/// Renders the egui developer overlay each frame while it is visible.
+pub fn draw_dev_overlay(
+  mut egui_contexts: EguiContexts,
+  mut state: ResMut<DevOverlayState>,
+  stats: DevOverlayStats,
+  styles: Res<FeedbackPanelStyles>,
+) {
+  if !state.visible {
+    return;
+  }
+
+  let Ok(ctx) = egui_contexts.ctx_mut() else {
+    return;
+  };
+
+  let mut open = true;
+  let mut toggled = Vec::new();
+
+  egui::Window::new("Indigauge")
+    .id(egui::Id::new("indigauge_dev_overlay"))
+    .open(&mut open)
+    .anchor(egui::Align2::RIGHT_TOP, [-12.0, 12.0])
+    .default_width(520.0)
+    .show(ctx, |ui| {
+      let stats_color = if stats.flush_failed() {
+        styles.error
+      } else {
+        styles.text_secondary
+      };
+      egui::Grid::new("indigauge_dev_overlay_stats").show(ui, |ui| {
+        for (label, value) in stats.rows() {
+          ui.label(label);
+          ui.colored_label(to_egui_color(stats_color), value);
+          ui.end_row();
+        }
+      });
+
+      if let Some(metadata) = state.session_metadata() {
+        ui.collapsing("Session metadata", |ui| {
+          ui.monospace(serde_json::to_string_pretty(metadata).unwrap_or_default());
+        });
+      }
+
+      ui.separator();
+      ui.horizontal_wrapped(|ui| {
+        for (namespace, shown) in state.namespaces() {
+          let mut checked = shown;
+          if ui.checkbox(&mut checked, namespace).changed() {
+            toggled.push(namespace.to_string());
+          }
+        }
+      });
+
+      ui.separator();
+      egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+        for entry in state.entries().rev() {
+          ui.colored_label(
+            to_egui_color(level_color(entry.event.level(), &styles)),
+            egui::RichText::new(event_line(entry)).monospace(),
+          );
+        }
+      });
+    });
+
+  for namespace in toggled {
+    state.toggle_namespace(&namespace);
+  }
+  if !open {
+    state.visible = false;
+  }
+}
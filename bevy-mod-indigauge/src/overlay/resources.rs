@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use indigauge_core::state::SessionHandle;
+use indigauge_core::types::EventPayload;
+use serde_json::Value;
+
+/// Maximum number of events kept by the [`DevOverlayState`].
+pub const DEV_OVERLAY_MAX_EVENTS: usize = 200;
+
+/// Key binding resource used to toggle the developer overlay.
+#[derive(Resource, Debug)]
+pub struct DevOverlayKeyCodeToggle(pub KeyCode);
+
+impl Default for DevOverlayKeyCodeToggle {
+  fn default() -> Self {
+    Self(KeyCode::F3)
+  }
+}
+
+/// An event shown in the developer overlay.
+#[derive(Clone, Debug)]
+pub struct DevOverlayEntry {
+  pub session: SessionHandle,
+  pub event: EventPayload,
+}
+
+impl DevOverlayEntry {
+  /// Returns the first segment of the event type, e.g. `ui` for `ui.menu.open`.
+  pub fn namespace(&self) -> &str {
+    let event_type = self.event.event_type();
+    event_type
+      .split_once('.')
+      .map_or(event_type, |(namespace, _)| namespace)
+  }
+}
+
+/// Resource holding what the developer overlay shows: the latest events and the namespace filters.
+#[derive(Resource, Debug, Default)]
+pub struct DevOverlayState {
+  /// Whether the overlay is shown.
+  pub visible: bool,
+  entries: VecDeque<DevOverlayEntry>,
+  /// Namespaces seen so far and whether their events are shown.
+  namespaces: BTreeMap<String, bool>,
+  session_metadata: Option<Value>,
+}
+
+impl DevOverlayState {
+  /// Adds an event, dropping the oldest one once [`DEV_OVERLAY_MAX_EVENTS`] events are kept.
+  pub fn push(&mut self, entry: DevOverlayEntry) {
+    if !self.namespaces.contains_key(entry.namespace()) {
+      self.namespaces.insert(entry.namespace().to_string(), true);
+    }
+    if self.entries.len() == DEV_OVERLAY_MAX_EVENTS {
+      self.entries.pop_front();
+    }
+    self.entries.push_back(entry);
+  }
+
+  /// Returns the events of the shown namespaces, oldest first.
+  pub fn entries(&self) -> impl DoubleEndedIterator<Item = &DevOverlayEntry> {
+    self
+      .entries
+      .iter()
+      .filter(|entry| self.namespaces.get(entry.namespace()).copied().unwrap_or(true))
+  }
+
+  /// Returns the namespaces seen so far and whether their events are shown.
+  pub fn namespaces(&self) -> impl Iterator<Item = (&str, bool)> {
+    self
+      .namespaces
+      .iter()
+      .map(|(namespace, shown)| (namespace.as_str(), *shown))
+  }
+
+  /// Shows or hides the events of a namespace.
+  pub fn set_namespace_shown(&mut self, namespace: &str, shown: bool) {
+    self.namespaces.insert(namespace.to_string(), shown);
+  }
+
+  /// Toggles whether the events of a namespace are shown.
+  pub fn toggle_namespace(&mut self, namespace: &str) {
+    let shown = self.namespaces.get(namespace).copied().unwrap_or(true);
+    self.set_namespace_shown(namespace, !shown);
+  }
+
+  /// Returns the session metadata resource as JSON, if it holds any data.
+  pub fn session_metadata(&self) -> Option<&Value> {
+    self.session_metadata.as_ref()
+  }
+
+  pub(crate) fn set_session_metadata(&mut self, metadata: Option<Value>) {
+    self.session_metadata = metadata.filter(|metadata| !metadata.is_null());
+  }
+
+  /// Removes all events.
+  pub fn clear(&mut self) {
+    self.entries.clear();
+  }
+}
+
+/// Events received by the overlay sink, moved into the [`DevOverlayState`] every frame.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct DevOverlayFeed(Arc<Mutex<Vec<DevOverlayEntry>>>);
+
+impl DevOverlayFeed {
+  pub(crate) fn push(&self, session: SessionHandle, events: &[EventPayload]) {
+    self
+      .0
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .extend(events.iter().map(|event| DevOverlayEntry {
+        session,
+        event: event.clone(),
+      }));
+  }
+
+  pub(crate) fn take(&self) -> Vec<DevOverlayEntry> {
+    std::mem::take(&mut *self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+  }
+}
@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+
+use crate::{
+  feedback::resources::FeedbackPanelStyles,
+  overlay::{DevOverlayStats, event_line, level_color, resources::DevOverlayState},
+};
+
+/// Number of events listed by the Bevy UI overlay, newest first.
+const LISTED_EVENTS: usize = 30;
+
+/// Marker component for the root node of the developer overlay.
+#[derive(Component)]
+pub struct DevOverlayPanel;
+
+/// Marker component for the SDK state text of the developer overlay.
+#[derive(Component)]
+pub struct DevOverlayStatsText;
+
+/// Marker component for the row of namespace filter buttons.
+#[derive(Component)]
+pub struct DevOverlayNamespaces;
+
+/// Button that shows or hides the events of a namespace.
+#[derive(Component)]
+pub struct DevOverlayNamespaceButton(String);
+
+/// Marker component for the event list of the developer overlay.
+#[derive(Component)]
+pub struct DevOverlayEventList;
+
+/// Spawns or despawns the overlay panel to match the `visible` flag.
+pub fn sync_overlay_panel(
+  mut commands: Commands,
+  state: Res<DevOverlayState>,
+  styles: Res<FeedbackPanelStyles>,
+  panel_query: Query<Entity, With<DevOverlayPanel>>,
+) {
+  match (state.visible, panel_query.single()) {
+    (true, Err(_)) => spawn_overlay_panel(&mut commands, &styles),
+    (false, Ok(panel)) => {
+      commands.entity(panel).despawn();
+    },
+    _ => {},
+  }
+}
+
+fn spawn_overlay_panel(commands: &mut Commands, styles: &FeedbackPanelStyles) {
+  let panel = commands
+    .spawn((
+      DevOverlayPanel,
+      Node {
+        position_type: PositionType::Absolute,
+        top: Val::Px(12.0),
+        right: Val::Px(12.0),
+        width: Val::Px(520.0),
+        max_height: Val::Percent(90.0),
+        padding: UiRect::all(Val::Px(12.0)),
+        border: UiRect::all(Val::Px(1.0)),
+        row_gap: Val::Px(8.0),
+        flex_direction: FlexDirection::Column,
+        overflow: Overflow::clip(),
+        ..default()
+      },
+      BackgroundColor(styles.background.with_alpha(0.92)),
+      BorderColor::all(styles.border),
+      GlobalZIndex(i32::MAX - 1),
+    ))
+    .id();
+
+  commands.spawn((
+    ChildOf(panel),
+    Text::new("Indigauge"),
+    TextFont {
+      font_size: FontSize::Px(16.),
+      ..default()
+    },
+    TextColor(styles.primary),
+  ));
+  commands.spawn((
+    ChildOf(panel),
+    DevOverlayStatsText,
+    Text::default(),
+    TextFont {
+      font_size: FontSize::Px(12.),
+      ..default()
+    },
+    TextColor(styles.text_secondary),
+  ));
+  commands.spawn((
+    ChildOf(panel),
+    DevOverlayNamespaces,
+    Node {
+      flex_direction: FlexDirection::Row,
+      flex_wrap: FlexWrap::Wrap,
+      column_gap: Val::Px(4.0),
+      row_gap: Val::Px(4.0),
+      ..default()
+    },
+  ));
+  commands.spawn((
+    ChildOf(panel),
+    DevOverlayEventList,
+    Node {
+      flex_direction: FlexDirection::Column,
+      ..default()
+    },
+  ));
+}
+
+/// Refreshes the SDK state every frame, and the namespace filters and event list when the overlay state changed.
+pub fn update_overlay_panel(
+  mut commands: Commands,
+  state: Res<DevOverlayState>,
+  stats: DevOverlayStats,
+  styles: Res<FeedbackPanelStyles>,
+  mut stats_query: Query<(&mut Text, &mut TextColor), With<DevOverlayStatsText>>,
+  namespaces_query: Query<(Entity, Ref<DevOverlayNamespaces>)>,
+  events_query: Query<Entity, With<DevOverlayEventList>>,
+) {
+  let Ok((mut text, mut color)) = stats_query.single_mut() else {
+    return;
+  };
+  let mut rows = stats
+    .rows()
+    .into_iter()
+    .map(|(label, value)| format!("{label}: {value}"))
+    .collect::<Vec<_>>();
+  if let Some(metadata) = state.session_metadata() {
+    rows.push(format!("Session metadata: {metadata}"));
+  }
+  **text = rows.join("\n");
+  color.0 = if stats.flush_failed() {
+    styles.error
+  } else {
+    styles.text_secondary
+  };
+
+  let (Ok((namespaces, added)), Ok(events)) = (namespaces_query.single(), events_query.single()) else {
+    return;
+  };
+  if !state.is_changed() && !added.is_added() {
+    return;
+  }
+
+  commands.entity(namespaces).despawn_related::<Children>();
+  for (namespace, shown) in state.namespaces() {
+    let button = commands
+      .spawn((
+        ChildOf(namespaces),
+        Button,
+        DevOverlayNamespaceButton(namespace.to_string()),
+        Node {
+          padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+          border: UiRect::all(Val::Px(1.0)),
+          ..default()
+        },
+        BackgroundColor(if shown { styles.surface } else { Color::NONE }),
+        BorderColor::all(styles.border),
+      ))
+      .id();
+    commands.spawn((
+      ChildOf(button),
+      Text::new(namespace),
+      TextFont {
+        font_size: FontSize::Px(12.),
+        ..default()
+      },
+      TextColor(if shown { styles.text_primary } else { styles.border }),
+    ));
+  }
+
+  commands.entity(events).despawn_related::<Children>();
+  for entry in state.entries().rev().take(LISTED_EVENTS) {
+    commands.spawn((
+      ChildOf(events),
+      Text::new(event_line(entry)),
+      TextFont {
+        font_size: FontSize::Px(12.),
+        ..default()
+      },
+      TextColor(level_color(entry.event.level(), &styles)),
+    ));
+  }
+}
+
+/// Shows or hides the events of a namespace when its button is pressed.
+pub fn handle_namespace_buttons(
+  buttons: Query<(&Interaction, &DevOverlayNamespaceButton), Changed<Interaction>>,
+  mut state: ResMut<DevOverlayState>,
+) {
+  for (interaction, button) in &buttons {
+    if *interaction == Interaction::Pressed {
+      state.toggle_namespace(&button.0);
+    }
+  }
+}
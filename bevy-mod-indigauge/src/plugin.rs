@@ -1,21 +1,70 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Mutex, time::Duration};
 
+use bevy::log::BoxedLayer;
 use bevy::prelude::*;
-use indigauge_core::state::{get_global_tx, init};
+use indigauge_core::consent::{load_consent, set_consent, set_consent_required};
+use indigauge_core::console::{ConsolePrinter, ConsoleVerbosity};
+use indigauge_core::event::{EventNamingPolicy, set_event_naming_policy};
+#[cfg(feature = "tracing")]
+use indigauge_core::filter::LevelDirectives;
+#[cfg(not(target_family = "wasm"))]
+use indigauge_core::ndjson::{NdjsonConfig, NdjsonWriter};
+#[cfg(feature = "otlp")]
+use indigauge_core::otlp::{OtlpConfig, OtlpExporter};
+use indigauge_core::redact::{Redactor, set_redactor};
+use indigauge_core::remote_config::{RemoteConfig, load_remote_config};
+use indigauge_core::schema::SchemaRegistry;
+use indigauge_core::sink::{EventSink, EventSinks, SinkOptions};
+use indigauge_core::state::{get_global_tx, init, set_min_event_level};
 use indigauge_core::types::{IndigaugeLogLevel, IndigaugeMode};
 use serde::Serialize;
 
+#[cfg(doc)]
+use crate::consent::SetTelemetryConsentEvent;
 use crate::{
   config::*,
+  consent::ConsentPlugin,
   event::{
     EventsPlugin,
-    resources::{BufferedEvents, EventQueueReceiver},
+    resources::{BufferedEvents, EventQueueReceiver, LastEventFlush},
   },
   http_runtime::ReqwestPlugin,
-  session::{SessionPlugin, resources::EmptySessionMeta},
+  remote_config::{BevyRemoteConfig, RemoteConfigPlugin},
+  session::{
+    SessionPlugin,
+    resources::{EmptySessionMeta, PlayerIdProvider},
+  },
 };
 use bevy::log::{info, warn};
 
+/// Tracing layer for `LogPlugin { custom_layer: indigauge_log_layer, .. }` that forwards Bevy logs to Indigauge.
+///
+/// Applies the `INDIGAUGE_LOG` directives and inserts a `BevyIndigaugeLogFilter` to change them at runtime. Nothing
+/// is forwarded when the [`IndigaugePlugin`] runs in [`IndigaugeMode::Disabled`]. Without the `tracing` feature, no
+/// layer is added.
+pub fn indigauge_log_layer(app: &mut App) -> Option<BoxedLayer> {
+  #[cfg(feature = "tracing")]
+  {
+    let disabled = app
+      .world()
+      .get_resource::<BevyIndigaugeMode>()
+      .is_some_and(|mode| **mode == IndigaugeMode::Disabled);
+    if disabled {
+      return None;
+    }
+
+    let layer = crate::tracing::default_bevy_indigauge_layer().with_env_directives();
+    app.insert_resource(BevyIndigaugeLogFilter(layer.filter_handle()));
+    Some(Box::new(layer))
+  }
+
+  #[cfg(not(feature = "tracing"))]
+  {
+    let _ = app;
+    None
+  }
+}
+
 /// Main Bevy plugin entrypoint for Indigauge telemetry and feedback features.
 pub struct IndigaugePlugin<Meta = EmptySessionMeta> {
   public_key: String,
@@ -24,6 +73,28 @@ pub struct IndigaugePlugin<Meta = EmptySessionMeta> {
   game_version: String,
   log_level: BevyIndigaugeLogLevel,
   mode: BevyIndigaugeMode,
+  api_base: Option<String>,
+  shutdown_timeout: Option<Duration>,
+  retry_backoff: Option<(Duration, Duration)>,
+  consent_required: bool,
+  redactor: Option<Redactor>,
+  player_id_provider: Option<PlayerIdProvider>,
+  schema_registry: Option<SchemaRegistry>,
+  strip_nonconforming_metadata: bool,
+  event_naming_policy: Option<EventNamingPolicy>,
+  min_event_level: Option<IndigaugeLogLevel>,
+  #[cfg(feature = "tracing")]
+  log_directives: Option<LevelDirectives>,
+  #[cfg(not(target_family = "wasm"))]
+  event_file: Option<NdjsonConfig>,
+  #[cfg(feature = "otlp")]
+  otlp: Option<OtlpConfig>,
+  sinks: Mutex<EventSinks>,
+  sink_options: Vec<(String, SinkOptions)>,
+  console_output: Option<ConsolePrinter>,
+  remote_config: Option<RemoteConfig>,
+  #[cfg(feature = "feedback")]
+  dev_overlay: bool,
   meta: PhantomData<Meta>,
 }
 
@@ -39,6 +110,170 @@ impl<M> IndigaugePlugin<M> {
     self.mode = BevyIndigaugeMode(mode);
     self
   }
+
+  /// Overrides the ingest API base URL (defaults to `INDIGAUGE_API_BASE` or the production ingest).
+  pub fn api_base(mut self, api_base: impl Into<String>) -> Self {
+    self.api_base = Some(api_base.into());
+    self
+  }
+
+  /// Sets the exponential backoff bounds used when retrying a failed session start.
+  pub fn retry_backoff(mut self, initial: Duration, max: Duration) -> Self {
+    self.retry_backoff = Some((initial, max));
+    self
+  }
+
+  /// Sets how long the final flush on exit may block before remaining events are dropped.
+  pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+    self.shutdown_timeout = Some(timeout);
+    self
+  }
+
+  /// Denies all telemetry until the player gives consent through [`SetTelemetryConsentEvent`].
+  ///
+  /// **Without this, telemetry is collected and the player id is persisted while the consent is still `Unknown`**,
+  /// as if the player had granted it. Use it where the player must opt in first, e.g. under the GDPR.
+  pub fn require_consent(mut self) -> Self {
+    self.consent_required = true;
+    self
+  }
+
+  /// Redacts event metadata, tracing fields, crash messages and feedback text before they are sent.
+  ///
+  /// Use [`Redactor::standard`] for the built-in rules.
+  pub fn redactor(mut self, redactor: Redactor) -> Self {
+    self.redactor = Some(redactor);
+    self
+  }
+
+  /// Uses a game-provided player id (e.g. a platform account id) instead of the generated one.
+  ///
+  /// The provider is called for every session start; returning `None` falls back to the generated id.
+  pub fn player_id_provider(mut self, provider: impl Fn() -> Option<String> + Send + Sync + 'static) -> Self {
+    self.player_id_provider = Some(PlayerIdProvider::new(provider));
+    self
+  }
+
+  /// Checks event metadata against the registered schemas.
+  ///
+  /// In [`IndigaugeMode::Dev`], unknown events, missing keys and type mismatches are logged as warnings.
+  pub fn schema_registry(mut self, registry: SchemaRegistry) -> Self {
+    self.schema_registry = Some(registry);
+    self
+  }
+
+  /// Removes metadata keys that are unknown or have the wrong type according to the [`schema_registry`] before
+  /// events are sent, e.g. for release builds.
+  ///
+  /// [`schema_registry`]: Self::schema_registry
+  pub fn strip_nonconforming_metadata(mut self) -> Self {
+    self.strip_nonconforming_metadata = true;
+    self
+  }
+
+  /// Validates event types with a custom naming policy, e.g. [`EventNamingPolicy::STRICT`].
+  ///
+  /// Events whose type does not match are dropped. The `ig_*` macros still check literals against
+  /// [`EventNamingPolicy::DEFAULT`] at compile time.
+  pub fn event_naming_policy(mut self, policy: EventNamingPolicy) -> Self {
+    self.event_naming_policy = Some(policy);
+    self
+  }
+
+  /// Drops events below the given level when they are queued, e.g. to keep `ig_trace!` and `ig_debug!` calls in
+  /// release builds without sending them. All levels are sent by default.
+  pub fn min_event_level(mut self, level: IndigaugeLogLevel) -> Self {
+    self.min_event_level = Some(level);
+    self
+  }
+
+  /// Sets the per-target level directives of the [`indigauge_log_layer`], e.g. `my_game::ai=warn,bevy_render=off`.
+  ///
+  /// Valid directives in the `INDIGAUGE_LOG` environment variable take precedence, so playtesters can still change
+  /// them without a rebuild.
+  #[cfg(feature = "tracing")]
+  pub fn log_directives(mut self, directives: LevelDirectives) -> Self {
+    self.log_directives = Some(directives);
+    self
+  }
+
+  /// In [`IndigaugeMode::Dev`], also writes every event, session start and end, metadata update and feedback to a
+  /// newline-delimited JSON file, e.g. to analyse playtests locally with `jq` or `pandas`.
+  ///
+  /// The file is rotated by size, see [`NdjsonConfig`]. Nothing is written without consent to telemetry, and the
+  /// files are deleted when the player revokes consent. Not available on the web.
+  #[cfg(not(target_family = "wasm"))]
+  pub fn event_file(mut self, config: NdjsonConfig) -> Self {
+    self.event_file = Some(config);
+    self
+  }
+
+  /// In [`IndigaugeMode::Live`], also exports event batches as OpenTelemetry log records to an OTLP/HTTP collector,
+  /// or only exports them with [`OtlpMode::Replace`](indigauge_core::otlp::OtlpMode::Replace).
+  #[cfg(feature = "otlp")]
+  pub fn otlp(mut self, config: OtlpConfig) -> Self {
+    self.otlp = Some(config);
+    self
+  }
+
+  /// Also sends the events that pass the filter of `options` to a sink, e.g. an [`NdjsonWriter`], a closure or a
+  /// custom [`EventSink`], in batches of their own size and interval. Registering a name twice replaces the sink.
+  ///
+  /// Sinks receive events in [`IndigaugeMode::Live`] and [`IndigaugeMode::Dev`], next to the built-in sinks listed
+  /// on [`BevyIndigaugeEventSinks`].
+  pub fn sink(mut self, name: impl Into<String>, sink: impl EventSink, options: SinkOptions) -> Self {
+    self
+      .sinks
+      .get_mut()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .register(name, sink, options);
+    self
+  }
+
+  /// Replaces the [`SinkOptions`] of the sink registered under `name`, e.g. to only send warnings to the Indigauge
+  /// ingest with [`INGEST_SINK`], or only write some namespaces to the event file with [`EVENT_FILE_SINK`].
+  ///
+  /// Applies to the built-in sinks and to the sinks added with [`Self::sink`]. A sink that is not registered in the
+  /// current mode is ignored with a warning.
+  pub fn sink_options(mut self, name: impl Into<String>, options: SinkOptions) -> Self {
+    self.sink_options.push((name.into(), options));
+    self
+  }
+
+  /// In [`IndigaugeMode::Dev`], prints each validated event to the console with the given printer, e.g. with
+  /// [`ConsoleVerbosity::Verbose`], or nothing with [`ConsoleVerbosity::Off`].
+  ///
+  /// By default, events are printed with [`ConsolePrinter::new`] if the log level is [`IndigaugeLogLevel::Info`] or
+  /// more verbose.
+  pub fn console_output(mut self, printer: ConsolePrinter) -> Self {
+    self.console_output = Some(printer);
+    self
+  }
+
+  /// Adds a [`BevyRemoteConfig`] resource with these defaults, overridden by the config cached from the last run
+  /// and, in [`IndigaugeMode::Live`], by the config fetched at each session start. The resource is also added in
+  /// [`IndigaugeMode::Disabled`], where nothing is fetched.
+  ///
+  /// Fetched changes trigger a [`RemoteConfigChangedEvent`], and evaluating a feature flag with
+  /// [`BevyRemoteConfig::flag`] or [`BevyRemoteConfig::variant`] records a `flag.exposure` event once per session.
+  ///
+  /// [`RemoteConfigChangedEvent`]: crate::remote_config::RemoteConfigChangedEvent
+  pub fn remote_config(mut self, defaults: RemoteConfig) -> Self {
+    self.remote_config = Some(defaults);
+    self
+  }
+
+  /// Adds a developer overlay, toggled with [`DevOverlayKeyCodeToggle`] (`F3` by default), showing the live event
+  /// stream, the queue, dropped events, the last flush, the session and its metadata, with filters by namespace.
+  ///
+  /// Rendered with `bevy_egui` under the `feedback_egui` feature, and with Bevy UI otherwise.
+  ///
+  /// [`DevOverlayKeyCodeToggle`]: crate::overlay::resources::DevOverlayKeyCodeToggle
+  #[cfg(feature = "feedback")]
+  pub fn dev_overlay(mut self) -> Self {
+    self.dev_overlay = true;
+    self
+  }
 }
 
 impl<M> IndigaugePlugin<M>
@@ -67,6 +302,28 @@ where
       game_version: env!("CARGO_PKG_VERSION").to_string(),
       log_level: BevyIndigaugeLogLevel(IndigaugeLogLevel::Info),
       mode: BevyIndigaugeMode(IndigaugeMode::Live),
+      api_base: None,
+      shutdown_timeout: None,
+      retry_backoff: None,
+      consent_required: false,
+      redactor: None,
+      player_id_provider: None,
+      schema_registry: None,
+      strip_nonconforming_metadata: false,
+      event_naming_policy: None,
+      min_event_level: None,
+      #[cfg(feature = "tracing")]
+      log_directives: None,
+      #[cfg(not(target_family = "wasm"))]
+      event_file: None,
+      #[cfg(feature = "otlp")]
+      otlp: None,
+      sinks: Mutex::default(),
+      sink_options: Vec::new(),
+      console_output: None,
+      remote_config: None,
+      #[cfg(feature = "feedback")]
+      dev_overlay: false,
       meta: PhantomData,
     }
   }
@@ -81,7 +338,47 @@ where
       return;
     }
 
-    let config = BevyIndigaugeConfig::new(&self.game_name, &self.public_key, &self.game_version);
+    let mut config = BevyIndigaugeConfig::new(&self.game_name, &self.public_key, &self.game_version);
+    if let Some(api_base) = &self.api_base {
+      config.0 = config.0.with_api_base(api_base);
+    }
+    if let Some(shutdown_timeout) = self.shutdown_timeout {
+      config.0 = config.0.with_shutdown_timeout(shutdown_timeout);
+    }
+    if let Some((initial, max)) = self.retry_backoff {
+      config.0 = config.0.with_retry_backoff(initial, max);
+    }
+
+    let consent = load_consent(config.game_name());
+    set_consent_required(self.consent_required);
+    set_consent(consent);
+
+    if let Some(redactor) = &self.redactor {
+      set_redactor(redactor.clone());
+    }
+    if let Some(policy) = self.event_naming_policy {
+      set_event_naming_policy(policy);
+    }
+    if let Some(level) = self.min_event_level {
+      set_min_event_level(level);
+    }
+
+    // The log layer is usually built first, by `LogPlugin`.
+    #[cfg(feature = "tracing")]
+    if *self.mode == IndigaugeMode::Disabled
+      && let Some(filter) = app.world().get_resource::<BevyIndigaugeLogFilter>()
+    {
+      filter.reload(LevelDirectives::off());
+    }
+
+    #[cfg(feature = "tracing")]
+    if *self.mode != IndigaugeMode::Disabled
+      && let Some(directives) = &self.log_directives
+      && !matches!(LevelDirectives::from_env(), Some(Ok(_)))
+      && let Some(filter) = app.world().get_resource::<BevyIndigaugeLogFilter>()
+    {
+      filter.reload(directives.clone());
+    }
 
     if matches!(*self.mode, IndigaugeMode::Live | IndigaugeMode::Dev) {
       if !config.has_public_key() && *self.mode == IndigaugeMode::Live {
@@ -104,15 +401,84 @@ where
       }
     }
 
+    if let Some(provider) = &self.player_id_provider {
+      app.insert_resource(provider.clone());
+    }
+
+    #[cfg(feature = "otlp")]
+    if let Some(otlp) = &self.otlp
+      && *self.mode == IndigaugeMode::Live
+    {
+      app.insert_resource(BevyIndigaugeOtlp::new(OtlpExporter::new(otlp.clone())));
+    }
+
+    if matches!(*self.mode, IndigaugeMode::Live | IndigaugeMode::Dev) {
+      let mut sinks = std::mem::take(&mut *self.sinks.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+      let printer = match &self.console_output {
+        Some(printer) => Some(printer.clone()),
+        None => (*self.log_level <= IndigaugeLogLevel::Info).then(ConsolePrinter::new),
+      };
+      if let Some(printer) = printer.filter(|printer| printer.verbosity() != ConsoleVerbosity::Off)
+        && *self.mode == IndigaugeMode::Dev
+      {
+        sinks.register(CONSOLE_SINK, printer, SinkOptions::new());
+      }
+
+      #[cfg(not(target_family = "wasm"))]
+      if let Some(event_file) = &self.event_file
+        && *self.mode == IndigaugeMode::Dev
+      {
+        let writer = std::sync::Arc::new(Mutex::new(NdjsonWriter::new(event_file.clone())));
+        let sink = EventFileSink {
+          writer: writer.clone(),
+          warn_failures: *self.log_level <= IndigaugeLogLevel::Warn,
+        };
+        sinks.register(EVENT_FILE_SINK, sink, SinkOptions::new());
+        app.insert_resource(BevyIndigaugeEventFile(writer));
+      }
+
+      app.insert_resource(BevyIndigaugeEventSinks::new(sinks));
+    }
+
+    if let Some(registry) = &self.schema_registry {
+      app.insert_resource(BevySchemaRegistry {
+        registry: registry.clone(),
+        strip_nonconforming: self.strip_nonconforming_metadata,
+      });
+    }
+
+    // Games read their tuning values from the resource, so it exists in every mode; only the fetch needs telemetry.
+    if let Some(defaults) = &self.remote_config {
+      app.insert_resource(BevyRemoteConfig::new(defaults.clone(), load_remote_config(config.game_name())));
+      if *self.mode != IndigaugeMode::Disabled {
+        app.add_plugins(RemoteConfigPlugin);
+      }
+    }
+
     #[cfg(feature = "feedback")]
     app.add_plugins(crate::feedback::FeedbackUiPlugin);
 
+    #[cfg(feature = "feedback")]
+    if self.dev_overlay && *self.mode != IndigaugeMode::Disabled {
+      app.add_plugins(crate::overlay::DevOverlayPlugin::<M>::default());
+    }
+
+    if let Some(sinks) = app.world().get_resource::<BevyIndigaugeEventSinks>() {
+      for (name, options) in &self.sink_options {
+        if !sinks.set_options(name, options.clone()) && *self.log_level <= IndigaugeLogLevel::Warn {
+          warn!(message = "Sink options set for a sink that is not registered", sink = name.as_str());
+        }
+      }
+    }
+
     app
-      .add_plugins(ReqwestPlugin)
+      .add_plugins((ReqwestPlugin, ConsentPlugin))
       .add_plugins((EventsPlugin::new(config.flush_interval()), SessionPlugin::<M>::new(config.flush_interval())))
       .insert_resource(self.log_level.clone())
       .insert_resource(BufferedEvents::default())
+      .insert_resource(LastEventFlush::default())
       .insert_resource(self.mode.clone())
+      .insert_resource(BevyTelemetryConsent(consent))
       .insert_resource(config);
   }
 }
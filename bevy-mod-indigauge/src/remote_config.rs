@@ -0,0 +1,264 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use bevy::log::{error, info, warn};
+use bevy::prelude::*;
+use indigauge_core::http::should_log_transport_error;
+use indigauge_core::remote_config::{FLAG_EXPOSURE_EVENT, RemoteConfig, store_remote_config};
+use indigauge_core::state::enqueue;
+use indigauge_core::types::{IndigaugeLogLevel, IndigaugeMode};
+use serde::de::DeserializeOwned;
+
+use crate::{
+  config::{BevyIndigaugeConfig, BevyIndigaugeLogLevel},
+  http_runtime::{ReqwestErrorEvent, ReqwestResponseEvent},
+  session::resources::SessionApiKey,
+  utils::BevyIndigauge,
+};
+
+/// Bevy resource holding the remote config: the defaults set with
+/// [`IndigaugePlugin::remote_config`](crate::plugin::IndigaugePlugin::remote_config), overridden by the config
+/// cached on disk at startup and by the config fetched at each session start in [`IndigaugeMode::Live`].
+///
+/// The getters only read values. [`Self::flag`] and [`Self::variant`] also record a `flag.exposure` event the first
+/// time a flag is evaluated with a given value in a session. Evaluate them with [`ig_flag!`](crate::ig_flag) and
+/// [`ig_variant!`](crate::ig_variant) to pass the calling module to the event dispatcher.
+#[derive(Resource, Debug)]
+pub struct BevyRemoteConfig {
+  defaults: RemoteConfig,
+  config: RemoteConfig,
+  fetched: bool,
+  exposed: Mutex<HashSet<(String, String)>>,
+}
+
+impl BevyRemoteConfig {
+  pub(crate) fn new(defaults: RemoteConfig, cached: Option<RemoteConfig>) -> Self {
+    let config = match &cached {
+      Some(cached) => defaults.merged(cached),
+      None => defaults.clone(),
+    };
+    Self {
+      defaults,
+      config,
+      fetched: false,
+      exposed: Mutex::default(),
+    }
+  }
+
+  /// Returns the current config.
+  pub fn config(&self) -> &RemoteConfig {
+    &self.config
+  }
+
+  /// Returns `true` once a config was fetched in this run, `false` while the defaults or the cached config are used.
+  pub fn is_fetched(&self) -> bool {
+    self.fetched
+  }
+
+  /// Returns a value.
+  pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+    self.config.get(key)
+  }
+
+  /// Returns a boolean value.
+  pub fn get_bool(&self, key: &str) -> Option<bool> {
+    self.config.get_bool(key)
+  }
+
+  /// Returns an integer value.
+  pub fn get_i64(&self, key: &str) -> Option<i64> {
+    self.config.get_i64(key)
+  }
+
+  /// Returns a number value.
+  pub fn get_f64(&self, key: &str) -> Option<f64> {
+    self.config.get_f64(key)
+  }
+
+  /// Returns a string value.
+  pub fn get_str(&self, key: &str) -> Option<&str> {
+    self.config.get_str(key)
+  }
+
+  /// Deserializes a value, e.g. into a struct of tuning values.
+  pub fn get_as<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+    self.config.get_as(key)
+  }
+
+  /// Evaluates a boolean feature flag, `false` if it is missing, and records its exposure.
+  #[track_caller]
+  pub fn flag(&self, name: &str) -> bool {
+    self.flag_in_module(name, module_path!())
+  }
+
+  /// Evaluates a string feature flag, e.g. an experiment variant, and records its exposure.
+  #[track_caller]
+  pub fn variant(&self, name: &str) -> Option<&str> {
+    self.variant_in_module(name, module_path!())
+  }
+
+  /// Like [`Self::flag`], recording the exposure with the given module. Used by [`ig_flag!`](crate::ig_flag).
+  #[track_caller]
+  pub fn flag_in_module(&self, name: &str, module: &'static str) -> bool {
+    self.record_exposure(name, module);
+    self.config.get_bool(name).unwrap_or(false)
+  }
+
+  /// Like [`Self::variant`], recording the exposure with the given module. Used by
+  /// [`ig_variant!`](crate::ig_variant).
+  #[track_caller]
+  pub fn variant_in_module(&self, name: &str, module: &'static str) -> Option<&str> {
+    self.record_exposure(name, module);
+    self.config.get_str(name)
+  }
+
+  /// Queues a `flag.exposure` event the first time a flag is evaluated with its current value in the session.
+  ///
+  /// An exposure that cannot be queued, e.g. without an active session, is not retried: the recorded exposures are
+  /// reset when the next session starts.
+  #[track_caller]
+  fn record_exposure(&self, name: &str, module: &'static str) {
+    let value = self.config.get(name).map(ToString::to_string).unwrap_or_default();
+    if !self
+      .exposed
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .insert((name.to_string(), value))
+    {
+      return;
+    }
+
+    let caller = std::panic::Location::caller();
+    enqueue(
+      IndigaugeLogLevel::Info,
+      FLAG_EXPOSURE_EVENT,
+      Some(self.config.exposure_metadata(name)),
+      caller.file(),
+      caller.line(),
+      module,
+    );
+  }
+
+  /// Replaces the fetched values and returns the keys whose value changed.
+  fn apply(&mut self, fetched: &RemoteConfig) -> Vec<String> {
+    let config = self.defaults.merged(fetched);
+    let changed = self.config.changed_keys(&config);
+    self.config = config;
+    self.fetched = true;
+    changed
+  }
+}
+
+/// Evaluates a boolean feature flag of a [`BevyRemoteConfig`] and records its exposure with the calling module.
+///
+/// Usage example: `if ig_flag!(remote_config, "new_hud") { ... }`
+#[macro_export]
+macro_rules! ig_flag {
+  ($remote_config:expr, $name:expr $(,)?) => {
+    $remote_config.flag_in_module($name, module_path!())
+  };
+}
+
+/// Evaluates a string feature flag of a [`BevyRemoteConfig`] and records its exposure with the calling module.
+///
+/// Usage example: `let shop = ig_variant!(remote_config, "shop_layout");`
+#[macro_export]
+macro_rules! ig_variant {
+  ($remote_config:expr, $name:expr $(,)?) => {
+    $remote_config.variant_in_module($name, module_path!())
+  };
+}
+
+/// Emitted when a fetched remote config changes values, with the changed keys sorted.
+#[derive(Event, Clone, Debug)]
+pub struct RemoteConfigChangedEvent {
+  pub keys: Vec<String>,
+}
+
+/// Plugin that fetches the remote config at session start.
+pub(crate) struct RemoteConfigPlugin;
+
+impl Plugin for RemoteConfigPlugin {
+  fn build(&self, app: &mut App) {
+    app.add_systems(
+      Update,
+      fetch_remote_config.run_if(resource_added::<SessionApiKey>.and_then(resource_exists::<BevyRemoteConfig>)),
+    );
+  }
+}
+
+/// Requests the remote config for a new session and resets the recorded exposures.
+///
+/// Exposures are recorded per flag and value, so a flag evaluated again once the fetched config changed its value is
+/// recorded again.
+fn fetch_remote_config(mut ig: BevyIndigauge, session_key: Res<SessionApiKey>, remote_config: Res<BevyRemoteConfig>) {
+  remote_config
+    .exposed
+    .lock()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+    .clear();
+
+  match **ig.mode {
+    IndigaugeMode::Live => match ig.runtime_client().remote_config(&session_key) {
+      Ok(request) => {
+        ig.reqwest_client
+          .send(request)
+          .on_response(on_remote_config_response)
+          .on_error(|trigger: On<ReqwestErrorEvent>, log_level: Res<BevyIndigaugeLogLevel>| {
+            if should_log_transport_error(&log_level) {
+              warn!(message = "Failed to fetch remote config, using the cached config", error = ?trigger.event().error);
+            }
+          });
+      },
+      Err(error) => {
+        if **ig.log_level <= IndigaugeLogLevel::Error {
+          error!(message = "Failed to build remote config request", ?error);
+        }
+      },
+    },
+    IndigaugeMode::Dev if **ig.log_level <= IndigaugeLogLevel::Info => {
+      info!(message = "DEVMODE: remote config is not fetched, using the defaults and the cached config");
+    },
+    _ => {},
+  }
+}
+
+fn on_remote_config_response(
+  trigger: On<ReqwestResponseEvent>,
+  mut commands: Commands,
+  mut remote_config: ResMut<BevyRemoteConfig>,
+  config: Res<BevyIndigaugeConfig>,
+  log_level: Res<BevyIndigaugeLogLevel>,
+) {
+  let status = trigger.event().status();
+  if !status.is_success() {
+    if **log_level <= IndigaugeLogLevel::Warn {
+      warn!(message = "Failed to fetch remote config, using the cached config", ?status);
+    }
+    return;
+  }
+
+  let fetched = match trigger.event().deserialize_json::<RemoteConfig>() {
+    Ok(fetched) => fetched,
+    Err(error) => {
+      if **log_level <= IndigaugeLogLevel::Error {
+        error!(message = "Failed to decode remote config", ?error);
+      }
+      return;
+    },
+  };
+
+  if let Err(error) = store_remote_config(config.game_name(), &fetched)
+    && **log_level <= IndigaugeLogLevel::Warn
+  {
+    warn!(message = "Failed to cache remote config", ?error);
+  }
+
+  let keys = remote_config.apply(&fetched);
+  if **log_level <= IndigaugeLogLevel::Info {
+    info!(message = "Remote config fetched", version = ?fetched.version, changed = keys.len());
+  }
+  if !keys.is_empty() {
+    commands.trigger(RemoteConfigChangedEvent { keys });
+  }
+}
@@ -5,11 +5,19 @@ use bevy::{prelude::*, time::common_conditions::on_timer, window::WindowCloseReq
 use serde::Serialize;
 
 use crate::{
-  session::observers::observe_start_session_event,
-  session::resources::{SessionApiKey, SessionMeta},
-  session::systems::{handle_exit_event, handle_updated_metadata, update_metadata},
+  session::observers::{
+    any_player_session_pending, observe_identify_player, observe_player_session_discarded,
+    observe_player_session_inserted, observe_player_session_invalidated, observe_reset_player_id,
+    observe_session_invalidated, observe_start_session_event, retry_player_session_starts, retry_session_start,
+  },
+  session::resources::{PendingSession, PlayerAccount, PlayerSessions, SessionApiKey, SessionMeta},
+  session::systems::{
+    any_session_active, handle_exit_event, handle_updated_metadata, handle_updated_player_metadata,
+    send_player_account, update_metadata, update_player_metadata,
+  },
 };
 
+pub mod components;
 pub mod events;
 pub(crate) mod observers;
 pub mod resources;
@@ -42,7 +50,20 @@ where
   fn build(&self, app: &mut App) {
     app
       .insert_resource(SessionMeta::<M>::default())
+      .init_resource::<PlayerSessions>()
       .add_observer(observe_start_session_event)
+      .add_observer(observe_session_invalidated)
+      .add_observer(observe_player_session_invalidated)
+      .add_observer(observe_player_session_inserted)
+      .add_observer(observe_player_session_discarded)
+      .add_observer(observe_identify_player)
+      .add_observer(observe_reset_player_id)
+      .add_systems(Update, retry_session_start.run_if(resource_exists::<PendingSession>))
+      .add_systems(Update, retry_player_session_starts.run_if(any_player_session_pending))
+      .add_systems(
+        Update,
+        send_player_account.run_if(resource_added::<SessionApiKey>.and_then(resource_exists::<PlayerAccount>)),
+      )
       .add_systems(
         Update,
         (
@@ -51,10 +72,15 @@ where
         )
           .run_if(resource_exists::<SessionApiKey>),
       )
+      .add_systems(
+        Update,
+        (handle_updated_player_metadata, update_player_metadata.run_if(on_timer(self.flush_interval))).chain(),
+      )
       .add_systems(
         PostUpdate,
         (handle_exit_event::<AppExit>, handle_exit_event::<WindowCloseRequested>)
-          .run_if(resource_exists::<SessionApiKey>),
+          .chain()
+          .run_if(any_session_active),
       );
   }
 }
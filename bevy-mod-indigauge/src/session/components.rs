@@ -0,0 +1,78 @@
+use bevy::ecs::{lifecycle::HookContext, world::DeferredWorld};
+use bevy::prelude::*;
+use indigauge_core::state::SessionHandle;
+use serde::Serialize;
+
+/// Component that runs a separate session for a local player, e.g. in couch co-op or split-screen.
+///
+/// Each insert allocates a new handle and starts a new session, so a cloned component runs its own session. The
+/// session ends when the component is replaced, removed or its entity is despawned. Like the default session, it
+/// accepts events while it is starting, retries a failed start with backoff and is renewed when it expires.
+///
+/// Route events to it with the `ig_*_for!` macros and [`PlayerSession::handle`]; the regular `ig_*!` macros
+/// keep targeting the default session.
+#[derive(Component, Clone, Debug)]
+#[component(on_insert = allocate_session_handle)]
+pub struct PlayerSession {
+  handle: SessionHandle,
+  player_id: String,
+  platform: Option<String>,
+  metadata: Option<serde_json::Value>,
+}
+
+impl PlayerSession {
+  /// Creates a player session for the given player id. Its handle is allocated when the component is inserted.
+  pub fn new(player_id: impl Into<String>) -> Self {
+    Self {
+      handle: SessionHandle::DEFAULT,
+      player_id: player_id.into(),
+      platform: None,
+      metadata: None,
+    }
+  }
+
+  /// Sets the platform string to send with the session start payload.
+  pub fn with_platform(mut self, platform: impl Into<String>) -> Self {
+    self.platform = Some(platform.into());
+    self
+  }
+
+  /// Sets the metadata sent once the session is online.
+  pub fn with_metadata<T: Serialize>(mut self, metadata: &T) -> Self {
+    self.set_metadata(metadata);
+    self
+  }
+
+  /// Replaces the session metadata; changes are synced on the next flush.
+  pub fn set_metadata<T: Serialize>(&mut self, metadata: &T) {
+    self.metadata = serde_json::to_value(metadata).ok();
+  }
+
+  /// Returns the handle used to route events to this session, [`SessionHandle::DEFAULT`] until the component is
+  /// inserted.
+  pub fn handle(&self) -> SessionHandle {
+    self.handle
+  }
+
+  /// Returns the player id the session was started for.
+  pub fn player_id(&self) -> &str {
+    &self.player_id
+  }
+
+  /// Returns the platform string sent with the session start payload.
+  pub fn platform(&self) -> Option<&String> {
+    self.platform.as_ref()
+  }
+
+  /// Returns the current session metadata.
+  pub fn metadata(&self) -> Option<&serde_json::Value> {
+    self.metadata.as_ref()
+  }
+}
+
+/// Gives every inserted [`PlayerSession`] its own handle.
+fn allocate_session_handle(mut world: DeferredWorld, context: HookContext) {
+  if let Some(mut player) = world.get_mut::<PlayerSession>(context.entity) {
+    player.handle = SessionHandle::next();
+  }
+}
@@ -1,14 +1,43 @@
 use bevy::prelude::*;
+use indigauge_core::state::SessionHandle;
+use indigauge_core::types::EventPayload;
 
 /// Emitted when session initialization completes or fails.
 #[derive(Event, Debug)]
 pub enum IndigaugeInitDoneEvent {
   Success,
+  /// The ingest could not be reached. Events are buffered locally while session start is retried in the
+  /// background; [`SessionOnlineEvent`] is emitted once the session has been created.
+  Pending(String),
   Skipped(String),
   Failure(String),
   UnexpectedFailure(String),
 }
 
+/// Emitted when a session that started offline ([`IndigaugeInitDoneEvent::Pending`]) has been created on the
+/// server. Events buffered in the meantime are back-filled with their original relative timestamps.
+#[derive(Event, Debug)]
+pub struct SessionOnlineEvent {
+  /// Number of start attempts that failed before the session went online.
+  pub failed_attempts: u32,
+}
+
+/// Emitted when an expired session was replaced by a new one linked to it.
+///
+/// [`SessionApiKey`](crate::session::resources::SessionApiKey) already holds the new token when this is observed,
+/// and events rejected with the expired token are replayed against it.
+#[derive(Event, Debug)]
+pub struct SessionRenewedEvent {
+  pub previous_session_token: String,
+}
+
+/// Internal signal that the ingest rejected the active token of a session.
+#[derive(Event, Debug)]
+pub(crate) struct SessionInvalidatedEvent {
+  pub(crate) session: SessionHandle,
+  pub(crate) unsent_events: Vec<EventPayload>,
+}
+
 /// Event used to trigger session start.
 #[derive(Event, Default, Clone)]
 pub struct StartSessionEvent {
@@ -27,3 +56,28 @@ impl StartSessionEvent {
     self
   }
 }
+
+/// Event linking the player to an account, e.g. after login.
+///
+/// The link is sent for the current session and for every session started afterwards, until the player id is
+/// reset with [`ResetPlayerIdEvent`]. Requires consent to a persistent player id.
+#[derive(Event, Clone, Debug)]
+pub struct IdentifyPlayerEvent {
+  pub account_id: String,
+}
+
+impl IdentifyPlayerEvent {
+  /// Creates an identify event for the given account id.
+  pub fn new(account_id: impl Into<String>) -> Self {
+    Self {
+      account_id: account_id.into(),
+    }
+  }
+}
+
+/// Event that forgets the linked account and replaces the stored player id, e.g. when switching accounts.
+///
+/// The new id is used from the next session on. Without consent to a persistent player id, the stored id is deleted
+/// instead.
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct ResetPlayerIdEvent;
@@ -1,9 +1,13 @@
-use std::{env::consts::OS, time::Instant};
+use std::env::consts::OS;
 
-use bevy::{diagnostic::SystemInfo, prelude::*, render::renderer::RenderAdapterInfo, state::state::FreelyMutableState};
+use bevy::{
+  diagnostic::SystemInfo, ecs::system::SystemParam, platform::time::Instant, prelude::*,
+  render::renderer::RenderAdapterInfo, state::state::FreelyMutableState,
+};
 
-use bevy::log::{error, info, warn};
-use indigauge_core::http::decode_api_response;
+use bevy::log::{debug, error, info, warn};
+use indigauge_core::consent::effective_consent;
+use indigauge_core::http::{decode_api_response, is_retryable_status, reset_player_id, should_log_transport_error};
 use indigauge_core::types::{
   ApiResponse, IndigaugeConfig, IndigaugeLogLevel, StartSessionPayload, StartSessionResponse,
 };
@@ -11,13 +15,26 @@ use indigauge_core::types::{
 use crate::{
   config::BevyIndigaugeConfig,
   config::BevyIndigaugeMode,
+  event::resources::BufferedEvents,
   http_runtime::{ReqwestErrorEvent, ReqwestResponseEvent},
   prelude::*,
-  session::resources::SessionApiKey,
+  session::components::PlayerSession,
+  session::events::{
+    IdentifyPlayerEvent, ResetPlayerIdEvent, SessionInvalidatedEvent, SessionOnlineEvent, SessionRenewedEvent,
+  },
+  session::resources::{
+    PendingSession, PlayerAccount, PlayerSessionState, PlayerSessions, SessionApiKey, SessionStartRequest,
+  },
+  session::systems::{end_orphaned_session, send_end_session},
   session::utils::{bucket_cores, bucket_ram_gb, coarsen_cpu_name},
-  utils::BevyIndigauge,
+  utils::{BevyIndigauge, requeue_events},
+};
+use indigauge_core::event::QueuedEvent;
+use indigauge_core::ndjson::NdjsonRecordKind;
+use indigauge_core::state::{
+  drain_pending_events, get_global_tx, get_session_start_instant, register_session, reset_session,
+  set_session_start_instant, set_session_token, track_pending_event, unregister_session,
 };
-use indigauge_core::state::{get_global_tx, get_session_start_instant, set_session_start_instant};
 
 /// Returns an observer that advances a Bevy state when session init completes.
 pub fn switch_state_after_session_init<S>(state: S) -> impl FnMut(On<IndigaugeInitDoneEvent>, ResMut<NextState<S>>)
@@ -29,15 +46,22 @@ where
   }
 }
 
+/// Hardware details reported with the session start payload.
+#[derive(SystemParam)]
+pub(crate) struct DeviceInfo<'w> {
+  sys_info: Option<Res<'w, SystemInfo>>,
+  render_info: Option<Res<'w, RenderAdapterInfo>>,
+}
+
 /// Observer that handles [`StartSessionEvent`] and triggers session startup flow.
 pub fn observe_start_session_event(
   event: On<StartSessionEvent>,
   mut ig: BevyIndigauge,
   mut cmd: Commands,
-  sys_info: Option<Res<SystemInfo>>,
-  render_info: Option<Res<RenderAdapterInfo>>,
+  pending: Option<Res<PendingSession>>,
+  device: DeviceInfo,
 ) {
-  if get_session_start_instant().is_some() {
+  if get_session_start_instant().is_some() || pending.is_some() {
     if **ig.log_level <= IndigaugeLogLevel::Warn {
       warn!("Session already started");
     }
@@ -50,10 +74,19 @@ pub fn observe_start_session_event(
     return;
   }
 
+  if !effective_consent().allows_session() {
+    cmd.trigger(IndigaugeInitDoneEvent::Skipped("Telemetry consent denied".to_string()));
+    return;
+  }
+
   match **ig.mode {
     IndigaugeMode::Dev => {
+      if ig.has_event_file() {
+        let player_id = ig.get_or_init_player_id();
+        write_dev_session_start(&ig, SessionHandle::DEFAULT, event.platform.as_ref(), player_id.as_ref(), &device);
+      }
       let dev_response = StartSessionResponse::dev();
-      start_session(&mut cmd, dev_response, &ig.log_level, &ig.mode, &ig.config);
+      start_session(&mut cmd, dev_response, None, &ig.log_level, &ig.mode, &ig.config);
       return;
     },
     IndigaugeMode::Disabled => {
@@ -63,103 +96,564 @@ pub fn observe_start_session_event(
     _ => {},
   }
 
-  #[cfg(not(target_family = "wasm"))]
-  let player_id = Some(ig.get_or_init_player_id());
+  cmd.insert_resource(SessionStartRequest(event.event().clone()));
 
-  #[cfg(target_family = "wasm")]
-  let player_id = None::<String>;
+  if send_start_session_request(&mut ig, event.event(), None, &device) {
+    // Start the local clock with the first attempt, so events emitted until the session is online are kept.
+    let _ = set_session_start_instant(Instant::now());
+    cmd.insert_resource(PendingSession::new(event.event().clone()));
+  } else {
+    cmd.trigger(IndigaugeInitDoneEvent::Failure("Failed to create session post client".to_string()));
+  }
+}
+
+/// Re-sends the session start request once the backoff timer of an offline session has elapsed.
+pub(crate) fn retry_session_start(
+  mut ig: BevyIndigauge,
+  mut pending: ResMut<PendingSession>,
+  time: Res<Time>,
+  device: DeviceInfo,
+) {
+  if !pending.retry_due(time.delta()) {
+    return;
+  }
+
+  if **ig.log_level <= IndigaugeLogLevel::Debug {
+    debug!(message = "Retrying session start", attempt = pending.failed_attempts + 1);
+  }
+
+  let start_event = pending.start_event.clone();
+  let previous_session_token = pending.previous_session_token.clone();
+  if !send_start_session_request(&mut ig, &start_event, previous_session_token.as_deref(), &device) {
+    pending.schedule_retry(&ig.config);
+  }
+}
+
+/// Re-sends the start requests of player sessions whose backoff timer has elapsed.
+pub(crate) fn retry_player_session_starts(
+  mut ig: BevyIndigauge,
+  mut players: ResMut<PlayerSessions>,
+  time: Res<Time>,
+  device: DeviceInfo,
+) {
+  for (handle, session) in players.sessions.iter_mut() {
+    let Some(pending) = session.pending.as_mut() else {
+      continue;
+    };
+    if !pending.retry_due(time.delta()) {
+      continue;
+    }
 
-  let event = event.event();
-  let cores = sys_info
-    .as_ref()
-    .and_then(|i| i.core_count.parse().map(bucket_cores).ok());
-  let memory = sys_info.as_ref().and_then(|i| {
+    if **ig.log_level <= IndigaugeLogLevel::Debug {
+      debug!(message = "Retrying player session start", session = handle.id(), attempt = pending.failed_attempts + 1);
+    }
+    send_player_start_request(&mut ig, *handle, session, &device);
+  }
+}
+
+/// Returns `true` while a player session is starting or being renewed.
+pub(crate) fn any_player_session_pending(players: Res<PlayerSessions>) -> bool {
+  players.sessions.values().any(|session| session.pending.is_some())
+}
+
+fn send_start_session_request(
+  ig: &mut BevyIndigauge,
+  event: &StartSessionEvent,
+  previous_session_token: Option<&str>,
+  device: &DeviceInfo,
+) -> bool {
+  let player_id = ig.get_or_init_player_id();
+
+  let Some(request) =
+    build_start_session_request(ig, event.platform.as_ref(), player_id.as_ref(), previous_session_token, device)
+  else {
+    return false;
+  };
+
+  ig.reqwest_client
+    .send(request)
+    .on_response(on_start_session_response)
+    .on_error(on_start_session_error);
+  true
+}
+
+fn build_start_session_request(
+  ig: &BevyIndigauge,
+  platform: Option<&String>,
+  player_id: Option<&String>,
+  previous_session_token: Option<&str>,
+  device: &DeviceInfo,
+) -> Option<reqwest::Request> {
+  with_start_session_payload(ig, platform, player_id, previous_session_token, device, |payload| {
+    match ig.runtime_client().start_session(payload) {
+      Ok(request) => Some(request),
+      Err(err) => {
+        if **ig.log_level <= IndigaugeLogLevel::Error {
+          error!("Failed to create session post client: {}", err);
+        }
+        None
+      },
+    }
+  })
+}
+
+/// Writes the start payload of a Dev mode session to the event file.
+fn write_dev_session_start(
+  ig: &BevyIndigauge,
+  session: SessionHandle,
+  platform: Option<&String>,
+  player_id: Option<&String>,
+  device: &DeviceInfo,
+) {
+  with_start_session_payload(ig, platform, player_id, None, device, |payload| {
+    ig.write_event_file(NdjsonRecordKind::SessionStart, session, payload);
+  });
+}
+
+fn with_start_session_payload<R>(
+  ig: &BevyIndigauge,
+  platform: Option<&String>,
+  player_id: Option<&String>,
+  previous_session_token: Option<&str>,
+  device: &DeviceInfo,
+  f: impl FnOnce(&StartSessionPayload<'_>) -> R,
+) -> R {
+  let sys_info = device.sys_info.as_deref();
+  let render_info = device.render_info.as_deref();
+
+  let cores = sys_info.and_then(|i| i.core_count.parse().map(bucket_cores).ok());
+  let memory = sys_info.and_then(|i| {
     i.memory
       .split('.')
       .collect::<Vec<_>>()
       .first()
       .and_then(|m| m.parse().map(bucket_ram_gb).ok())
   });
-  let cpu_family = sys_info.as_ref().and_then(|i| coarsen_cpu_name(&i.cpu));
-  let gpu = render_info.as_ref().map(|i| &i.name);
+  let cpu_family = sys_info.and_then(|i| coarsen_cpu_name(&i.cpu));
+  let gpu = render_info.map(|i| &i.name);
 
   let payload = StartSessionPayload {
     client_version: ig.config.game_version(),
     sdk_version: concat!("bevy:", env!("CARGO_PKG_VERSION")),
-    player_id: player_id.as_ref(),
-    platform: event.platform.as_ref(),
+    player_id,
+    platform,
     os: Some(OS),
     cpu_family: cpu_family.as_ref(),
     cores,
     memory,
     gpu,
+    previous_session_token,
   };
 
-  match ig.runtime_client().start_session(&payload) {
-    Ok(reqwest_client) => {
-      ig.reqwest_client
-        .send(reqwest_client)
-        .on_response(on_start_session_response)
-        .on_error(on_start_session_error);
-    },
-    Err(err) => {
-      error!("Failed to create session post client: {}", err);
-      cmd.trigger(IndigaugeInitDoneEvent::Failure("Failed to create session post client".to_string()));
+  f(&payload)
+}
+
+/// Observer that starts a separate session when a [`PlayerSession`] is inserted.
+///
+/// The session is registered right away, so events routed to it are buffered until it is online.
+pub(crate) fn observe_player_session_inserted(
+  insert: On<Insert, PlayerSession>,
+  player_sessions: Query<&PlayerSession>,
+  mut players: ResMut<PlayerSessions>,
+  mut ig: BevyIndigauge,
+  device: DeviceInfo,
+) {
+  let Ok(player) = player_sessions.get(insert.entity) else {
+    return;
+  };
+  let handle = player.handle();
+  let consent = effective_consent();
+
+  if !consent.allows_session() {
+    return;
+  }
+
+  let start_event = StartSessionEvent {
+    platform: player.platform().cloned(),
+  };
+  let player_id = consent.allows_player_id().then(|| player.player_id().to_string());
+
+  match **ig.mode {
+    IndigaugeMode::Dev => {
+      if **ig.log_level <= IndigaugeLogLevel::Info {
+        info!(message = "DEVMODE: Player session started", session = handle.id(), player_id = player.player_id());
+      }
+      if ig.has_event_file() {
+        write_dev_session_start(&ig, handle, player.platform(), player_id.as_ref(), &device);
+      }
+      let token = StartSessionResponse::dev().session_token;
+      players
+        .sessions
+        .insert(handle, PlayerSessionState::online(start_event, player_id, token));
+      register_session(handle, Instant::now());
+      return;
     },
+    IndigaugeMode::Disabled => return,
+    IndigaugeMode::Live => {},
   }
+
+  let mut session = PlayerSessionState::starting(start_event, player_id);
+  send_player_start_request(&mut ig, handle, &mut session, &device);
+  players.sessions.insert(handle, session);
+  register_session(handle, Instant::now());
 }
 
-/// Handles successful HTTP responses from session start requests.
-pub fn on_start_session_response(
-  trigger: On<ReqwestResponseEvent>,
+/// Sends the start (or renewal) request of a player session, scheduling a retry if it cannot be built.
+fn send_player_start_request(
+  ig: &mut BevyIndigauge,
+  handle: SessionHandle,
+  session: &mut PlayerSessionState,
+  device: &DeviceInfo,
+) {
+  let Some(pending) = session.pending.as_mut() else {
+    return;
+  };
+  let Some(request) = build_start_session_request(
+    ig,
+    session.start_event.platform.as_ref(),
+    session.player_id.as_ref(),
+    pending.previous_session_token.as_deref(),
+    device,
+  ) else {
+    pending.schedule_retry(&ig.config);
+    return;
+  };
+
+  ig.reqwest_client
+    .send(request)
+    .on_response(
+      move |trigger: On<ReqwestResponseEvent>,
+            mut commands: Commands,
+            mut players: ResMut<PlayerSessions>,
+            config: Res<BevyIndigaugeConfig>,
+            log_level: Res<BevyIndigaugeLogLevel>| {
+        let outcome = read_start_response(trigger.event(), &log_level);
+        let Some(session) = players.sessions.get_mut(&handle) else {
+          // The player left before the session came online.
+          if let StartResponse::Started(response) = outcome {
+            commands.run_system_cached_with(end_orphaned_session, (handle, response.session_token));
+          }
+          return;
+        };
+
+        match outcome {
+          StartResponse::Retry => {
+            if let Some(pending) = session.pending.as_mut() {
+              pending.schedule_retry(&config);
+            }
+          },
+          StartResponse::Failed(_) => {
+            let dropped = session.events.len()
+              + session
+                .pending
+                .as_ref()
+                .map_or(0, |pending| pending.replay_events.len());
+            if **log_level <= IndigaugeLogLevel::Warn {
+              warn!(message = "Player session ended after a failed start", session = handle.id(), dropped);
+            }
+            players.sessions.remove(&handle);
+            unregister_session(handle);
+          },
+          StartResponse::Started(response) => {
+            let pending = session.pending.take();
+            let renewed = pending.as_ref().is_some_and(PendingSession::is_renewal);
+            // Replay batches rejected with the expired token ahead of anything queued since.
+            let replay_events = pending
+              .into_iter()
+              .flat_map(|pending| pending.replay_events)
+              .map(|event| QueuedEvent::new(event).with_session(handle));
+            session.events.splice(0..0, replay_events);
+            session.token = Some(response.session_token);
+            session.metadata_changed = true;
+
+            if **log_level <= IndigaugeLogLevel::Info {
+              if renewed {
+                info!(message = "Player session renewed", session = handle.id());
+              } else {
+                info!(message = "Player session started", session = handle.id());
+              }
+            }
+          },
+        }
+      },
+    )
+    .on_error(
+      move |trigger: On<ReqwestErrorEvent>,
+            mut players: ResMut<PlayerSessions>,
+            config: Res<BevyIndigaugeConfig>,
+            log_level: Res<BevyIndigaugeLogLevel>| {
+        if should_log_transport_error(&log_level) {
+          warn!(message = "Player session start failed, will retry", error = %trigger.event().error);
+        }
+        if let Some(pending) = players
+          .sessions
+          .get_mut(&handle)
+          .and_then(|session| session.pending.as_mut())
+        {
+          pending.schedule_retry(&config);
+        }
+      },
+    );
+}
+
+/// Observer that flushes and ends the session of a replaced or removed [`PlayerSession`].
+pub(crate) fn observe_player_session_discarded(
+  discard: On<Discard, PlayerSession>,
+  player_sessions: Query<&PlayerSession>,
+  mut players: ResMut<PlayerSessions>,
+  mut ig: BevyIndigauge,
+) {
+  let Ok(player) = player_sessions.get(discard.entity) else {
+    return;
+  };
+  let handle = player.handle();
+
+  unregister_session(handle);
+  let Some(mut session) = players.sessions.remove(&handle) else {
+    return;
+  };
+  let Some(token) = session.token else {
+    return;
+  };
+
+  while ig.flush_session_events(handle, &token, &mut session.events) > 0 {}
+  send_end_session(&mut ig, handle, &token);
+}
+
+/// Observer that replaces an expired default session with a new one linked to it.
+///
+/// The active [`SessionApiKey`] is removed so nothing else is sent with the stale token, and the unsent events of
+/// the rejected batch are kept to be replayed once the new session is online.
+pub(crate) fn observe_session_invalidated(
+  event: On<SessionInvalidatedEvent>,
+  mut ig: BevyIndigauge,
   mut commands: Commands,
-  ig_config: Res<BevyIndigaugeConfig>,
-  log_level: Res<BevyIndigaugeLogLevel>,
-  mode: Res<BevyIndigaugeMode>,
+  session_key: Option<Res<SessionApiKey>>,
+  start_request: Option<Res<SessionStartRequest>>,
+  pending: Option<ResMut<PendingSession>>,
+  device: DeviceInfo,
+) {
+  if !event.event().session.is_default() {
+    return;
+  }
+  let replay_events = event.event().unsent_events.clone();
+
+  if let Some(mut pending) = pending {
+    // A renewal is already in progress; hold on to this batch as well.
+    pending.replay_events.extend(replay_events);
+    return;
+  }
+
+  let Some(session_key) = session_key else {
+    return;
+  };
+
+  if **ig.log_level <= IndigaugeLogLevel::Warn {
+    warn!(message = "Session expired, starting a new session", unsent_events = replay_events.len());
+  }
+
+  let start_event = start_request.map(|request| request.0.clone()).unwrap_or_default();
+  let mut pending = PendingSession::renewal(start_event.clone(), session_key.0.clone(), replay_events);
+
+  if !send_start_session_request(&mut ig, &start_event, Some(&session_key), &device) {
+    pending.schedule_retry(&ig.config);
+  }
+
+  commands.remove_resource::<SessionApiKey>();
+  commands.insert_resource(pending);
+}
+
+/// Observer that replaces an expired player session with a new one linked to it, like
+/// [`observe_session_invalidated`] does for the default session.
+pub(crate) fn observe_player_session_invalidated(
+  event: On<SessionInvalidatedEvent>,
+  mut ig: BevyIndigauge,
+  mut players: ResMut<PlayerSessions>,
+  device: DeviceInfo,
 ) {
-  let Ok(response) = decode_api_response::<StartSessionResponse>(trigger.event().body()) else {
+  let handle = event.event().session;
+  let Some(session) = players.sessions.get_mut(&handle) else {
+    return;
+  };
+  let replay_events = event.event().unsent_events.clone();
+
+  if let Some(pending) = session.pending.as_mut() {
+    // A renewal is already in progress; hold on to this batch as well.
+    pending.replay_events.extend(replay_events);
+    return;
+  }
+  let Some(token) = session.token.take() else {
+    return;
+  };
+
+  if **ig.log_level <= IndigaugeLogLevel::Warn {
+    warn!(
+      message = "Player session expired, starting a new session",
+      session = handle.id(),
+      unsent_events = replay_events.len()
+    );
+  }
+
+  session.pending = Some(PendingSession::renewal(session.start_event.clone(), token, replay_events));
+  send_player_start_request(&mut ig, handle, session, &device);
+}
+
+/// Outcome of a session start response, shared by the default session and player sessions.
+enum StartResponse {
+  Started(StartSessionResponse),
+  /// The ingest is unavailable; the start is retried with backoff.
+  Retry,
+  /// The start failed for good, with the init event reporting why.
+  Failed(IndigaugeInitDoneEvent),
+}
+
+/// Classifies a session start response, logging failures.
+fn read_start_response(response: &ReqwestResponseEvent, log_level: &BevyIndigaugeLogLevel) -> StartResponse {
+  let status = response.status();
+
+  if is_retryable_status(status) {
+    if **log_level <= IndigaugeLogLevel::Warn {
+      warn!(message = "Session start failed, will retry", ?status);
+    }
+    return StartResponse::Retry;
+  }
+
+  let Ok(response) = decode_api_response::<StartSessionResponse>(response.body()) else {
     if **log_level <= IndigaugeLogLevel::Error {
       error!("Failed to deserialize response");
     }
-    commands.trigger(IndigaugeInitDoneEvent::UnexpectedFailure("Failed to deserialize response".to_string()));
-    return;
+    return StartResponse::Failed(IndigaugeInitDoneEvent::UnexpectedFailure(
+      "Failed to deserialize response".to_string(),
+    ));
   };
 
   match response {
-    ApiResponse::Ok(response) => {
-      start_session(&mut commands, response, &log_level, &mode, &ig_config);
-    },
+    ApiResponse::Ok(response) => StartResponse::Started(response),
     ApiResponse::Err(error_body) => {
       if **log_level <= IndigaugeLogLevel::Error {
         error!(message = "Failed to start session", error_code = error_body.code, error_message = error_body.message);
       }
-      commands.trigger(IndigaugeInitDoneEvent::Failure("Failed to start session".to_string()));
+      StartResponse::Failed(IndigaugeInitDoneEvent::Failure("Failed to start session".to_string()))
+    },
+  }
+}
+
+/// Handles successful HTTP responses from session start requests.
+pub fn on_start_session_response(
+  trigger: On<ReqwestResponseEvent>,
+  mut commands: Commands,
+  ig_config: Res<BevyIndigaugeConfig>,
+  log_level: Res<BevyIndigaugeLogLevel>,
+  mode: Res<BevyIndigaugeMode>,
+  mut buffered_events: ResMut<BufferedEvents>,
+  mut pending: Option<ResMut<PendingSession>>,
+) {
+  match read_start_response(trigger.event(), &log_level) {
+    StartResponse::Retry => {
+      schedule_session_retry(&mut commands, pending, &ig_config, "Ingest unavailable");
+    },
+    StartResponse::Failed(init_done) => {
+      abandon_session_start(&mut commands, pending.as_deref_mut(), &mut buffered_events, &log_level);
+      commands.trigger(init_done);
+    },
+    StartResponse::Started(response) => {
+      let Some(pending) = pending.as_deref_mut() else {
+        // The session was ended while its start request was in flight.
+        commands.run_system_cached_with(end_orphaned_session, (SessionHandle::DEFAULT, response.session_token));
+        return;
+      };
+
+      // Replay batches rejected with the expired token ahead of anything queued since.
+      let replay_events = pending.replay_events.drain(..).map(QueuedEvent::new);
+      buffered_events.events.splice(0..0, replay_events);
+      start_session(&mut commands, response, Some(pending), &log_level, &mode, &ig_config);
     },
   }
 }
 
-/// Handles transport-level errors from session start requests.
+/// Gives up on a session start that failed for good, stopping the local clock started by its first attempt.
+///
+/// The events of a failed renewal, including the batches rejected with the expired token, stay buffered and are
+/// sent with the next session started with [`StartSessionEvent`].
+fn abandon_session_start(
+  commands: &mut Commands,
+  pending: Option<&mut PendingSession>,
+  buffered_events: &mut BufferedEvents,
+  log_level: &BevyIndigaugeLogLevel,
+) {
+  commands.remove_resource::<PendingSession>();
+  let Some(pending) = pending else {
+    return;
+  };
+
+  if !pending.is_renewal() {
+    reset_session();
+    buffered_events.events.clear();
+    return;
+  }
+
+  let tracked = drain_pending_events();
+  reset_session();
+  tracked.into_iter().for_each(track_pending_event);
+  requeue_events(SessionHandle::DEFAULT, pending.replay_events.drain(..).collect(), buffered_events, None);
+  if **log_level <= IndigaugeLogLevel::Warn {
+    warn!(
+      message = "Session renewal failed, keeping unsent events for the next session",
+      unsent_events = buffered_events.events.len()
+    );
+  }
+}
+
+/// Handles transport-level errors from session start requests by scheduling a retry.
 pub fn on_start_session_error(
   trigger: On<ReqwestErrorEvent>,
   mut commands: Commands,
+  ig_config: Res<BevyIndigaugeConfig>,
   log_level: Res<BevyIndigaugeLogLevel>,
+  pending: Option<ResMut<PendingSession>>,
+) {
+  if **log_level <= IndigaugeLogLevel::Warn {
+    warn!(message = "Create session post request failed, will retry", error = %trigger.event().error);
+  }
+  schedule_session_retry(&mut commands, pending, &ig_config, "Create session post request failed");
+}
+
+/// Marks the session as offline on the first failure and arms the backoff timer for the next attempt.
+fn schedule_session_retry(
+  commands: &mut Commands,
+  pending: Option<ResMut<PendingSession>>,
+  config: &IndigaugeConfig,
+  reason: &str,
 ) {
-  if **log_level <= IndigaugeLogLevel::Error {
-    error!(message = "Create session post request failed", error = %trigger.event().error);
+  let Some(mut pending) = pending else {
+    return;
+  };
+
+  if !pending.is_offline() && !pending.is_renewal() {
+    commands.trigger(IndigaugeInitDoneEvent::Pending(reason.to_string()));
   }
-  commands.trigger(IndigaugeInitDoneEvent::Failure("Create session post request failed".to_string()));
+
+  pending.schedule_retry(config);
 }
 
 #[allow(unused_variables)]
 fn start_session(
   commands: &mut Commands,
   response: StartSessionResponse,
+  pending: Option<&PendingSession>,
   log_level: &BevyIndigaugeLogLevel,
   mode: &IndigaugeMode,
   config: &IndigaugeConfig,
 ) {
-  let start_instant = Instant::now();
-  if let Err(set_start_instance_err) = set_session_start_instant(start_instant) {
+  let failed_attempts = pending.map(PendingSession::failed_attempts).unwrap_or_default();
+  let previous_session_token = pending.and_then(|pending| pending.previous_session_token.clone());
+  commands.remove_resource::<PendingSession>();
+
+  // Live sessions started the local clock with their first start attempt, and renewed sessions keep the clock of the
+  // original session.
+  if pending.is_none()
+    && let Err(set_start_instance_err) = set_session_start_instant(Instant::now())
+  {
     if **log_level <= IndigaugeLogLevel::Error {
       error!(message = "Failed to set session start instant", error = ?set_start_instance_err);
     }
@@ -169,8 +663,11 @@ fn start_session(
 
   if **log_level <= IndigaugeLogLevel::Info {
     match *mode {
+      IndigaugeMode::Live if previous_session_token.is_some() => {
+        info!(message = "Indigauge session renewed");
+      },
       IndigaugeMode::Live => {
-        info!(message = "Indigauge session started");
+        info!(message = "Indigauge session started", failed_attempts);
       },
       IndigaugeMode::Dev => {
         info!(message = "DEVMODE: Indigauge session started");
@@ -180,20 +677,77 @@ fn start_session(
   }
 
   let key = response.session_token.clone();
+  set_session_token(key.clone());
 
-  #[cfg(all(feature = "panic_handler", not(target_family = "wasm")))]
+  #[cfg(feature = "panic_handler")]
   {
     use crate::session::utils::panic_handler;
+    use std::sync::Once;
+
+    // The hook reads the current session token at panic time, so it is installed once and survives renewals.
+    static PANIC_HOOK: Once = Once::new();
+    PANIC_HOOK.call_once(|| {
+      let previous_hook = std::panic::take_hook();
+      let indigauge_hook = panic_handler(config.clone());
+      std::panic::set_hook(Box::new(move |info| {
+        indigauge_hook(info);
+        previous_hook(info);
+      }));
+    });
+  }
+
+  #[cfg(target_family = "wasm")]
+  if *mode == IndigaugeMode::Live {
+    use indigauge_core::web::{install_unload_flush, replay_offline_events};
 
-    let session_start = start_instant;
-    let previous_hook = std::panic::take_hook();
-    let indigauge_hook = panic_handler(config.clone(), key.clone(), session_start);
-    std::panic::set_hook(Box::new(move |info| {
-      indigauge_hook(info);
-      previous_hook(info);
-    }));
+    install_unload_flush(config.clone());
+    let log_level = **log_level;
+    replay_offline_events(config, &key, move |replayed| {
+      if replayed > 0 && log_level <= IndigaugeLogLevel::Info {
+        info!(message = "Replayed events stored while offline", replayed);
+      }
+    });
   }
 
   commands.insert_resource(SessionApiKey::new(key));
-  commands.trigger(IndigaugeInitDoneEvent::Success);
+
+  if let Some(previous_session_token) = previous_session_token {
+    commands.trigger(SessionRenewedEvent { previous_session_token });
+  } else if failed_attempts == 0 {
+    commands.trigger(IndigaugeInitDoneEvent::Success);
+  } else {
+    commands.trigger(SessionOnlineEvent { failed_attempts });
+  }
+}
+
+/// Observer that links the player to an account for the current and all later sessions.
+pub(crate) fn observe_identify_player(
+  event: On<IdentifyPlayerEvent>,
+  mut commands: Commands,
+  mut ig: BevyIndigauge,
+  session_key: Option<Res<SessionApiKey>>,
+) {
+  if !effective_consent().allows_player_id() {
+    if **ig.log_level <= IndigaugeLogLevel::Warn {
+      warn!("Player not identified: no consent to a persistent player id");
+    }
+    return;
+  }
+
+  let account_id = event.event().account_id.clone();
+  if let Some(key) = session_key {
+    ig.send_identify(&account_id, &key);
+  }
+  commands.insert_resource(PlayerAccount(account_id));
+}
+
+/// Observer that forgets the linked account and replaces the stored player id, or deletes it without consent to a
+/// persistent id.
+pub(crate) fn observe_reset_player_id(
+  _event: On<ResetPlayerIdEvent>,
+  mut commands: Commands,
+  config: Res<BevyIndigaugeConfig>,
+) {
+  commands.remove_resource::<PlayerAccount>();
+  let _ = reset_player_id(config.game_name());
 }
@@ -1,6 +1,16 @@
 use bevy::prelude::*;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use indigauge_core::event::QueuedEvent;
+use indigauge_core::http::is_valid_player_id;
+use indigauge_core::state::SessionHandle;
+use indigauge_core::types::{EventPayload, IndigaugeConfig};
+
+use crate::session::events::StartSessionEvent;
 
 /// Resource holding the active session API key/token.
 #[derive(Resource, Deref)]
@@ -12,6 +22,163 @@ impl SessionApiKey {
   }
 }
 
+/// Resource present while a live session start is in flight or waiting to be retried.
+///
+/// Events emitted meanwhile stay in the local queue (bounded by the configured max queue size) and are
+/// flushed once the session is online. Player sessions keep their own instance while they start.
+#[derive(Resource)]
+pub struct PendingSession {
+  pub(crate) start_event: StartSessionEvent,
+  pub(crate) failed_attempts: u32,
+  pub(crate) retry_timer: Option<Timer>,
+  pub(crate) previous_session_token: Option<String>,
+  pub(crate) replay_events: Vec<EventPayload>,
+}
+
+impl PendingSession {
+  pub(crate) fn new(start_event: StartSessionEvent) -> Self {
+    Self {
+      start_event,
+      failed_attempts: 0,
+      retry_timer: None,
+      previous_session_token: None,
+      replay_events: Vec::new(),
+    }
+  }
+
+  pub(crate) fn renewal(
+    start_event: StartSessionEvent,
+    previous_session_token: String,
+    replay_events: Vec<EventPayload>,
+  ) -> Self {
+    Self {
+      previous_session_token: Some(previous_session_token),
+      replay_events,
+      ..Self::new(start_event)
+    }
+  }
+
+  /// Returns `true` if this start replaces an expired session.
+  pub fn is_renewal(&self) -> bool {
+    self.previous_session_token.is_some()
+  }
+
+  /// Returns how many session start attempts have failed so far.
+  pub fn failed_attempts(&self) -> u32 {
+    self.failed_attempts
+  }
+
+  /// Returns `true` once at least one attempt failed and the session is running offline.
+  pub fn is_offline(&self) -> bool {
+    self.failed_attempts > 0
+  }
+
+  /// Counts a failed attempt and arms the backoff timer for the next one.
+  pub(crate) fn schedule_retry(&mut self, config: &IndigaugeConfig) {
+    self.failed_attempts += 1;
+    self.retry_timer = Some(Timer::new(config.retry_backoff(self.failed_attempts), TimerMode::Once));
+  }
+
+  /// Advances the backoff timer and returns `true` once the next attempt is due.
+  pub(crate) fn retry_due(&mut self, delta: Duration) -> bool {
+    let Some(timer) = self.retry_timer.as_mut() else {
+      return false;
+    };
+    if !timer.tick(delta).is_finished() {
+      return false;
+    }
+    self.retry_timer = None;
+    true
+  }
+}
+
+/// Start request of the current session, kept so an expired session can be renewed with the same parameters.
+#[derive(Resource)]
+pub(crate) struct SessionStartRequest(pub(crate) StartSessionEvent);
+
+/// Resource tracking the sessions started through [`PlayerSession`](crate::session::components::PlayerSession).
+#[derive(Resource, Default)]
+pub struct PlayerSessions {
+  pub(crate) sessions: HashMap<SessionHandle, PlayerSessionState>,
+}
+
+/// State of a single player session.
+///
+/// Player sessions are started, retried and renewed like the default session, with their own [`PendingSession`].
+pub(crate) struct PlayerSessionState {
+  pub(crate) start_event: StartSessionEvent,
+  pub(crate) player_id: Option<String>,
+  /// Session token, `None` while the session is starting or being renewed.
+  pub(crate) token: Option<String>,
+  pub(crate) pending: Option<PendingSession>,
+  pub(crate) events: Vec<QueuedEvent>,
+  pub(crate) metadata_changed: bool,
+}
+
+impl PlayerSessionState {
+  /// Creates the state of a session that is starting.
+  pub(crate) fn starting(start_event: StartSessionEvent, player_id: Option<String>) -> Self {
+    Self {
+      pending: Some(PendingSession::new(start_event.clone())),
+      start_event,
+      player_id,
+      token: None,
+      events: Vec::new(),
+      metadata_changed: false,
+    }
+  }
+
+  /// Creates the state of a session that is online with the given token, e.g. in Dev mode.
+  pub(crate) fn online(start_event: StartSessionEvent, player_id: Option<String>, token: String) -> Self {
+    Self {
+      pending: None,
+      token: Some(token),
+      ..Self::starting(start_event, player_id)
+    }
+  }
+}
+
+impl PlayerSessions {
+  /// Returns the token of the player session behind `handle` once it is online.
+  pub fn token(&self, handle: SessionHandle) -> Option<&str> {
+    self.sessions.get(&handle).and_then(|session| session.token.as_deref())
+  }
+
+  /// Returns `true` if no player session is started or starting.
+  pub fn is_empty(&self) -> bool {
+    self.sessions.is_empty()
+  }
+
+  /// Buffers an event for its player session; events of unknown sessions are dropped.
+  pub(crate) fn push(&mut self, event: QueuedEvent) {
+    if let Some(session) = self.sessions.get_mut(&event.session()) {
+      session.events.push(event);
+    }
+  }
+}
+
+/// Resource holding the game-provided player id source set with
+/// [`IndigaugePlugin::player_id_provider`](crate::plugin::IndigaugePlugin::player_id_provider).
+#[derive(Resource, Clone)]
+pub struct PlayerIdProvider(Arc<dyn Fn() -> Option<String> + Send + Sync>);
+
+impl PlayerIdProvider {
+  pub(crate) fn new(provider: impl Fn() -> Option<String> + Send + Sync + 'static) -> Self {
+    Self(Arc::new(provider))
+  }
+
+  /// Returns the provided player id, ignoring ids that are empty or malformed.
+  pub fn player_id(&self) -> Option<String> {
+    (self.0)()
+      .map(|player_id| player_id.trim().to_string())
+      .filter(|player_id| is_valid_player_id(player_id))
+  }
+}
+
+/// Account the player was identified as, linked to every session started while it is set.
+#[derive(Resource)]
+pub(crate) struct PlayerAccount(pub(crate) String);
+
 /// Marker metadata type for integrations that do not use session metadata.
 #[derive(Resource, Serialize)]
 pub struct EmptySessionMeta;
@@ -1,23 +1,94 @@
+use bevy::log::{error, info};
 use bevy::prelude::*;
-use indigauge_core::types::IndigaugeLogLevel;
+use indigauge_core::types::{IndigaugeLogLevel, IndigaugeMode};
 use serde::Serialize;
 
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+use indigauge_core::ndjson::NdjsonRecordKind;
+#[cfg(not(target_family = "wasm"))]
+use indigauge_core::state::clear_pending_event_count;
+use indigauge_core::state::{SessionHandle, reset_session, unregister_session};
+
 use crate::{
+  event::resources::EventQueueReceiver,
+  event::systems::{buffer_ingested, route_events},
   prelude::{EmptySessionMeta, StartSessionEvent},
-  session::resources::{SessionApiKey, SessionMeta},
-  utils::BevyIndigauge,
+  session::components::PlayerSession,
+  session::resources::{
+    PendingSession, PlayerAccount, PlayerSessions, SessionApiKey, SessionMeta, SessionStartRequest,
+  },
+  utils::{BevyIndigauge, EndSessionRecord},
 };
 
-/// Ends the active session when exit events are observed.
-pub fn handle_exit_event<E>(exit_events: MessageReader<E>, ig: BevyIndigauge, session_key: Option<Res<SessionApiKey>>)
-where
+/// Ends the active sessions when exit events are observed.
+///
+/// On native targets this performs a bounded blocking flush of all remaining events followed by the
+/// end-session call, since async requests would not complete before the process terminates. The shutdown
+/// timeout bounds the flush of all sessions together.
+pub fn handle_exit_event<E>(
+  exit_events: MessageReader<E>,
+  mut commands: Commands,
+  mut ig: BevyIndigauge,
+  mut players: ResMut<PlayerSessions>,
+  receiver: Option<Res<EventQueueReceiver>>,
+  session_key: Option<Res<SessionApiKey>>,
+) where
   E: Message + std::fmt::Debug,
 {
-  if !exit_events.is_empty()
-    && let Some(key) = session_key
+  if exit_events.is_empty() {
+    return;
+  }
+
+  if let Some(receiver) = receiver {
+    let events = receiver.try_iter().filter(|event| event.validate().is_ok());
+    route_events(events, ig.event_sinks.as_deref(), &mut ig.buffered_events, &mut players);
+  }
+
+  if let Some(sinks) = &ig.event_sinks {
+    sinks.flush_all();
+    buffer_ingested(sinks, &mut ig.buffered_events, &mut players);
+  }
+
+  let player_sessions = players
+    .sessions
+    .drain()
+    .filter_map(|(handle, session)| {
+      unregister_session(handle);
+      Some((handle, session.token?, session.events))
+    })
+    .collect::<Vec<_>>();
+
+  #[cfg(not(target_family = "wasm"))]
+  {
+    let deadline = Instant::now() + ig.config.shutdown_timeout();
+
+    if let Some(key) = session_key {
+      let events = ig.buffered_events.events.drain(..).collect::<Vec<_>>();
+      clear_pending_event_count(events.len());
+      ig.final_flush(SessionHandle::DEFAULT, &key, events, deadline.saturating_duration_since(Instant::now()));
+    }
+
+    for (handle, token, events) in player_sessions {
+      ig.final_flush(handle, &token, events, deadline.saturating_duration_since(Instant::now()));
+    }
+  }
+
+  #[cfg(target_family = "wasm")]
   {
-    end_session(ig, key);
+    if let Some(key) = session_key {
+      ig.flush_events(&key);
+      send_end_session(&mut ig, SessionHandle::DEFAULT, &key);
+    }
+
+    for (handle, token, mut events) in player_sessions {
+      ig.flush_session_events(handle, &token, &mut events);
+      send_end_session(&mut ig, handle, &token);
+    }
   }
+
+  commands.remove_resource::<SessionApiKey>();
 }
 
 /// System to start a default session.
@@ -83,22 +154,67 @@ pub fn start_default_session(mut commands: Commands) {
 ///     .run();
 /// }
 /// ```
-pub fn end_session(mut ig: BevyIndigauge, session_key: Res<SessionApiKey>) {
-  ig.flush_events(&session_key);
+///
+/// Remaining events are flushed and the session state is reset, so a new session can be started afterwards
+/// (e.g. when returning to the main menu or switching profiles). A session that is still starting or offline is
+/// abandoned together with its buffered events.
+pub fn end_session(
+  mut commands: Commands,
+  mut ig: BevyIndigauge,
+  mut players: ResMut<PlayerSessions>,
+  receiver: Option<Res<EventQueueReceiver>>,
+  session_key: Option<Res<SessionApiKey>>,
+) {
+  if let Some(receiver) = receiver {
+    let events = receiver.try_iter().filter(|event| event.validate().is_ok());
+    route_events(events, ig.event_sinks.as_deref(), &mut ig.buffered_events, &mut players);
+  }
 
-  match ig.runtime_client().end_session(&session_key, "ended") {
-    Ok(request) => {
-      ig.reqwest_client.send(request);
+  if let Some(sinks) = &ig.event_sinks {
+    sinks.flush_all();
+    buffer_ingested(sinks, &mut ig.buffered_events, &mut players);
+  }
+
+  if let Some(key) = session_key {
+    while ig.flush_events(&key) > 0 {}
+    send_end_session(&mut ig, SessionHandle::DEFAULT, &key);
+  }
+
+  ig.buffered_events.events.clear();
+  reset_session();
+
+  commands.remove_resource::<SessionApiKey>();
+  commands.remove_resource::<SessionStartRequest>();
+  commands.remove_resource::<PendingSession>();
+}
+
+pub(crate) fn send_end_session(ig: &mut BevyIndigauge, session: SessionHandle, session_key: &str) {
+  match **ig.mode {
+    IndigaugeMode::Live => match ig.runtime_client().end_session(session_key, "ended") {
+      Ok(request) => {
+        ig.reqwest_client.send(request);
+      },
+      Err(error) => {
+        if **ig.log_level <= IndigaugeLogLevel::Error {
+          error!(message = "Failed to build end session request", ?error);
+        }
+      },
     },
-    Err(error) => {
-      if **ig.log_level <= IndigaugeLogLevel::Error {
-        use bevy::log::error;
-        error!(message = "Failed to build end session request", ?error);
+    IndigaugeMode::Dev => {
+      ig.write_event_file(NdjsonRecordKind::SessionEnd, session, &EndSessionRecord { reason: "ended" });
+      if **ig.log_level <= IndigaugeLogLevel::Info {
+        info!("DEVMODE: end session");
       }
     },
+    _ => {},
   }
 }
 
+/// Ends a session whose start response arrived after it was no longer wanted.
+pub(crate) fn end_orphaned_session(In((session, token)): In<(SessionHandle, String)>, mut ig: BevyIndigauge) {
+  send_end_session(&mut ig, session, &token);
+}
+
 pub(crate) fn handle_updated_metadata<M>(mut session_meta: ResMut<SessionMeta<M>>)
 where
   M: Resource + Serialize,
@@ -119,7 +235,7 @@ pub(crate) fn update_metadata<M>(
 
     if let Some(key) = session_key {
       if let Some(metadata_resource) = metadata {
-        ig.update_metadata(&*metadata_resource, &key);
+        ig.update_metadata(SessionHandle::DEFAULT, &*metadata_resource, &key);
       } else {
         use bevy::log::warn;
         use std::any::type_name;
@@ -132,3 +248,68 @@ pub(crate) fn update_metadata<M>(
     }
   }
 }
+
+/// Returns `true` while the default session or any player session is started.
+pub(crate) fn any_session_active(session_key: Option<Res<SessionApiKey>>, players: Res<PlayerSessions>) -> bool {
+  session_key.is_some() || !players.is_empty()
+}
+
+/// Flushes player sessions whose buffered events reached the batch size.
+pub(crate) fn maybe_flush_player_sessions(mut ig: BevyIndigauge, mut players: ResMut<PlayerSessions>) {
+  let batch_size = ig.config.batch_size();
+  for (handle, session) in players.sessions.iter_mut() {
+    if let Some(token) = session.token.as_deref()
+      && session.events.len() >= batch_size
+    {
+      ig.flush_session_events(*handle, token, &mut session.events);
+    }
+  }
+}
+
+/// Periodic flush of player sessions that falls back to a heartbeat when no events are pending.
+pub(crate) fn flush_player_sessions(mut ig: BevyIndigauge, mut players: ResMut<PlayerSessions>) {
+  for (handle, session) in players.sessions.iter_mut() {
+    if let Some(token) = session.token.as_deref()
+      && ig.flush_session_events(*handle, token, &mut session.events) == 0
+    {
+      ig.send_heartbeat(*handle, token);
+    }
+  }
+}
+
+pub(crate) fn handle_updated_player_metadata(
+  changed: Query<&PlayerSession, Changed<PlayerSession>>,
+  mut players: ResMut<PlayerSessions>,
+) {
+  for player in &changed {
+    if let Some(session) = players.sessions.get_mut(&player.handle()) {
+      session.metadata_changed = true;
+    }
+  }
+}
+
+/// Sends the metadata of player sessions that changed since the last sync.
+pub(crate) fn update_player_metadata(
+  player_sessions: Query<&PlayerSession>,
+  mut players: ResMut<PlayerSessions>,
+  mut ig: BevyIndigauge,
+) {
+  for player in &player_sessions {
+    let Some(session) = players.sessions.get_mut(&player.handle()) else {
+      continue;
+    };
+    let (Some(token), Some(metadata)) = (session.token.as_deref(), player.metadata()) else {
+      continue;
+    };
+
+    if session.metadata_changed {
+      session.metadata_changed = false;
+      ig.update_metadata(player.handle(), metadata, token);
+    }
+  }
+}
+
+/// Links a newly started (or renewed) session to the account the player was identified as.
+pub(crate) fn send_player_account(mut ig: BevyIndigauge, account: Res<PlayerAccount>, session_key: Res<SessionApiKey>) {
+  ig.send_identify(&account.0, &session_key);
+}
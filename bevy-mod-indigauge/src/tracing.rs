@@ -5,18 +5,13 @@ use indigauge_core::tracing::IndigaugeSink;
 use indigauge_core::types::IndigaugeLogLevel;
 use std::sync::Arc;
 
+pub use indigauge_core::filter::{FilterHandle, LevelDirectives};
 pub use indigauge_core::tracing::IndigaugeLayer;
 
 impl BevyIndigaugeLogLevel {
   /// Returns a lowercase string representation of the configured log level.
   pub fn as_str(&self) -> &'static str {
-    match **self {
-      IndigaugeLogLevel::Debug => "debug",
-      IndigaugeLogLevel::Info => "info",
-      IndigaugeLogLevel::Warn => "warn",
-      IndigaugeLogLevel::Error => "error",
-      IndigaugeLogLevel::Silent => "silent",
-    }
+    self.0.as_str()
   }
 }
 
@@ -27,7 +22,7 @@ impl From<&Level> for BevyIndigaugeLogLevel {
       Level::WARN => BevyIndigaugeLogLevel(IndigaugeLogLevel::Warn),
       Level::INFO => BevyIndigaugeLogLevel(IndigaugeLogLevel::Info),
       Level::DEBUG => BevyIndigaugeLogLevel(IndigaugeLogLevel::Debug),
-      Level::TRACE => BevyIndigaugeLogLevel(IndigaugeLogLevel::Silent),
+      Level::TRACE => BevyIndigaugeLogLevel(IndigaugeLogLevel::Trace),
     }
   }
 }
@@ -37,7 +32,7 @@ struct EnqueueSink;
 impl IndigaugeSink for EnqueueSink {
   fn log(
     &self,
-    level: &'static str,
+    level: IndigaugeLogLevel,
     event_type: &str,
     metadata: Option<serde_json::Value>,
     file: &'static str,
@@ -1,17 +1,35 @@
+#[cfg(not(target_family = "wasm"))]
+use std::time::Duration;
+
 use bevy::ecs::observer::On;
-use bevy::ecs::system::{Res, ResMut, SystemParam};
+use bevy::ecs::system::{Commands, Res, ResMut, SystemParam};
+#[cfg(not(target_family = "wasm"))]
+use bevy::log::warn;
 use bevy::log::{error, info};
+use indigauge_core::consent::effective_consent;
+use indigauge_core::event::QueuedEvent;
+#[cfg(feature = "otlp")]
+use indigauge_core::http::is_retryable_status;
+use indigauge_core::http::{
+  ResponseDisposition, classify_status, get_or_init_player_id, is_session_invalidated, response_disposition_for_level,
+  should_log_transport_error,
+};
+use indigauge_core::ndjson::NdjsonRecordKind;
+#[cfg(feature = "otlp")]
+use indigauge_core::otlp::{OtlpMode, OtlpResource};
 #[cfg(not(target_family = "wasm"))]
-use indigauge_core::http::get_or_init_player_id;
-use indigauge_core::http::{ResponseDisposition, response_disposition_for_level, should_log_transport_error};
+use indigauge_core::runtime::IndigaugeBlockingRuntimeClient;
 use indigauge_core::runtime::IndigaugeRuntimeClient;
-use indigauge_core::state::clear_pending_event_count;
-use indigauge_core::types::BatchEventPayload;
+use indigauge_core::state::{SessionHandle, clear_pending_event_count, track_pending_event};
+use indigauge_core::types::{BatchEventPayload, EventPayload, IdentifyPayload};
+use indigauge_core::utils::{Instant, select};
 use serde::Serialize;
 
 use crate::config::*;
-use crate::event::resources::BufferedEvents;
+use crate::event::resources::{BufferedEvents, FlushStatus, LastEventFlush};
 use crate::http_runtime::{BevyReqwest, ReqwestErrorEvent, ReqwestResponseEvent};
+use crate::session::events::SessionInvalidatedEvent;
+use crate::session::resources::{PlayerIdProvider, PlayerSessions};
 
 #[cfg(feature = "feedback")]
 use indigauge_core::types::FeedbackPayload;
@@ -27,8 +45,15 @@ pub struct BevyIndigauge<'w, 's> {
   pub reqwest_client: BevyReqwest<'w, 's>,
   pub config: Res<'w, BevyIndigaugeConfig>,
   pub buffered_events: ResMut<'w, BufferedEvents>,
+  pub last_flush: ResMut<'w, LastEventFlush>,
   pub log_level: Res<'w, BevyIndigaugeLogLevel>,
   pub mode: Res<'w, BevyIndigaugeMode>,
+  pub player_id_provider: Option<Res<'w, PlayerIdProvider>>,
+  #[cfg(not(target_family = "wasm"))]
+  pub event_file: Option<Res<'w, BevyIndigaugeEventFile>>,
+  pub event_sinks: Option<Res<'w, BevyIndigaugeEventSinks>>,
+  #[cfg(feature = "otlp")]
+  pub otlp: Option<Res<'w, BevyIndigaugeOtlp>>,
 }
 
 impl<'w, 's> BevyIndigauge<'w, 's> {
@@ -36,6 +61,46 @@ impl<'w, 's> BevyIndigauge<'w, 's> {
     IndigaugeRuntimeClient::with_client(self.config.0.clone(), self.reqwest_client.client().clone())
   }
 
+  /// Returns `true` if records are written to a [`BevyIndigaugeEventFile`].
+  pub(crate) fn has_event_file(&self) -> bool {
+    #[cfg(not(target_family = "wasm"))]
+    return self.event_file.is_some();
+    #[cfg(target_family = "wasm")]
+    false
+  }
+
+  /// Appends a record to the [`BevyIndigaugeEventFile`], if one is set and the player consents to telemetry.
+  #[cfg(not(target_family = "wasm"))]
+  pub(crate) fn write_event_file<T>(&self, kind: NdjsonRecordKind, session: SessionHandle, data: &T)
+  where
+    T: Serialize,
+  {
+    let Some(event_file) = &self.event_file else {
+      return;
+    };
+    if !effective_consent().allows_session() {
+      return;
+    }
+
+    let result = event_file
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .write(kind, Some(session), data);
+    if let Err(error) = result
+      && **self.log_level <= IndigaugeLogLevel::Warn
+    {
+      warn!(message = "Failed to write event file", ?error);
+    }
+  }
+
+  /// Event files are not available on the web.
+  #[cfg(target_family = "wasm")]
+  pub(crate) fn write_event_file<T>(&self, _kind: NdjsonRecordKind, _session: SessionHandle, _data: &T)
+  where
+    T: Serialize,
+  {
+  }
+
   #[cfg(feature = "feedback")]
   pub(crate) fn send_feedback_screenshot(&mut self, api_key: &str, feedback_id: &str, image_data: Vec<u8>) {
     match **self.mode {
@@ -96,59 +161,147 @@ impl<'w, 's> BevyIndigauge<'w, 's> {
           }
         },
       },
-      IndigaugeMode::Dev if **self.log_level <= IndigaugeLogLevel::Info => {
-        info!(message = "DEVMODE: Sent feedback", feedback = ?payload);
+      IndigaugeMode::Dev => {
+        self.write_event_file(NdjsonRecordKind::Feedback, SessionHandle::DEFAULT, payload);
+        if **self.log_level <= IndigaugeLogLevel::Info {
+          info!(message = "DEVMODE: Sent feedback", feedback = ?payload);
+        }
       },
       _ => {},
     }
   }
 
   pub(crate) fn flush_events(&mut self, api_key: &str) -> usize {
+    // Events the page already delivered while it was hidden are still buffered and tracked; skip them.
+    #[cfg(target_family = "wasm")]
+    {
+      let mut delivered = Vec::new();
+      self.buffered_events.events.retain(|event| {
+        let key = event.payload().idempotency_key();
+        let flushed = indigauge_core::web::take_unload_flushed(key);
+        if flushed {
+          delivered.push(key.to_string());
+        }
+        !flushed
+      });
+      if !delivered.is_empty() {
+        indigauge_core::state::untrack_pending_events(&delivered.iter().map(String::as_str).collect::<Vec<_>>());
+      }
+    }
+
     let event_len = self.buffered_events.events.len();
     if event_len == 0 {
       return 0;
     }
 
+    let events = self
+      .buffered_events
+      .events
+      .drain(..(event_len.min(self.config.batch_size())))
+      .collect::<Vec<_>>();
+    clear_pending_event_count(events.len());
+
+    self.send_event_batch(SessionHandle::DEFAULT, api_key, events)
+  }
+
+  /// Sends up to one batch of `events` for an additional session.
+  pub(crate) fn flush_session_events(
+    &mut self,
+    session: SessionHandle,
+    api_key: &str,
+    events: &mut Vec<QueuedEvent>,
+  ) -> usize {
+    if events.is_empty() {
+      return 0;
+    }
+
+    let batch = events.drain(..(events.len().min(self.config.batch_size()))).collect();
+    self.send_event_batch(session, api_key, batch)
+  }
+
+  fn send_event_batch(&mut self, session: SessionHandle, api_key: &str, events: Vec<QueuedEvent>) -> usize {
     let events = BatchEventPayload {
-      events: self
-        .buffered_events
-        .events
-        .drain(..(event_len.min(self.config.batch_size())))
-        .map(|event| event.into_inner())
-        .collect::<Vec<_>>(),
+      events: events.into_iter().map(QueuedEvent::into_inner).collect(),
     };
-    clear_pending_event_count(events.events.len());
+
+    let tracked = session.is_default();
+    if tracked {
+      *self.last_flush = LastEventFlush {
+        at: Some(Instant::now()),
+        count: events.events.len(),
+        status: select(FlushStatus::Pending, FlushStatus::Local, **self.mode == IndigaugeMode::Live),
+      };
+    }
+
+    #[cfg(feature = "otlp")]
+    if **self.mode == IndigaugeMode::Live && self.export_otlp(session, &events.events) {
+      return events.events.len();
+    }
 
     match **self.mode {
       IndigaugeMode::Live => match self.runtime_client().event_batch(api_key, &events) {
         Ok(request) => {
+          // Events of a batch rejected with an expired token are replayed once the session is renewed.
+          let mut unsent_events = events.events.clone();
           self
             .reqwest_client
             .send(request)
-            .on_response(|trigger: On<ReqwestResponseEvent>, log_level: Res<BevyIndigaugeLogLevel>| {
-              match response_disposition_for_level(&log_level, trigger.event().status()) {
-                Some(ResponseDisposition::Success) => info!(message = "Event batch sent successfully"),
-                Some(ResponseDisposition::Failure) => {
-                  let status = trigger.event().status();
-                  error!(message = "Failed to send event batch", ?status);
-                },
-                None => {},
-              }
-            })
-            .on_error(|trigger: On<ReqwestErrorEvent>, log_level: Res<BevyIndigaugeLogLevel>| {
-              if should_log_transport_error(&log_level) {
-                error!(message = "Failed to send event batch", error = ?trigger.event().error);
-              }
-            });
+            .on_response(
+              move |trigger: On<ReqwestResponseEvent>,
+                    mut commands: Commands,
+                    mut last_flush: ResMut<LastEventFlush>,
+                    log_level: Res<BevyIndigaugeLogLevel>| {
+                let status = trigger.event().status();
+                if tracked {
+                  last_flush.status = match classify_status(status) {
+                    ResponseDisposition::Success => FlushStatus::Sent,
+                    ResponseDisposition::Failure => FlushStatus::Failed(status.to_string()),
+                  };
+                }
+
+                if is_session_invalidated(status, trigger.event().body()) {
+                  let unsent_events = std::mem::take(&mut unsent_events);
+                  commands.trigger(SessionInvalidatedEvent { session, unsent_events });
+                  return;
+                }
+
+                match response_disposition_for_level(&log_level, trigger.event().status()) {
+                  Some(ResponseDisposition::Success) => info!(message = "Event batch sent successfully"),
+                  Some(ResponseDisposition::Failure) => {
+                    let status = trigger.event().status();
+                    error!(message = "Failed to send event batch", ?status);
+                  },
+                  None => {},
+                }
+              },
+            )
+            .on_error(
+              move |trigger: On<ReqwestErrorEvent>,
+                    mut last_flush: ResMut<LastEventFlush>,
+                    log_level: Res<BevyIndigaugeLogLevel>| {
+                if tracked {
+                  last_flush.status = FlushStatus::Failed(trigger.event().error.to_string());
+                }
+                if should_log_transport_error(&log_level) {
+                  error!(message = "Failed to send event batch", error = ?trigger.event().error);
+                }
+              },
+            );
         },
         Err(error) => {
           if **self.log_level <= IndigaugeLogLevel::Error {
             error!(message = "Failed to build event batch request", ?error);
           }
+          if tracked {
+            self.last_flush.status = FlushStatus::Failed(error.to_string());
+          }
+          // Keep the events for the next flush instead of dropping them.
+          requeue_events(session, events.events, &mut self.buffered_events, None);
+          return 0;
         },
       },
       IndigaugeMode::Dev if **self.log_level <= IndigaugeLogLevel::Info => {
-        info!(message = "DEVMODE: sending event batch", count = events.events.len());
+        info!(message = "DEVMODE: sending event batch", session = session.id(), count = events.events.len());
       },
       _ => {},
     }
@@ -156,23 +309,213 @@ impl<'w, 's> BevyIndigauge<'w, 's> {
     events.events.len()
   }
 
-  pub(crate) fn send_heartbeat(&mut self, api_key: &str) {
+  /// Synchronously sends `events` and ends the session behind `api_key`, blocking for at most `timeout`.
+  ///
+  /// Used on exit, where requests spawned through [`BevyReqwest`] would not complete before the process terminates.
+  #[cfg(not(target_family = "wasm"))]
+  pub(crate) fn final_flush(
+    &mut self,
+    session: SessionHandle,
+    api_key: &str,
+    events: Vec<QueuedEvent>,
+    timeout: Duration,
+  ) {
+    let events = events.into_iter().map(QueuedEvent::into_inner).collect::<Vec<_>>();
+
+    match **self.mode {
+      IndigaugeMode::Live => {
+        #[cfg(feature = "otlp")]
+        let (events, timeout) = self.export_otlp_blocking(session, events, timeout);
+        let count = events.len();
+        let report = IndigaugeBlockingRuntimeClient::new(self.config.0.clone())
+          .flush_and_end_session(api_key, events, "ended", timeout);
+
+        if report.dropped_events > 0 || !report.session_ended {
+          if **self.log_level <= IndigaugeLogLevel::Warn {
+            warn!(
+              message = "Final flush incomplete",
+              sent = report.sent_events,
+              dropped = report.dropped_events,
+              session_ended = report.session_ended
+            );
+          }
+        } else if **self.log_level <= IndigaugeLogLevel::Info {
+          info!(message = "Final flush completed", count);
+        }
+      },
+      IndigaugeMode::Dev => {
+        self.write_event_file(NdjsonRecordKind::SessionEnd, session, &EndSessionRecord { reason: "ended" });
+        if **self.log_level <= IndigaugeLogLevel::Info {
+          info!(message = "DEVMODE: final flush and end session", count = events.len());
+        }
+      },
+      _ => {},
+    }
+  }
+
+  /// Returns the OTLP resource of a session. Only the default session exports the player id.
+  #[cfg(feature = "otlp")]
+  fn otlp_resource(&self, otlp: &BevyIndigaugeOtlp, session: SessionHandle) -> OtlpResource {
+    OtlpResource::new(self.config.game_name(), self.config.game_version())
+      .with_session_id(otlp.session_id(session))
+      .with_player_id(session.is_default().then(|| self.get_or_init_player_id()).flatten())
+      .with_session_start(session)
+  }
+
+  /// Exports events to the OTLP collector, if one is set. Returns `true` if the export replaces the event batch.
+  ///
+  /// With [`OtlpMode::Replace`], the export reports the outcome in [`LastEventFlush`] and buffers the events again
+  /// when it fails with a retryable status or a transport error. If the request cannot be built, the events are sent
+  /// to Indigauge instead.
+  #[cfg(feature = "otlp")]
+  fn export_otlp(&mut self, session: SessionHandle, events: &[EventPayload]) -> bool {
+    let Some(otlp) = &self.otlp else {
+      return false;
+    };
+
+    let resource = self.otlp_resource(otlp, session);
+    let request = match otlp.logs_request(self.reqwest_client.client(), &resource, events) {
+      Ok(request) => request,
+      Err(error) => {
+        if **self.log_level <= IndigaugeLogLevel::Error {
+          error!(message = "Failed to build OTLP export request", ?error);
+        }
+        return false;
+      },
+    };
+
+    let replace = otlp.config().mode() == OtlpMode::Replace;
+    let tracked = replace && session.is_default();
+    let mut retry_events = if replace { events.to_vec() } else { Vec::new() };
+    let mut failed_events = retry_events.clone();
+    self
+      .reqwest_client
+      .send(request)
+      .on_response(
+        move |trigger: On<ReqwestResponseEvent>,
+              mut buffered_events: ResMut<BufferedEvents>,
+              mut players: ResMut<PlayerSessions>,
+              mut last_flush: ResMut<LastEventFlush>,
+              log_level: Res<BevyIndigaugeLogLevel>| {
+          let status = trigger.event().status();
+          if tracked {
+            last_flush.status = match classify_status(status) {
+              ResponseDisposition::Success => FlushStatus::Sent,
+              ResponseDisposition::Failure => FlushStatus::Failed(status.to_string()),
+            };
+          }
+          if !status.is_success() && is_retryable_status(status) {
+            let events = std::mem::take(&mut retry_events);
+            requeue_events(session, events, &mut buffered_events, Some(&mut players));
+          }
+
+          match response_disposition_for_level(&log_level, status) {
+            Some(ResponseDisposition::Success) => info!(message = "Events exported to OTLP collector"),
+            Some(ResponseDisposition::Failure) => {
+              error!(message = "Failed to export events to OTLP collector", ?status);
+            },
+            None => {},
+          }
+        },
+      )
+      .on_error(
+        move |trigger: On<ReqwestErrorEvent>,
+              mut buffered_events: ResMut<BufferedEvents>,
+              mut players: ResMut<PlayerSessions>,
+              mut last_flush: ResMut<LastEventFlush>,
+              log_level: Res<BevyIndigaugeLogLevel>| {
+          if tracked {
+            last_flush.status = FlushStatus::Failed(trigger.event().error.to_string());
+          }
+          let events = std::mem::take(&mut failed_events);
+          requeue_events(session, events, &mut buffered_events, Some(&mut players));
+
+          if should_log_transport_error(&log_level) {
+            error!(message = "Failed to export events to OTLP collector", error = ?trigger.event().error);
+          }
+        },
+      );
+
+    replace
+  }
+
+  /// Synchronously exports events to the OTLP collector, if one is set, within `timeout`.
+  ///
+  /// Returns the events still to be sent to Indigauge, none with [`OtlpMode::Replace`], and the remaining timeout.
+  #[cfg(all(feature = "otlp", not(target_family = "wasm")))]
+  fn export_otlp_blocking(
+    &self,
+    session: SessionHandle,
+    events: Vec<EventPayload>,
+    timeout: Duration,
+  ) -> (Vec<EventPayload>, Duration) {
+    let Some(otlp) = &self.otlp else {
+      return (events, timeout);
+    };
+    if events.is_empty() {
+      return (events, timeout);
+    }
+
+    let started = Instant::now();
+    let client = IndigaugeBlockingRuntimeClient::new(self.config.0.clone());
+    let result = otlp
+      .logs_request_blocking(client.client(), &self.otlp_resource(otlp, session), &events)
+      .map_err(|error| error.to_string())
+      .and_then(|mut request| {
+        *request.timeout_mut() = Some(timeout.min(otlp.config().timeout()));
+        client.send(request).map_err(|error| error.to_string())
+      });
+
+    match result {
+      Ok(response) if response.status().is_success() => {},
+      Ok(response) => {
+        if **self.log_level <= IndigaugeLogLevel::Warn {
+          warn!(message = "Final OTLP export failed", status = ?response.status());
+        }
+      },
+      Err(error) => {
+        if **self.log_level <= IndigaugeLogLevel::Warn {
+          warn!(message = "Final OTLP export failed", %error);
+        }
+      },
+    }
+
+    let remaining = timeout.saturating_sub(started.elapsed());
+    match otlp.config().mode() {
+      OtlpMode::FanOut => (events, remaining),
+      OtlpMode::Replace => (Vec::new(), remaining),
+    }
+  }
+
+  pub(crate) fn send_heartbeat(&mut self, session: SessionHandle, api_key: &str) {
     match **self.mode {
       IndigaugeMode::Live => match self.runtime_client().heartbeat(api_key) {
         Ok(request) => {
           self
             .reqwest_client
             .send(request)
-            .on_response(|trigger: On<ReqwestResponseEvent>, log_level: Res<BevyIndigaugeLogLevel>| {
-              match response_disposition_for_level(&log_level, trigger.event().status()) {
-                Some(ResponseDisposition::Success) => info!(message = "Heartbeat sent successfully"),
-                Some(ResponseDisposition::Failure) => {
-                  let status = trigger.event().status();
-                  error!(message = "Failed to update heartbeat", ?status);
-                },
-                None => {},
-              }
-            })
+            .on_response(
+              move |trigger: On<ReqwestResponseEvent>,
+                    mut commands: Commands,
+                    log_level: Res<BevyIndigaugeLogLevel>| {
+                if is_session_invalidated(trigger.event().status(), trigger.event().body()) {
+                  commands.trigger(SessionInvalidatedEvent {
+                    session,
+                    unsent_events: Vec::new(),
+                  });
+                  return;
+                }
+
+                match response_disposition_for_level(&log_level, trigger.event().status()) {
+                  Some(ResponseDisposition::Success) => info!(message = "Heartbeat sent successfully"),
+                  Some(ResponseDisposition::Failure) => {
+                    let status = trigger.event().status();
+                    error!(message = "Failed to update heartbeat", ?status);
+                  },
+                  None => {},
+                }
+              },
+            )
             .on_error(|trigger: On<ReqwestErrorEvent>, log_level: Res<BevyIndigaugeLogLevel>| {
               if should_log_transport_error(&log_level) {
                 error!(message = "Failed to send session heartbeat", error = ?trigger.event().error);
@@ -186,13 +529,13 @@ impl<'w, 's> BevyIndigauge<'w, 's> {
         },
       },
       IndigaugeMode::Dev if **self.log_level <= IndigaugeLogLevel::Info => {
-        info!("DEVMODE: heartbeat");
+        info!(message = "DEVMODE: heartbeat", session = session.id());
       },
       _ => {},
     }
   }
 
-  pub(crate) fn update_metadata<T>(&mut self, meta: &T, api_key: &str)
+  pub(crate) fn update_metadata<T>(&mut self, session: SessionHandle, meta: &T, api_key: &str)
   where
     T: Serialize,
   {
@@ -234,15 +577,91 @@ impl<'w, 's> BevyIndigauge<'w, 's> {
           }
         },
       },
+      IndigaugeMode::Dev => {
+        self.write_event_file(NdjsonRecordKind::Metadata, session, &metadata);
+        if **self.log_level <= IndigaugeLogLevel::Info {
+          info!(message = "DEVMODE: update metadata", ?metadata);
+        }
+      },
+      _ => {},
+    }
+  }
+
+  /// Returns the player id from the configured provider or local storage, or `None` if the player did not consent
+  /// to a persistent id.
+  pub(crate) fn get_or_init_player_id(&self) -> Option<String> {
+    if !effective_consent().allows_player_id() {
+      return None;
+    }
+
+    self
+      .player_id_provider
+      .as_ref()
+      .and_then(|provider| provider.player_id())
+      .or_else(|| Some(get_or_init_player_id(self.config.game_name())))
+  }
+
+  pub(crate) fn send_identify(&mut self, account_id: &str, api_key: &str) {
+    match **self.mode {
+      IndigaugeMode::Live => match self.runtime_client().identify(api_key, &IdentifyPayload { account_id }) {
+        Ok(request) => {
+          self
+            .reqwest_client
+            .send(request)
+            .on_response(|trigger: On<ReqwestResponseEvent>, log_level: Res<BevyIndigaugeLogLevel>| {
+              match response_disposition_for_level(&log_level, trigger.event().status()) {
+                Some(ResponseDisposition::Success) => info!(message = "Player identified successfully"),
+                Some(ResponseDisposition::Failure) => {
+                  let status = trigger.event().status();
+                  error!(message = "Failed to identify player", ?status);
+                },
+                None => {},
+              }
+            })
+            .on_error(|trigger: On<ReqwestErrorEvent>, log_level: Res<BevyIndigaugeLogLevel>| {
+              if should_log_transport_error(&log_level) {
+                error!(message = "Failed to send identify request", error = ?trigger.event().error);
+              }
+            });
+        },
+        Err(error) => {
+          if **self.log_level <= IndigaugeLogLevel::Error {
+            error!(message = "Failed to build identify request", ?error);
+          }
+        },
+      },
       IndigaugeMode::Dev if **self.log_level <= IndigaugeLogLevel::Info => {
-        info!(message = "DEVMODE: update metadata", ?metadata);
+        info!(message = "DEVMODE: identify player", account_id);
       },
       _ => {},
     }
   }
+}
 
-  #[cfg(not(target_family = "wasm"))]
-  pub(crate) fn get_or_init_player_id(&self) -> String {
-    get_or_init_player_id(self.config.game_name())
+/// Data of a [`NdjsonRecordKind::SessionEnd`] record.
+#[derive(Serialize)]
+pub(crate) struct EndSessionRecord {
+  pub reason: &'static str,
+}
+
+/// Buffers events of a failed batch again, ahead of newer events, so the next flush retries them.
+///
+/// Events of a player session are only buffered again while the session is active.
+pub(crate) fn requeue_events(
+  session: SessionHandle,
+  events: Vec<EventPayload>,
+  buffered_events: &mut BufferedEvents,
+  players: Option<&mut PlayerSessions>,
+) {
+  let events = events
+    .into_iter()
+    .map(|event| QueuedEvent::new(event).with_session(session));
+
+  if session.is_default() {
+    let events = events.collect::<Vec<_>>();
+    events.iter().cloned().for_each(track_pending_event);
+    buffered_events.events.splice(0..0, events);
+  } else if let Some(player) = players.and_then(|players| players.sessions.get_mut(&session)) {
+    player.events.splice(0..0, events);
   }
 }
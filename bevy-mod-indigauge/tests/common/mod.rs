@@ -0,0 +1,52 @@
+#![allow(dead_code)]
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy::render::RenderPlugin;
+use bevy::render::settings::{RenderCreation, WgpuSettings};
+
+/// Headless app with the default plugins and no GPU backend.
+pub fn get_app() -> App {
+  get_app_with_log_plugin(LogPlugin::default())
+}
+
+/// Headless app like [`get_app`] with a custom `LogPlugin`.
+pub fn get_app_with_log_plugin(log_plugin: LogPlugin) -> App {
+  let mut app = App::new();
+
+  app.add_plugins(
+    DefaultPlugins
+      .set(log_plugin)
+      .set(WindowPlugin {
+        primary_window: None,
+        exit_condition: bevy::window::ExitCondition::DontExit,
+        close_when_requested: false,
+        ..default()
+      })
+      .set(RenderPlugin {
+        render_creation: RenderCreation::Automatic(Box::new(WgpuSettings {
+          backends: None,
+          ..default()
+        })),
+        ..default()
+      })
+      .disable::<bevy::winit::WinitPlugin>(),
+  );
+  app
+}
+
+/// Updates the app until `condition` holds, giving in-flight requests time to complete.
+pub fn update_until(app: &mut App, condition: impl Fn(&World) -> bool) -> bool {
+  let deadline = Instant::now() + Duration::from_secs(10);
+  while Instant::now() < deadline {
+    app.update();
+    if condition(app.world()) {
+      return true;
+    }
+    thread::sleep(Duration::from_millis(10));
+  }
+  false
+}
@@ -0,0 +1,106 @@
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::test_util::StubIngest;
+use indigauge_core::types::EventPayload;
+use indigauge_core::utils::{game_data_dir, set_data_dir};
+
+mod common;
+
+use common::{get_app, update_until};
+
+const GAME_NAME: &str = "indigauge consent test";
+
+#[derive(Resource, Default)]
+struct Skipped(Option<String>);
+
+#[test]
+fn consent_gates_sessions_and_revocation_purges_events() {
+  let data_dir = std::env::temp_dir().join(format!("indigauge-consent-test-{}", std::process::id()));
+  set_data_dir(&data_dir);
+  let game_dir = game_data_dir(GAME_NAME).expect("game data dir");
+
+  let ingest = StubIngest::spawn(|request| match request.path.as_str() {
+    "/v1/sessions/start" => ("200 OK", r#"{"sessionToken":"consent-token"}"#.to_string()),
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let sunk = Arc::new(Mutex::new(Vec::<String>::new()));
+  let sink = {
+    let sunk = sunk.clone();
+    move |_session: SessionHandle, events: &[EventPayload]| {
+      let types = events.iter().map(|event| event.event_type().to_string());
+      sunk.lock().unwrap().extend(types);
+    }
+  };
+
+  let mut app = get_app();
+  app
+    .add_plugins(
+      IndigaugePlugin::<EmptySessionMeta>::new("public-key", GAME_NAME, "1.0.0")
+        .mode(IndigaugeMode::Live)
+        .api_base(&ingest.api_base)
+        .require_consent()
+        .sink("recorder", sink, SinkOptions::new().with_batch_size(100)),
+    )
+    .init_resource::<Skipped>()
+    .add_observer(|event: On<IndigaugeInitDoneEvent>, mut skipped: ResMut<Skipped>| {
+      if let IndigaugeInitDoneEvent::Skipped(reason) = event.event() {
+        skipped.0 = Some(reason.clone());
+      }
+    });
+
+  assert_eq!(**app.world().resource::<BevyTelemetryConsent>(), TelemetryConsent::Unknown);
+
+  // Consent is required, so nothing starts while the player has not decided.
+  app.world_mut().trigger(StartSessionEvent::new());
+  app.update();
+  assert_eq!(app.world().resource::<Skipped>().0.as_deref(), Some("Telemetry consent denied"));
+  assert!(ingest.requests_to("/v1/sessions/start").is_empty());
+
+  app
+    .world_mut()
+    .trigger(SetTelemetryConsentEvent(TelemetryConsent::Granted));
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+  assert!(ingest.requests_to("/v1/sessions/start")[0].body["playerId"].is_string());
+  assert!(game_dir.join("player_id.txt").exists());
+
+  ig_info!("menu.open");
+  app.update();
+  assert_eq!(indigauge_core::state::get_session_token().as_deref(), Some("consent-token"));
+
+  // Narrowing the consent dispatches the queued events it still allows and purges the sink buffers.
+  ig_info!("menu.hover");
+  ig_error!("menu.error");
+  app
+    .world_mut()
+    .trigger(SetTelemetryConsentEvent(TelemetryConsent::Essential));
+  app.update();
+  assert!(app.world().resource::<BevyIndigaugeEventSinks>().remove("recorder"));
+  assert_eq!(*sunk.lock().unwrap(), ["menu.error"]);
+
+  app
+    .world_mut()
+    .trigger(SetTelemetryConsentEvent(TelemetryConsent::Denied));
+  app.update();
+
+  assert!(!app.world().contains_resource::<SessionApiKey>());
+  assert!(indigauge_core::state::drain_pending_events().is_empty());
+  assert!(!indigauge_core::state::enqueue(
+    IndigaugeLogLevel::Error,
+    "menu.crash",
+    None,
+    file!(),
+    line!(),
+    module_path!()
+  ));
+  assert!(update_until(&mut app, |_| !ingest.requests_to("/v1/sessions/end").is_empty()));
+  assert!(ingest.requests_to("/v1/events/batch").is_empty());
+
+  assert_eq!(fs::read_to_string(game_dir.join("consent.txt")).ok().as_deref(), Some("denied"));
+  assert!(!game_dir.join("player_id.txt").exists());
+  let _ = fs::remove_dir_all(data_dir);
+}
@@ -0,0 +1,23 @@
+use bevy_mod_indigauge::prelude::*;
+
+mod common;
+
+use common::get_app;
+
+#[test]
+fn dev_mode_prints_events_unless_console_output_is_off() {
+  let sink_names = |plugin: IndigaugePlugin<EmptySessionMeta>| {
+    let mut app = get_app();
+    app.add_plugins(plugin);
+    let sinks = app.world().resource::<BevyIndigaugeEventSinks>();
+    sinks.lock().unwrap().names().map(str::to_string).collect::<Vec<_>>()
+  };
+  let plugin = || IndigaugePlugin::<EmptySessionMeta>::new("", "console game", "1.0.0").mode(IndigaugeMode::Dev);
+
+  assert_eq!(sink_names(plugin()), [CONSOLE_SINK, INGEST_SINK]);
+  assert_eq!(
+    sink_names(plugin().console_output(ConsolePrinter::new().with_verbosity(ConsoleVerbosity::Off))),
+    [INGEST_SINK]
+  );
+  assert_eq!(sink_names(plugin().log_level(IndigaugeLogLevel::Warn)), [INGEST_SINK]);
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "feedback")]
+
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+
+mod common;
+
+use common::{get_app, update_until};
+
+#[test]
+fn dev_overlay_shows_events_filtered_by_namespace() {
+  let mut app = get_app();
+  app.add_plugins(
+    IndigaugePlugin::<EmptySessionMeta>::new("", "overlay game", "1.0.0")
+      .mode(IndigaugeMode::Dev)
+      .dev_overlay(),
+  );
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+
+  ig_info!("combat.hit", { "damage": 3 });
+  ig_warn!("ui.menu.slow");
+  ig_error!("combat.crash");
+  assert!(update_until(&mut app, |world| world.resource::<DevOverlayState>().entries().count() == 3));
+
+  let mut state = app.world_mut().resource_mut::<DevOverlayState>();
+  assert_eq!(state.namespaces().collect::<Vec<_>>(), [("combat", true), ("ui", true)]);
+  assert!(state.entries().nth(1).unwrap().event.context().is_some());
+
+  state.toggle_namespace("combat");
+  state.visible = true;
+  let shown = state
+    .entries()
+    .map(|entry| entry.event.event_type().to_string())
+    .collect::<Vec<_>>();
+  assert_eq!(shown, ["ui.menu.slow"]);
+
+  app.update();
+  app.update();
+  #[cfg(not(feature = "feedback_egui"))]
+  {
+    let mut texts = app.world_mut().query::<&Text>();
+    let texts = texts.iter(app.world()).map(|text| text.0.clone()).collect::<Vec<_>>();
+    assert!(texts.iter().any(|text| text.contains("Mode: dev")), "{texts:?}");
+    assert!(texts.iter().any(|text| text.contains("ui.menu.slow")));
+    assert!(!texts.iter().any(|text| text.contains("combat.hit")));
+  }
+}
@@ -0,0 +1,59 @@
+use std::fs;
+
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::utils::set_data_dir;
+
+mod common;
+
+use common::{get_app, update_until};
+
+#[test]
+fn dev_mode_writes_sessions_and_events_to_the_event_file() {
+  let dir = std::env::temp_dir().join(format!("indigauge-event-file-{}", std::process::id()));
+  let path = dir.join("playtest.ndjson");
+  set_data_dir(dir.join("data"));
+
+  let mut app = get_app();
+  app.add_plugins(
+    IndigaugePlugin::<EmptySessionMeta>::new("", "event file game", "1.0.0")
+      .mode(IndigaugeMode::Dev)
+      .event_file(NdjsonConfig::new(&path)),
+  );
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+
+  ig_info!("menu.open", { "tab": "settings" });
+  app.world_mut().run_system_cached(end_session).expect("end session");
+  app.update();
+
+  let records = fs::read_to_string(&path)
+    .unwrap()
+    .lines()
+    .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+    .collect::<Vec<_>>();
+  let kinds = records
+    .iter()
+    .map(|record| record["kind"].as_str().unwrap())
+    .collect::<Vec<_>>();
+  assert_eq!(kinds, ["session_start", "event", "session_end"]);
+  assert_eq!(records[0]["data"]["clientVersion"], "1.0.0");
+  assert_eq!(records[1]["session"], 0);
+  assert_eq!(records[1]["data"]["eventType"], "menu.open");
+  assert_eq!(records[1]["data"]["metadata"]["tab"], "settings");
+
+  app
+    .world_mut()
+    .trigger(SetTelemetryConsentEvent(TelemetryConsent::Denied));
+  app.update();
+  assert!(!path.exists());
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  ig_info!("menu.close");
+  app.update();
+  app.world_mut().remove_resource::<BevyIndigaugeEventFile>();
+  assert!(!path.exists());
+
+  let _ = fs::remove_dir_all(dir);
+}
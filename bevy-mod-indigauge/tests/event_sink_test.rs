@@ -0,0 +1,48 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::types::EventPayload;
+
+mod common;
+
+use common::{get_app, update_until};
+
+#[test]
+fn registered_sinks_receive_filtered_batches() {
+  let received = Arc::new(Mutex::new(Vec::<(usize, Vec<String>)>::new()));
+  let sink = {
+    let received = received.clone();
+    move |_session: SessionHandle, events: &[EventPayload]| {
+      let types = events.iter().map(|event| event.event_type().to_string()).collect();
+      received.lock().unwrap().push((events.len(), types));
+    }
+  };
+
+  let mut app = get_app();
+  app.add_plugins(
+    IndigaugePlugin::<EmptySessionMeta>::new("", "sink game", "1.0.0")
+      .mode(IndigaugeMode::Dev)
+      .sink(
+        "combat",
+        sink,
+        SinkOptions::new()
+          .with_namespaces(["combat"])
+          .with_min_level(IndigaugeLogLevel::Info)
+          .with_batch_size(10),
+      ),
+  );
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+
+  ig_info!("combat.hit", { "damage": 3 });
+  ig_debug!("combat.tick");
+  ig_info!("menu.open");
+  ig_warn!("combat.miss");
+  app.update();
+  assert!(received.lock().unwrap().is_empty());
+
+  app.world_mut().run_system_cached(end_session).expect("end session");
+  assert_eq!(*received.lock().unwrap(), vec![(2, vec!["combat.hit".to_string(), "combat.miss".to_string()])]);
+}
@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::test_util::StubIngest;
+
+mod common;
+
+use common::{get_app, update_until};
+
+#[test]
+fn custom_player_id_and_account_link_are_sent() {
+  let ingest = StubIngest::spawn(|request| match request.path.as_str() {
+    "/v1/sessions/start" => ("200 OK", r#"{"sessionToken":"identity-token"}"#.to_string()),
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let mut app = get_app();
+  app.add_plugins(
+    IndigaugePlugin::<EmptySessionMeta>::new("public-key", "identity game", "1.0.0")
+      .mode(IndigaugeMode::Live)
+      .api_base(&ingest.api_base)
+      .player_id_provider(|| Some("custom-player".to_string())),
+  );
+
+  // Identifying before the session exists links the account once it starts.
+  app.world_mut().trigger(IdentifyPlayerEvent::new("account-42"));
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |_| !ingest.requests_to("/v1/sessions/identify").is_empty()));
+
+  let starts = ingest.requests_to("/v1/sessions/start");
+  assert_eq!(starts[0].body["playerId"], "custom-player");
+
+  let identifies = ingest.requests_to("/v1/sessions/identify");
+  assert_eq!(identifies.len(), 1);
+  assert_eq!(identifies[0].api_key, "identity-token");
+  assert_eq!(identifies[0].body["accountId"], "account-42");
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "tracing")]
+
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use bevy_mod_indigauge::tracing::LevelDirectives;
+use indigauge_core::test_util::StubIngest;
+
+mod common;
+
+use common::{get_app_with_log_plugin, update_until};
+
+#[test]
+fn bevy_logs_are_forwarded_through_the_log_layer() {
+  let ingest = StubIngest::spawn(|request| match request.path.as_str() {
+    "/v1/sessions/start" => ("200 OK", r#"{"sessionToken":"log-layer-token"}"#.to_string()),
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let mut app = get_app_with_log_plugin(LogPlugin {
+    custom_layer: indigauge_log_layer,
+    ..default()
+  });
+  app.add_plugins(
+    IndigaugePlugin::<EmptySessionMeta>::new("public-key", "log layer game", "1.0.0")
+      .mode(IndigaugeMode::Live)
+      .api_base(&ingest.api_base)
+      .log_directives(LevelDirectives::parse("log_layer_test=warn").unwrap()),
+  );
+  assert!(app.world().contains_resource::<BevyIndigaugeLogFilter>());
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+
+  bevy::log::error!(ig = "level.load_failed", level = 3, "missing asset");
+  bevy::log::debug!(ig = "level.debug", "not forwarded");
+  bevy::log::info!(ig = "level.info", "dropped by the configured directives");
+
+  let events = indigauge_core::state::drain_pending_events();
+  let event_types = events
+    .iter()
+    .map(|event| event.payload().event_type())
+    .collect::<Vec<_>>();
+  assert!(event_types.contains(&"level.load_failed"));
+  assert!(!event_types.contains(&"level.debug"));
+  assert!(!event_types.contains(&"level.info"));
+}
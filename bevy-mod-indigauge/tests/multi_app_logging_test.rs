@@ -9,7 +9,7 @@ use std::sync::{Mutex, Once, OnceLock};
 
 #[derive(Clone, Debug)]
 struct CapturedEvent {
-  level: &'static str,
+  level: IndigaugeLogLevel,
   event_type: String,
 }
 
@@ -17,7 +17,7 @@ static EVENTS: OnceLock<Mutex<Vec<CapturedEvent>>> = OnceLock::new();
 static DISPATCHER_INIT: Once = Once::new();
 
 fn capture_event(
-  level: &'static str,
+  level: IndigaugeLogLevel,
   event_type: &str,
   _metadata: Option<Value>,
   _file: &'static str,
@@ -99,21 +99,21 @@ fn master_app_plugin_does_not_block_sub_app_ig_events() {
   assert!(
     captured
       .iter()
-      .any(|event| event.event_type == "appone.tick" && event.level == "info")
+      .any(|event| event.event_type == "appone.tick" && event.level == IndigaugeLogLevel::Info)
   );
   assert!(
     captured
       .iter()
-      .any(|event| event.event_type == "appone.warn" && event.level == "warn")
+      .any(|event| event.event_type == "appone.warn" && event.level == IndigaugeLogLevel::Warn)
   );
   assert!(
     captured
       .iter()
-      .any(|event| event.event_type == "apptwo.tick" && event.level == "debug")
+      .any(|event| event.event_type == "apptwo.tick" && event.level == IndigaugeLogLevel::Debug)
   );
   assert!(
     captured
       .iter()
-      .any(|event| event.event_type == "apptwo.error" && event.level == "error")
+      .any(|event| event.event_type == "apptwo.error" && event.level == IndigaugeLogLevel::Error)
   );
 }
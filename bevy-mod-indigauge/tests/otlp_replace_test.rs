@@ -0,0 +1,54 @@
+#![cfg(feature = "otlp")]
+
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::test_util::StubIngest;
+
+mod common;
+
+use common::{get_app, update_until};
+
+const BATCH_SIZE: usize = 64;
+
+#[test]
+fn replaced_batches_are_retried_after_a_failed_export() {
+  let mut exports = 0;
+  let ingest = StubIngest::spawn(move |request| match request.path.as_str() {
+    "/v1/sessions/start" => ("200 OK", r#"{"sessionToken":"otlp-replace-token"}"#.to_string()),
+    "/v1/logs" => {
+      exports += 1;
+      match exports {
+        1 => ("503 Service Unavailable", "{}".to_string()),
+        _ => ("200 OK", "{}".to_string()),
+      }
+    },
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let mut app = get_app();
+  app.add_plugins(
+    IndigaugePlugin::<EmptySessionMeta>::new("public-key", "otlp replace game", "1.0.0")
+      .mode(IndigaugeMode::Live)
+      .api_base(&ingest.api_base)
+      .otlp(OtlpConfig::new(format!("{}/v1/logs", ingest.api_base)).with_mode(OtlpMode::Replace)),
+  );
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+
+  for index in 0..BATCH_SIZE {
+    ig_warn!("otlp.event", { "index": index });
+  }
+
+  assert!(update_until(&mut app, |world| world.resource::<LastEventFlush>().status == FlushStatus::Sent));
+
+  let exports = ingest.requests_to("/v1/logs");
+  assert_eq!(exports.len(), 2);
+  let records = |export: usize| {
+    exports[export].body["resourceLogs"][0]["scopeLogs"][0]["logRecords"]
+      .as_array()
+      .map(Vec::len)
+  };
+  assert_eq!(records(0), Some(BATCH_SIZE));
+  assert_eq!(records(1), Some(BATCH_SIZE));
+  assert!(ingest.requests_to("/v1/events/batch").is_empty());
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "otlp")]
+
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::test_util::StubIngest;
+
+mod common;
+
+use common::{get_app, update_until};
+
+const BATCH_SIZE: usize = 64;
+
+#[test]
+fn event_batches_are_exported_next_to_the_indigauge_batch() {
+  let ingest = StubIngest::spawn(|request| match request.path.as_str() {
+    "/v1/sessions/start" => ("200 OK", r#"{"sessionToken":"otlp-token"}"#.to_string()),
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let mut app = get_app();
+  app.add_plugins(
+    IndigaugePlugin::<EmptySessionMeta>::new("public-key", "otlp game", "1.0.0")
+      .mode(IndigaugeMode::Live)
+      .api_base(&ingest.api_base)
+      .otlp(OtlpConfig::new(format!("{}/v1/logs", ingest.api_base))),
+  );
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+
+  // A full batch is flushed immediately.
+  for index in 0..BATCH_SIZE {
+    ig_warn!("otlp.event", { "index": index });
+  }
+
+  assert!(update_until(&mut app, |_| !ingest.requests_to("/v1/logs").is_empty()
+    && !ingest.requests_to("/v1/events/batch").is_empty()));
+
+  let export = &ingest.requests_to("/v1/logs")[0].body["resourceLogs"][0];
+  let resource = export["resource"]["attributes"].as_array().unwrap();
+  let resource_value = |key: &str| {
+    resource
+      .iter()
+      .find(|attribute| attribute["key"] == key)
+      .map(|attribute| attribute["value"]["stringValue"].clone())
+  };
+  assert_eq!(resource_value("service.name"), Some("otlp game".into()));
+  assert_eq!(resource_value("service.version"), Some("1.0.0".into()));
+  assert!(resource_value("session.id").is_some());
+  assert!(resource_value("user.id").is_some());
+
+  let records = export["scopeLogs"][0]["logRecords"].as_array().unwrap();
+  assert_eq!(records.len(), BATCH_SIZE);
+  assert_eq!(records[0]["severityText"], "WARN");
+  assert_eq!(records[0]["body"]["stringValue"], "otlp.event");
+  assert!(
+    records[0]["attributes"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .any(|attribute| attribute["key"] == "index" && attribute["value"]["intValue"] == "0")
+  );
+
+  let batch = &ingest.requests_to("/v1/events/batch")[0];
+  assert_eq!(batch.api_key, "otlp-token");
+  assert_eq!(batch.body["events"].as_array().map(Vec::len), Some(BATCH_SIZE));
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::state::SessionHandle;
+use indigauge_core::test_util::StubIngest;
+
+mod common;
+
+use common::{get_app, update_until};
+
+const BATCH_SIZE: usize = 64;
+
+#[test]
+fn player_sessions_route_events_by_handle() {
+  let mut second_starts = 0;
+  let ingest = StubIngest::spawn(move |request| match request.path.as_str() {
+    "/v1/sessions/start" => {
+      let player_id = request.body["playerId"].as_str().unwrap_or_default();
+      if player_id == "second" {
+        second_starts += 1;
+        if second_starts == 1 {
+          return ("503 Service Unavailable", "{}".to_string());
+        }
+      }
+      let renewed = if request.body["previousSessionToken"].is_string() {
+        "-renewed"
+      } else {
+        ""
+      };
+      ("200 OK", format!(r#"{{"sessionToken":"token-{player_id}{renewed}"}}"#))
+    },
+    "/v1/events/batch" if request.api_key == "token-first" => {
+      ("401 Unauthorized", r#"{"code":"session_expired","message":"Session expired"}"#.to_string())
+    },
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let mut app = get_app();
+  app.add_plugins(
+    IndigaugePlugin::<EmptySessionMeta>::new("public-key", "couch game", "1.0.0")
+      .mode(IndigaugeMode::Live)
+      .api_base(&ingest.api_base)
+      .retry_backoff(Duration::from_millis(10), Duration::from_millis(50)),
+  );
+
+  let first = PlayerSession::new("first");
+  let first_entity = app.world_mut().spawn(first.clone()).id();
+  let second_entity = app.world_mut().spawn(PlayerSession::new("second")).id();
+  let handle = |app: &App, entity| app.world().get::<PlayerSession>(entity).map(PlayerSession::handle);
+  let (first_handle, second_handle) = (handle(&app, first_entity).unwrap(), handle(&app, second_entity).unwrap());
+
+  // Handles are allocated on insert, so a clone of an inserted component runs its own session.
+  let clone = app.world().get::<PlayerSession>(first_entity).cloned().unwrap();
+  let clone_entity = app.world_mut().spawn(clone).id();
+  assert_ne!(handle(&app, clone_entity), Some(first_handle));
+  assert_eq!(first.handle(), SessionHandle::DEFAULT);
+  app.world_mut().despawn(clone_entity);
+
+  // Events are accepted while the sessions are starting. The first batch is rejected as expired and replayed with
+  // the renewed session.
+  for _ in 0..BATCH_SIZE {
+    ig_info_for!(first_handle, "player.jump");
+  }
+  // Without a default session, the regular macros are dropped.
+  ig_info!("default.event");
+
+  assert!(update_until(&mut app, |world| {
+    let players = world.resource::<PlayerSessions>();
+    players.token(first_handle) == Some("token-first-renewed") && players.token(second_handle).is_some()
+  }));
+  assert!(!app.world().contains_resource::<SessionApiKey>());
+  assert_eq!(app.world().resource::<PlayerSessions>().token(second_handle), Some("token-second"));
+  assert_eq!(second_starts_of(&ingest), 2);
+
+  assert!(update_until(&mut app, |_| ingest
+    .requests_to("/v1/events/batch")
+    .iter()
+    .any(|request| request.api_key == "token-first-renewed")));
+  let batches = ingest.requests_to("/v1/events/batch");
+  assert_eq!(batches.len(), 2);
+  assert_eq!(batches[0].api_key, "token-first");
+  assert_eq!(batches[1].body["events"].as_array().map(Vec::len), Some(BATCH_SIZE));
+
+  app.world_mut().despawn(second_entity);
+  assert!(update_until(&mut app, |_| ingest
+    .requests_to("/v1/sessions/end")
+    .iter()
+    .any(|request| request.api_key == "token-second")));
+
+  assert!(app.world().resource::<PlayerSessions>().token(second_handle).is_none());
+  assert!(app.world().resource::<PlayerSessions>().token(first_handle).is_some());
+}
+
+fn second_starts_of(ingest: &StubIngest) -> usize {
+  ingest
+    .requests_to("/v1/sessions/start")
+    .iter()
+    .filter(|request| request.body["playerId"] == "second")
+    .count()
+}
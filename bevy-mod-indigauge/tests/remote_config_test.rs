@@ -0,0 +1,104 @@
+use std::fs;
+
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::remote_config::{REMOTE_CONFIG_FILE, load_remote_config};
+use indigauge_core::test_util::StubIngest;
+use indigauge_core::utils::{game_data_dir, set_data_dir};
+
+mod common;
+
+use common::{get_app, update_until};
+
+const GAME_NAME: &str = "indigauge remote config test";
+
+#[derive(Resource, Default)]
+struct ChangedKeys(Vec<String>);
+
+#[test]
+fn fetched_config_is_cached_and_flags_record_exposures() {
+  let data_dir = std::env::temp_dir().join(format!("indigauge-remote-config-test-{}", std::process::id()));
+  set_data_dir(&data_dir);
+
+  let ingest = StubIngest::spawn(|request| match request.path.as_str() {
+    "/v1/sessions/start" => ("200 OK", r#"{"sessionToken":"config-token"}"#.to_string()),
+    "/v1/config" => ("200 OK", r#"{"version":"3","values":{"new_hud":true,"enemy_speed":1.5,"shop":"b"}}"#.to_string()),
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let mut app = get_app();
+  app
+    .add_plugins(
+      IndigaugePlugin::<EmptySessionMeta>::new("public-key", GAME_NAME, "1.0.0")
+        .mode(IndigaugeMode::Live)
+        .api_base(&ingest.api_base)
+        .remote_config(
+          RemoteConfig::new()
+            .with_value("new_hud", false)
+            .with_value("enemy_speed", 1.0)
+            .with_value("lives", 3),
+        ),
+    )
+    .init_resource::<ChangedKeys>()
+    .add_observer(|event: On<RemoteConfigChangedEvent>, mut changed: ResMut<ChangedKeys>| {
+      changed.0 = event.event().keys.clone();
+    });
+
+  let remote_config = app.world().resource::<BevyRemoteConfig>();
+  assert!(!remote_config.is_fetched());
+  assert_eq!(remote_config.get_bool("new_hud"), Some(false));
+  // Without a session the exposure is not queued, and it is not retried.
+  assert!(!ig_flag!(remote_config, "new_hud"));
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.resource::<BevyRemoteConfig>().is_fetched()));
+
+  let remote_config = app.world().resource::<BevyRemoteConfig>();
+  assert_eq!(ingest.requests_to("/v1/config")[0].api_key, "config-token");
+  assert_eq!(remote_config.get_f64("enemy_speed"), Some(1.5));
+  assert_eq!(remote_config.get_i64("lives"), Some(3));
+  assert_eq!(app.world().resource::<ChangedKeys>().0, ["enemy_speed", "new_hud", "shop"]);
+  assert_eq!(load_remote_config(GAME_NAME).and_then(|cached| cached.version), Some("3".to_string()));
+
+  assert!(game_data_dir(GAME_NAME).unwrap().join(REMOTE_CONFIG_FILE).exists());
+
+  let remote_config = app.world().resource::<BevyRemoteConfig>();
+  assert!(ig_flag!(remote_config, "new_hud"));
+  assert!(remote_config.flag("new_hud"));
+  assert_eq!(ig_variant!(remote_config, "shop"), Some("b"));
+  app.update();
+  app.world_mut().run_system_cached(end_session).expect("end session");
+  assert!(update_until(&mut app, |_| !ingest.requests_to("/v1/events/batch").is_empty()));
+
+  let batches = ingest.requests_to("/v1/events/batch");
+  let exposures = batches
+    .iter()
+    .flat_map(|batch| batch.body["events"].as_array().cloned().unwrap_or_default())
+    .filter(|event| event["eventType"] == "flag.exposure")
+    .map(|event| event["metadata"].clone())
+    .collect::<Vec<_>>();
+  assert_eq!(exposures.len(), 2);
+  assert_eq!(exposures[0]["flag"], "new_hud");
+  assert_eq!(exposures[0]["value"], true);
+  assert_eq!(exposures[0]["version"], "3");
+  assert_eq!(exposures[1]["flag"], "shop");
+
+  let _ = fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn remote_config_is_available_when_telemetry_is_disabled() {
+  let mut app = get_app();
+  app
+    .add_plugins(
+      IndigaugePlugin::<EmptySessionMeta>::new("public-key", "indigauge disabled remote config test", "1.0.0")
+        .mode(IndigaugeMode::Disabled)
+        .remote_config(RemoteConfig::new().with_value("enemy_speed", 1.0)),
+    )
+    .add_systems(Update, |remote_config: Res<BevyRemoteConfig>| {
+      assert_eq!(remote_config.get_f64("enemy_speed"), Some(1.0));
+    });
+
+  app.update();
+  assert!(!app.world().resource::<BevyRemoteConfig>().is_fetched());
+}
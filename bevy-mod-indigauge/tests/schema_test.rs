@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::test_util::StubIngest;
+use serde_json::json;
+
+mod common;
+
+use common::{get_app, update_until};
+
+#[test]
+fn nonconforming_metadata_is_stripped() {
+  let ingest = StubIngest::spawn(|request| match request.path.as_str() {
+    "/v1/sessions/start" => ("200 OK", r#"{"sessionToken":"schema-token"}"#.to_string()),
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let schemas = SchemaRegistry::new().register_schema(
+    "player.death",
+    json!({
+      "properties": { "cause": { "type": "string" }, "level": { "type": "integer" } },
+      "required": ["cause"],
+      "additionalProperties": false,
+    }),
+  );
+
+  let mut app = get_app();
+  app.add_plugins(
+    IndigaugePlugin::<EmptySessionMeta>::new("public-key", "schema game", "1.0.0")
+      .mode(IndigaugeMode::Live)
+      .api_base(&ingest.api_base)
+      .schema_registry(schemas)
+      .strip_nonconforming_metadata(),
+  );
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+
+  ig_info!("player.death", { "cause": "lava", "level": "three", "debug": true });
+  ig_info!("player.spawn", { "x": 1 });
+  app.update();
+  app.world_mut().run_system_cached(end_session).expect("end session");
+  assert!(update_until(&mut app, |_| !ingest.requests_to("/v1/events/batch").is_empty()));
+
+  let events = &ingest.requests_to("/v1/events/batch")[0].body["events"];
+  assert_eq!(events[0]["metadata"], json!({ "cause": "lava" }));
+  // Events without a registered schema are sent as they are.
+  assert_eq!(events[1]["metadata"], json!({ "x": 1 }));
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::test_util::StubIngest;
+
+mod common;
+
+use common::{get_app, update_until};
+
+#[derive(Resource, Default)]
+struct RenewalFailed(bool);
+
+const BATCH_SIZE: usize = 64;
+
+#[test]
+fn unsent_events_of_a_rejected_renewal_are_sent_with_the_next_session() {
+  let mut start_calls = 0;
+  let ingest = StubIngest::spawn(move |request| match request.path.as_str() {
+    "/v1/sessions/start" => {
+      start_calls += 1;
+      match start_calls {
+        1 => ("200 OK", r#"{"sessionToken":"first-token"}"#.to_string()),
+        2 => ("403 Forbidden", r#"{"code":"forbidden","message":"Renewal refused"}"#.to_string()),
+        _ => ("200 OK", r#"{"sessionToken":"next-token"}"#.to_string()),
+      }
+    },
+    "/v1/events/batch" if request.api_key == "first-token" => {
+      ("401 Unauthorized", r#"{"code":"session_expired","message":"Session expired"}"#.to_string())
+    },
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let mut app = get_app();
+  app
+    .add_plugins(
+      IndigaugePlugin::<EmptySessionMeta>::new("public-key", "renewal failure game", "1.0.0")
+        .mode(IndigaugeMode::Live)
+        .api_base(&ingest.api_base)
+        .retry_backoff(Duration::from_millis(10), Duration::from_millis(50)),
+    )
+    .init_resource::<RenewalFailed>()
+    .add_observer(|event: On<IndigaugeInitDoneEvent>, mut failed: ResMut<RenewalFailed>| {
+      if matches!(event.event(), IndigaugeInitDoneEvent::Failure(_)) {
+        failed.0 = true;
+      }
+    });
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+
+  // A full batch is flushed immediately, rejected because the session expired, and the renewal is refused.
+  for _ in 0..BATCH_SIZE {
+    ig_info!("renewal.event");
+  }
+
+  assert!(update_until(&mut app, |world| world.resource::<RenewalFailed>().0));
+  assert!(!app.world().contains_resource::<PendingSession>());
+  assert!(!app.world().contains_resource::<SessionApiKey>());
+
+  // The next session starts normally and sends the events kept from the rejected batch.
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |_| ingest
+    .requests_to("/v1/events/batch")
+    .iter()
+    .any(|request| request.api_key == "next-token")));
+
+  assert_eq!(ingest.requests_to("/v1/sessions/start").len(), 3);
+  let sent = ingest
+    .requests_to("/v1/events/batch")
+    .into_iter()
+    .find(|request| request.api_key == "next-token")
+    .expect("batch of the next session");
+  assert_eq!(sent.body["events"].as_array().map(Vec::len), Some(BATCH_SIZE));
+}
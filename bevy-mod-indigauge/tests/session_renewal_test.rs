@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::test_util::StubIngest;
+
+mod common;
+
+use common::{get_app, update_until};
+
+#[derive(Resource, Default)]
+struct RenewedFrom(Option<String>);
+
+const BATCH_SIZE: usize = 64;
+
+#[test]
+fn expired_session_is_renewed_and_unsent_batch_replayed() {
+  let mut start_calls = 0;
+  let ingest = StubIngest::spawn(move |request| match request.path.as_str() {
+    "/v1/sessions/start" => {
+      start_calls += 1;
+      let token = if start_calls == 1 {
+        "first-token"
+      } else {
+        "second-token"
+      };
+      ("200 OK", format!(r#"{{"sessionToken":"{token}"}}"#))
+    },
+    "/v1/events/batch" if request.api_key == "first-token" => {
+      ("401 Unauthorized", r#"{"code":"session_expired","message":"Session expired"}"#.to_string())
+    },
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let mut app = get_app();
+  app
+    .add_plugins(
+      IndigaugePlugin::<EmptySessionMeta>::new("public-key", "renewal game", "1.0.0")
+        .mode(IndigaugeMode::Live)
+        .api_base(&ingest.api_base)
+        .retry_backoff(Duration::from_millis(10), Duration::from_millis(50)),
+    )
+    .init_resource::<RenewedFrom>()
+    .add_observer(|event: On<SessionRenewedEvent>, mut renewed: ResMut<RenewedFrom>| {
+      renewed.0 = Some(event.event().previous_session_token.clone());
+    });
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+
+  // A full batch is flushed immediately and rejected because the session expired.
+  for _ in 0..BATCH_SIZE {
+    ig_info!("renewal.event");
+  }
+
+  assert!(update_until(&mut app, |world| world.resource::<RenewedFrom>().0.is_some()));
+  assert_eq!(app.world().resource::<RenewedFrom>().0.as_deref(), Some("first-token"));
+  assert_eq!(app.world().resource::<SessionApiKey>().0, "second-token");
+
+  let starts = ingest.requests_to("/v1/sessions/start");
+  assert_eq!(starts.len(), 2);
+  assert_eq!(starts[1].body["previousSessionToken"], "first-token");
+
+  assert!(update_until(&mut app, |_| ingest
+    .requests_to("/v1/events/batch")
+    .iter()
+    .any(|request| request.api_key == "second-token")));
+
+  let replayed = ingest
+    .requests_to("/v1/events/batch")
+    .into_iter()
+    .find(|request| request.api_key == "second-token")
+    .expect("replayed batch");
+  assert_eq!(replayed.body["events"].as_array().map(Vec::len), Some(BATCH_SIZE));
+}
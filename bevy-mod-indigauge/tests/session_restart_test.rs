@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::test_util::StubIngest;
+
+mod common;
+
+use common::{get_app, update_until};
+
+#[derive(Resource, Default)]
+struct InitCount(usize);
+
+#[test]
+fn session_can_be_started_again_after_it_ended() {
+  let mut start_calls = 0;
+  let ingest = StubIngest::spawn(move |request| match request.path.as_str() {
+    "/v1/sessions/start" => {
+      start_calls += 1;
+      ("200 OK", format!(r#"{{"sessionToken":"token-{start_calls}"}}"#))
+    },
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let mut app = get_app();
+  app
+    .add_plugins(
+      IndigaugePlugin::<EmptySessionMeta>::new("public-key", "restart game", "1.0.0")
+        .mode(IndigaugeMode::Live)
+        .api_base(&ingest.api_base),
+    )
+    .init_resource::<InitCount>()
+    .add_observer(|event: On<IndigaugeInitDoneEvent>, mut count: ResMut<InitCount>| {
+      if matches!(event.event(), IndigaugeInitDoneEvent::Success) {
+        count.0 += 1;
+      }
+    });
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.resource::<InitCount>().0 == 1));
+  assert_eq!(app.world().resource::<SessionApiKey>().0, "token-1");
+
+  ig_info!("menu.quit");
+  app.world_mut().run_system_cached(end_session).expect("end session");
+  app.update();
+  assert!(!app.world().contains_resource::<SessionApiKey>());
+
+  // Events emitted between sessions are dropped.
+  ig_info!("menu.idle");
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.resource::<InitCount>().0 == 2));
+  assert_eq!(app.world().resource::<SessionApiKey>().0, "token-2");
+  assert!(update_until(&mut app, |_| !ingest.requests_to("/v1/sessions/end").is_empty()));
+
+  let ends = ingest.requests_to("/v1/sessions/end");
+  assert_eq!(ends.len(), 1);
+  assert_eq!(ends[0].api_key, "token-1");
+
+  let batches = ingest.requests_to("/v1/events/batch");
+  assert_eq!(batches.len(), 1);
+  assert_eq!(batches[0].api_key, "token-1");
+  assert_eq!(batches[0].body["events"][0]["eventType"], "menu.quit");
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::test_util::StubIngest;
+
+mod common;
+
+use common::{get_app, update_until};
+
+#[derive(Resource, Default)]
+struct Observed {
+  pending: bool,
+  success: bool,
+  online_after: Option<u32>,
+}
+
+#[test]
+fn session_start_is_retried_and_goes_online() {
+  let mut start_calls = 0;
+  let ingest = StubIngest::spawn(move |request| {
+    if request.path != "/v1/sessions/start" {
+      return ("200 OK", "{}".to_string());
+    }
+
+    start_calls += 1;
+    if start_calls <= 2 {
+      ("503 Service Unavailable", "{}".to_string())
+    } else {
+      ("200 OK", r#"{"sessionToken":"online-token"}"#.to_string())
+    }
+  });
+
+  let mut app = get_app();
+  app
+    .add_plugins(
+      IndigaugePlugin::<EmptySessionMeta>::new("public-key", "retry game", "1.0.0")
+        .mode(IndigaugeMode::Live)
+        .api_base(&ingest.api_base)
+        .retry_backoff(Duration::from_millis(10), Duration::from_millis(50)),
+    )
+    .init_resource::<Observed>()
+    .add_observer(|event: On<IndigaugeInitDoneEvent>, mut observed: ResMut<Observed>| match event.event() {
+      IndigaugeInitDoneEvent::Pending(_) => observed.pending = true,
+      IndigaugeInitDoneEvent::Success => observed.success = true,
+      _ => {},
+    })
+    .add_observer(|event: On<SessionOnlineEvent>, mut observed: ResMut<Observed>| {
+      observed.online_after = Some(event.event().failed_attempts);
+    });
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  // Events emitted before the first attempt fails are kept as well.
+  ig_info!("starting.event");
+
+  assert!(update_until(&mut app, |world| world.resource::<Observed>().pending));
+  assert!(world_is_offline(app.world()));
+
+  // Events emitted while offline are kept in the local queue until the session goes online.
+  ig_info!("offline.event");
+
+  assert!(update_until(&mut app, |world| world.resource::<Observed>().online_after.is_some()));
+
+  let observed = app.world().resource::<Observed>();
+  assert_eq!(observed.online_after, Some(2));
+  assert!(!observed.success);
+  assert!(!app.world().contains_resource::<PendingSession>());
+  assert_eq!(ingest.requests_to("/v1/sessions/start").len(), 3);
+  assert_eq!(indigauge_core::state::drain_pending_events().len(), 2);
+}
+
+fn world_is_offline(world: &World) -> bool {
+  world
+    .get_resource::<PendingSession>()
+    .is_some_and(PendingSession::is_offline)
+}
@@ -0,0 +1,39 @@
+use bevy_mod_indigauge::prelude::*;
+use indigauge_core::test_util::StubIngest;
+
+mod common;
+
+use common::{get_app, update_until};
+
+#[test]
+fn sink_options_filter_the_events_sent_to_the_ingest() {
+  let ingest = StubIngest::spawn(|request| match request.path.as_str() {
+    "/v1/sessions/start" => ("200 OK", r#"{"sessionToken":"token"}"#.to_string()),
+    _ => ("200 OK", "{}".to_string()),
+  });
+
+  let mut app = get_app();
+  app.add_plugins(
+    IndigaugePlugin::<EmptySessionMeta>::new("public-key", "sink options game", "1.0.0")
+      .mode(IndigaugeMode::Live)
+      .api_base(&ingest.api_base)
+      .sink_options(INGEST_SINK, SinkOptions::new().with_min_level(IndigaugeLogLevel::Warn)),
+  );
+
+  app.world_mut().trigger(StartSessionEvent::new());
+  assert!(update_until(&mut app, |world| world.contains_resource::<SessionApiKey>()));
+
+  ig_info!("menu.open");
+  ig_warn!("combat.miss");
+  ig_error!("combat.crash");
+  app.world_mut().run_system_cached(end_session).expect("end session");
+  assert!(update_until(&mut app, |_| !ingest.requests_to("/v1/sessions/end").is_empty()));
+
+  let batches = ingest.requests_to("/v1/events/batch");
+  let event_types = batches
+    .iter()
+    .flat_map(|batch| batch.body["events"].as_array().unwrap().clone())
+    .map(|event| event["eventType"].as_str().unwrap().to_string())
+    .collect::<Vec<_>>();
+  assert_eq!(event_types, ["combat.miss", "combat.crash"]);
+}
@@ -0,0 +1,382 @@
+#![doc = include_str!("../README.md")]
+
+mod recording;
+mod replay;
+mod scan;
+mod state;
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use indigauge_core::runtime::IndigaugeBlockingRuntimeClient;
+use indigauge_core::types::{IndigaugeConfig, TelemetryConsent};
+use indigauge_core::utils::game_data_dir;
+
+use crate::recording::Recording;
+use crate::replay::{ReplayLog, ReplayOptions, replay_session};
+use crate::scan::{Scan, check_call_sites, source_files};
+use crate::state::LocalState;
+
+const USAGE: &str = "\
+Usage: indigauge <command> [options] <file>...
+
+Commands:
+  validate <file>...         Check the events of recorded sessions or offline event files
+  replay <file>...           Validate, then replay recorded sessions against the ingest
+  status <game>              Show the SDK state stored for a game: paths, player id, consent and pending records
+  purge <game>               Delete the SDK state stored for a game, keeping the player's consent
+  export <game>              Write the SDK state stored for a game as NDJSON
+  validate-events <path>...  Report invalid or duplicate event types of the ig_*! call sites in a source tree
+
+Replay options:
+  --public-key <key>      Public key of the game (defaults to INDIGAUGE_PUBLIC_KEY)
+  --api-base <url>        Ingest base URL (defaults to INDIGAUGE_API_BASE or the production ingest)
+  --client-version <ver>  Client version of sessions recorded without a start payload (default: unknown)
+  --realtime              Wait between events as long as during the recording
+  --speed <factor>        Like --realtime, but <factor> times faster
+  --allow-invalid         Replay the valid events of files with issues instead of skipping the files
+
+State options:
+  --data-dir <dir>        Read the state from <dir> instead of the game's folder in the preference directory
+  --event-file <file>     Include an NDJSON event file and its rotated files, can be repeated
+  --include-consent       Also delete the stored consent when purging
+  --output <file>         Export to <file> instead of stdout
+";
+
+/// Parsed command line of the `status`, `purge` and `export` commands.
+struct StateArgs {
+  dir: PathBuf,
+  event_files: Vec<PathBuf>,
+  include_consent: bool,
+  output: Option<PathBuf>,
+}
+
+/// Parsed command line of the `replay` command.
+struct ReplayArgs {
+  files: Vec<PathBuf>,
+  public_key: Option<String>,
+  api_base: Option<String>,
+  client_version: String,
+  speed: Option<f64>,
+  allow_invalid: bool,
+}
+
+fn main() -> ExitCode {
+  let args = std::env::args().skip(1).collect::<Vec<_>>();
+  let result = match args.first().map(String::as_str) {
+    Some("validate") => validate(&args[1..]),
+    Some("replay") => parse_replay_args(&args[1..]).and_then(|args| replay(&args)),
+    Some("status") => parse_state_args(&args[1..]).and_then(|args| status(&args)),
+    Some("purge") => parse_state_args(&args[1..]).and_then(|args| purge(&args)),
+    Some("export") => parse_state_args(&args[1..]).and_then(|args| export(&args)),
+    Some("validate-events") => validate_events(&args[1..]),
+    Some("-h" | "--help" | "help") => {
+      print!("{USAGE}");
+      return ExitCode::SUCCESS;
+    },
+    _ => Err("missing or unknown command".to_string()),
+  };
+
+  match result {
+    Ok(true) => ExitCode::SUCCESS,
+    Ok(false) => ExitCode::FAILURE,
+    Err(error) => {
+      eprintln!("error: {error}\n\n{USAGE}");
+      ExitCode::from(2)
+    },
+  }
+}
+
+/// Loads a file and prints its issues. Returns `None` if it cannot be read.
+fn load(path: &Path) -> Option<Recording> {
+  match Recording::load(path) {
+    Ok(recording) => {
+      for issue in &recording.issues {
+        eprintln!("{}: {issue}", path.display());
+      }
+      Some(recording)
+    },
+    Err(error) => {
+      eprintln!("{}: {error}", path.display());
+      None
+    },
+  }
+}
+
+fn validate(files: &[String]) -> Result<bool, String> {
+  if files.is_empty() {
+    return Err("no files given".to_string());
+  }
+
+  let mut valid = true;
+  for path in files.iter().map(PathBuf::from) {
+    let Some(recording) = load(&path) else {
+      valid = false;
+      continue;
+    };
+    valid &= recording.issues.is_empty();
+    println!(
+      "{}: {} sessions, {} valid events, {} issues",
+      path.display(),
+      recording.sessions.len(),
+      recording.event_count(),
+      recording.issues.len()
+    );
+  }
+  Ok(valid)
+}
+
+fn parse_replay_args(args: &[String]) -> Result<ReplayArgs, String> {
+  let mut replay = ReplayArgs {
+    files: Vec::new(),
+    public_key: None,
+    api_base: None,
+    client_version: "unknown".to_string(),
+    speed: None,
+    allow_invalid: false,
+  };
+
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    let mut value = || args.next().cloned().ok_or_else(|| format!("missing value for {arg}"));
+    match arg.as_str() {
+      "--public-key" => replay.public_key = Some(value()?),
+      "--api-base" => replay.api_base = Some(value()?),
+      "--client-version" => replay.client_version = value()?,
+      "--realtime" => replay.speed = Some(1.0),
+      "--speed" => {
+        let speed = value()?;
+        replay.speed = Some(
+          speed
+            .parse()
+            .ok()
+            .filter(|speed: &f64| *speed > 0.0)
+            .ok_or_else(|| format!("invalid speed: {speed}"))?,
+        );
+      },
+      "--allow-invalid" => replay.allow_invalid = true,
+      option if option.starts_with("--") => return Err(format!("unknown option: {option}")),
+      file => replay.files.push(PathBuf::from(file)),
+    }
+  }
+
+  if replay.files.is_empty() {
+    return Err("no files given".to_string());
+  }
+  Ok(replay)
+}
+
+fn replay(args: &ReplayArgs) -> Result<bool, String> {
+  let public_key = args
+    .public_key
+    .clone()
+    .or_else(|| std::env::var("INDIGAUGE_PUBLIC_KEY").ok())
+    .filter(|key| !key.is_empty())
+    .ok_or("no public key given, pass --public-key or set INDIGAUGE_PUBLIC_KEY")?;
+  let mut config = IndigaugeConfig::new("indigauge-cli", public_key, &args.client_version);
+  if let Some(api_base) = &args.api_base {
+    config = config.with_api_base(api_base);
+  }
+  let client = IndigaugeBlockingRuntimeClient::new(config);
+  let options = ReplayOptions {
+    client_version: &args.client_version,
+    speed: args.speed,
+  };
+
+  let mut succeeded = true;
+  for path in &args.files {
+    let Some(recording) = load(path) else {
+      succeeded = false;
+      continue;
+    };
+    if !recording.issues.is_empty() && !args.allow_invalid {
+      eprintln!("{}: skipped, pass --allow-invalid to replay its valid events", path.display());
+      succeeded = false;
+      continue;
+    }
+
+    let mut log = match ReplayLog::load(path) {
+      Ok(log) => log,
+      Err(error) => {
+        eprintln!("{}: {error}", ReplayLog::path(path).display());
+        succeeded = false;
+        continue;
+      },
+    };
+
+    for session in &recording.sessions {
+      match replay_session(&client, session, options, log.progress(client.config(), session.id)) {
+        Ok(report) => {
+          succeeded &= report.failed_events == 0 && report.failed_requests == 0;
+          println!(
+            "{} session {}: {} events sent, {} failed, {} other requests failed, {} already sent{}",
+            path.display(),
+            session.id,
+            report.sent_events,
+            report.failed_events,
+            report.failed_requests,
+            report.already_sent,
+            if report.session_ended { ", ended" } else { "" }
+          );
+        },
+        Err(error) => {
+          succeeded = false;
+          eprintln!("{} session {}: {error}", path.display(), session.id);
+        },
+      }
+
+      // Saved after every session, so an interrupted replay resumes where it stopped.
+      if let Err(error) = log.save(path) {
+        succeeded = false;
+        eprintln!("{}: {error}", ReplayLog::path(path).display());
+      }
+    }
+  }
+  Ok(succeeded)
+}
+
+fn parse_state_args(args: &[String]) -> Result<StateArgs, String> {
+  let mut game = None;
+  let mut dir = None;
+  let mut state = StateArgs {
+    dir: PathBuf::new(),
+    event_files: Vec::new(),
+    include_consent: false,
+    output: None,
+  };
+
+  let mut args = args.iter();
+  while let Some(arg) = args.next() {
+    let mut value = || args.next().cloned().ok_or_else(|| format!("missing value for {arg}"));
+    match arg.as_str() {
+      "--data-dir" => dir = Some(PathBuf::from(value()?)),
+      "--event-file" => state.event_files.push(PathBuf::from(value()?)),
+      "--include-consent" => state.include_consent = true,
+      "--output" => state.output = Some(PathBuf::from(value()?)),
+      option if option.starts_with("--") => return Err(format!("unknown option: {option}")),
+      name if game.is_none() => game = Some(name.to_string()),
+      name => return Err(format!("unexpected argument: {name}")),
+    }
+  }
+
+  state.dir = match (dir, game) {
+    (Some(dir), _) => dir,
+    (None, Some(game)) => game_data_dir(&game).ok_or("no preference directory on this platform, pass --data-dir")?,
+    (None, None) => return Err("no game given".to_string()),
+  };
+  Ok(state)
+}
+
+fn read_state(args: &StateArgs) -> Option<LocalState> {
+  LocalState::read(&args.dir, &args.event_files)
+    .inspect_err(|error| eprintln!("{}: {error}", args.dir.display()))
+    .ok()
+}
+
+fn status(args: &StateArgs) -> Result<bool, String> {
+  let Some(state) = read_state(args) else {
+    return Ok(false);
+  };
+
+  println!("data dir:   {}", state.dir.display());
+  match &state.player_id {
+    Some(player_id) if state.has_valid_player_id() => println!("player id:  {player_id}"),
+    Some(player_id) => println!("player id:  {player_id:?} (invalid, replaced on the next launch)"),
+    None => println!("player id:  none"),
+  }
+  println!("consent:    {}", state.consent.unwrap_or(TelemetryConsent::Unknown).as_str());
+  println!("pending:    {} records", state.pending_records());
+  for file in &state.files {
+    match file.records {
+      Some(records) => println!("  {} ({} bytes, {records} records)", file.path.display(), file.bytes),
+      None => println!("  {} ({} bytes)", file.path.display(), file.bytes),
+    }
+  }
+  Ok(true)
+}
+
+fn purge(args: &StateArgs) -> Result<bool, String> {
+  let Some(state) = read_state(args) else {
+    return Ok(false);
+  };
+
+  match state.purge(args.include_consent) {
+    Ok(deleted) => {
+      for path in &deleted {
+        println!("deleted {}", path.display());
+      }
+      println!("{} files deleted", deleted.len());
+      Ok(true)
+    },
+    Err(error) => {
+      eprintln!("{}: {error}", state.dir.display());
+      Ok(false)
+    },
+  }
+}
+
+fn export(args: &StateArgs) -> Result<bool, String> {
+  let Some(state) = read_state(args) else {
+    return Ok(false);
+  };
+
+  let result = match &args.output {
+    Some(path) => std::fs::File::create(path).and_then(|file| state.export(&mut std::io::BufWriter::new(file))),
+    None => state.export(&mut std::io::stdout().lock()),
+  };
+  if let Err(error) = result {
+    eprintln!("export failed: {error}");
+    return Ok(false);
+  }
+  Ok(true)
+}
+
+fn validate_events(paths: &[String]) -> Result<bool, String> {
+  if paths.is_empty() {
+    return Err("no paths given".to_string());
+  }
+
+  let mut scan = Scan::default();
+  let mut readable = true;
+  for path in paths.iter().map(Path::new) {
+    let files = match source_files(path) {
+      Ok(files) => files,
+      Err(error) => {
+        eprintln!("{}: {error}", path.display());
+        readable = false;
+        continue;
+      },
+    };
+    for file in files {
+      match std::fs::read_to_string(&file).and_then(|source| scan.add_source(&file, &source)) {
+        Ok(()) => {},
+        Err(error) => {
+          eprintln!("{}: {error}", file.display());
+          readable = false;
+        },
+      }
+    }
+  }
+
+  let scan = scan.resolve();
+  let issues = check_call_sites(&scan.sites);
+  for issue in &issues {
+    eprintln!("{issue}");
+  }
+  for site in &scan.unresolved {
+    eprintln!("{}:{}: skipped, no unique value found for constant {}", site.path.display(), site.line, site.event_type);
+  }
+  let event_types = scan
+    .sites
+    .iter()
+    .map(|site| site.event_type.as_str())
+    .collect::<std::collections::BTreeSet<_>>();
+  println!(
+    "{} call sites, {} event types, {} issues, {} skipped",
+    scan.sites.len(),
+    event_types.len(),
+    issues.len(),
+    scan.unresolved.len()
+  );
+  Ok(readable && issues.is_empty())
+}
@@ -0,0 +1,218 @@
+//! Loading and validation of recorded sessions.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use indigauge_core::event::event_naming_policy;
+use indigauge_core::ndjson::NdjsonRecordKind;
+use indigauge_core::types::EventPayload;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::state::LOCAL_STATE_KIND;
+
+/// Session id used for events that were not recorded with one, e.g. offline queue exports.
+const DEFAULT_SESSION: u32 = 0;
+
+/// Something recorded during a session, in recording order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+  Event(EventPayload),
+  Metadata(Value),
+  Feedback(Value),
+}
+
+/// A recorded session, ready to be replayed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RecordedSession {
+  pub id: u32,
+  /// The recorded start payload, if the recording contains one.
+  pub start: Option<Value>,
+  pub entries: Vec<Entry>,
+  /// The recorded end reason, if the session was ended.
+  pub end_reason: Option<String>,
+}
+
+impl RecordedSession {
+  /// Returns the recorded events.
+  pub fn events(&self) -> impl Iterator<Item = &EventPayload> {
+    self.entries.iter().filter_map(|entry| match entry {
+      Entry::Event(event) => Some(event),
+      _ => None,
+    })
+  }
+}
+
+/// A problem found on a line of a recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+  /// 1-based line, or the 1-based index of the event in a JSON array.
+  pub line: usize,
+  pub message: String,
+}
+
+impl fmt::Display for Issue {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "line {}: {}", self.line, self.message)
+  }
+}
+
+/// The valid sessions of a recording and the issues of the lines that were skipped.
+#[derive(Debug, Default)]
+pub struct Recording {
+  pub sessions: Vec<RecordedSession>,
+  pub issues: Vec<Issue>,
+}
+
+impl Recording {
+  /// Reads a recording from a file, see [`Recording::parse`].
+  pub fn load(path: &Path) -> std::io::Result<Self> {
+    Ok(Self::parse(&std::fs::read_to_string(path)?))
+  }
+
+  /// Parses a recording.
+  ///
+  /// Accepts the NDJSON files written by the Dev mode event file or `indigauge export`, NDJSON files of bare event
+  /// payloads and JSON arrays of event payloads, such as offline queue exports. Every event is checked against the event naming policy.
+  pub fn parse(content: &str) -> Self {
+    let mut recording = Self::default();
+    let mut sessions = BTreeMap::<u32, RecordedSession>::new();
+
+    if content.trim_start().starts_with('[') {
+      match serde_json::from_str::<Vec<Value>>(content) {
+        Ok(events) => {
+          for (index, event) in events.into_iter().enumerate() {
+            recording.add_event(&mut sessions, DEFAULT_SESSION, index + 1, event);
+          }
+        },
+        Err(error) => recording.issue(1, format!("invalid JSON array: {error}")),
+      }
+    } else {
+      for (index, line) in content.lines().enumerate() {
+        if !line.trim().is_empty() {
+          recording.add_line(&mut sessions, index + 1, line);
+        }
+      }
+    }
+
+    recording.sessions = sessions.into_values().collect();
+    recording
+  }
+
+  /// Returns the number of valid events in all sessions.
+  pub fn event_count(&self) -> usize {
+    self.sessions.iter().map(|session| session.events().count()).sum()
+  }
+
+  fn add_line(&mut self, sessions: &mut BTreeMap<u32, RecordedSession>, line: usize, content: &str) {
+    #[derive(Deserialize)]
+    struct Record {
+      kind: NdjsonRecordKind,
+      session: Option<u32>,
+      data: Value,
+    }
+
+    let value = match serde_json::from_str::<Value>(content) {
+      Ok(value) => value,
+      Err(error) => return self.issue(line, format!("invalid JSON: {error}")),
+    };
+    match value.get("kind").and_then(Value::as_str) {
+      None => return self.add_event(sessions, DEFAULT_SESSION, line, value),
+      Some(LOCAL_STATE_KIND) => return,
+      Some(_) => {},
+    }
+
+    let record = match serde_json::from_value::<Record>(value) {
+      Ok(record) => record,
+      Err(error) => return self.issue(line, format!("invalid record: {error}")),
+    };
+    let id = record.session.unwrap_or(DEFAULT_SESSION);
+
+    match record.kind {
+      NdjsonRecordKind::Event => self.add_event(sessions, id, line, record.data),
+      NdjsonRecordKind::SessionStart => session(sessions, id).start = Some(record.data),
+      NdjsonRecordKind::SessionEnd => {
+        let reason = record.data.get("reason").and_then(Value::as_str).unwrap_or("ended");
+        session(sessions, id).end_reason = Some(reason.to_string());
+      },
+      NdjsonRecordKind::Metadata => session(sessions, id).entries.push(Entry::Metadata(record.data)),
+      NdjsonRecordKind::Feedback => session(sessions, id).entries.push(Entry::Feedback(record.data)),
+    }
+  }
+
+  fn add_event(&mut self, sessions: &mut BTreeMap<u32, RecordedSession>, id: u32, line: usize, data: Value) {
+    let event = match serde_json::from_value::<EventPayload>(data) {
+      Ok(event) => event,
+      Err(error) => return self.issue(line, format!("invalid event payload: {error}")),
+    };
+    if let Err(error) = event_naming_policy().validate(event.event_type()) {
+      return self.issue(line, format!("invalid event type '{}': {error}", event.event_type()));
+    }
+
+    session(sessions, id).entries.push(Entry::Event(event));
+  }
+
+  fn issue(&mut self, line: usize, message: String) {
+    self.issues.push(Issue { line, message });
+  }
+}
+
+fn session(sessions: &mut BTreeMap<u32, RecordedSession>, id: u32) -> &mut RecordedSession {
+  sessions.entry(id).or_insert_with(|| RecordedSession {
+    id,
+    ..Default::default()
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use indigauge_core::types::IndigaugeLogLevel;
+  use serde_json::json;
+
+  use super::*;
+
+  fn event(event_type: &str, elapsed_ms: u128) -> Value {
+    serde_json::to_value(EventPayload::new(event_type, IndigaugeLogLevel::Info, None, elapsed_ms)).unwrap()
+  }
+
+  #[test]
+  fn event_file_records_are_grouped_by_session() {
+    let lines = [
+      json!({ "kind": "session_start", "timestampMs": 1, "session": 0, "data": { "clientVersion": "1.0.0" } }),
+      json!({ "kind": "session_start", "timestampMs": 2, "session": 3, "data": { "clientVersion": "1.0.0" } }),
+      json!({ "kind": "event", "timestampMs": 3, "session": 3, "data": event("player.join", 10) }),
+      json!({ "kind": "metadata", "timestampMs": 4, "session": 0, "data": { "level": 2 } }),
+      json!({ "kind": "event", "timestampMs": 5, "session": 0, "data": event("level.start", 20) }),
+      json!({ "kind": "event", "timestampMs": 6, "session": 0, "data": event("not valid", 30) }),
+      json!({ "kind": "session_end", "timestampMs": 7, "session": 0, "data": { "reason": "ended" } }),
+    ];
+    let content = lines.map(|line| line.to_string()).join("\n") + "\n{ truncated";
+
+    let recording = Recording::parse(&content);
+
+    assert_eq!(recording.sessions.len(), 2);
+    assert_eq!(recording.event_count(), 2);
+    let default = &recording.sessions[0];
+    assert_eq!(default.start, Some(json!({ "clientVersion": "1.0.0" })));
+    assert_eq!(default.entries[0], Entry::Metadata(json!({ "level": 2 })));
+    assert_eq!(default.events().next().unwrap().event_type(), "level.start");
+    assert_eq!(default.end_reason.as_deref(), Some("ended"));
+    assert_eq!(recording.sessions[1].id, 3);
+    assert_eq!(recording.sessions[1].end_reason, None);
+    assert_eq!(recording.issues.iter().map(|issue| issue.line).collect::<Vec<_>>(), [6, 8]);
+  }
+
+  #[test]
+  fn event_arrays_and_bare_events_are_accepted() {
+    let array = json!([event("level.start", 1), { "eventType": "level.end" }]).to_string();
+    let recording = Recording::parse(&array);
+    assert_eq!(recording.event_count(), 1);
+    assert_eq!(recording.issues[0].line, 2);
+
+    let lines = format!("{}\n\n{}", event("level.start", 1), event("level.end", 2));
+    let recording = Recording::parse(&lines);
+    assert_eq!(recording.event_count(), 2);
+    assert!(recording.issues.is_empty());
+  }
+}
@@ -0,0 +1,374 @@
+//! Replaying recorded sessions against an ingest.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use indigauge_core::http::decode_api_response;
+use indigauge_core::runtime::IndigaugeBlockingRuntimeClient;
+use indigauge_core::types::{
+  ApiResponse, BatchEventPayload, EventPayload, FeedbackPayload, IndigaugeConfig, StartSessionPayload,
+  StartSessionResponse,
+};
+use reqwest::blocking::Request;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::recording::{Entry, RecordedSession};
+
+/// Sdk version sent for sessions recorded without a start payload.
+const SDK_VERSION: &str = concat!("cli:", env!("CARGO_PKG_VERSION"));
+
+/// How recorded sessions are replayed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayOptions<'a> {
+  /// Client version sent for sessions recorded without a start payload.
+  pub client_version: &'a str,
+  /// Waits between events according to their recorded elapsed time, divided by this factor. Sends as fast as
+  /// possible if `None`.
+  pub speed: Option<f64>,
+}
+
+/// Outcome of replaying one session.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayReport {
+  pub sent_events: usize,
+  pub failed_events: usize,
+  pub failed_requests: usize,
+  /// Events, metadata updates and feedback skipped because an earlier replay sent them.
+  pub already_sent: usize,
+  pub session_ended: bool,
+}
+
+/// What an earlier replay of a session to one ingest sent, so a repeated replay reuses the session and only sends the
+/// rest.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReplayProgress {
+  /// Token of the session started by the first replay.
+  pub token: Option<String>,
+  /// Idempotency keys of the events accepted by the ingest.
+  pub sent_events: BTreeSet<String>,
+  /// Indexes of the metadata updates and feedback accepted by the ingest.
+  pub sent_entries: BTreeSet<usize>,
+  pub ended: bool,
+}
+
+/// Replay progress of the sessions of one recording, stored next to it as `<recording>.replayed.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReplayLog {
+  sessions: BTreeMap<String, ReplayProgress>,
+}
+
+impl ReplayLog {
+  /// Returns the path of the log of a recording.
+  pub fn path(recording: &Path) -> PathBuf {
+    let mut path = recording.as_os_str().to_owned();
+    path.push(".replayed.json");
+    PathBuf::from(path)
+  }
+
+  /// Reads the log of a recording, empty if the recording was never replayed.
+  pub fn load(recording: &Path) -> io::Result<Self> {
+    match fs::read_to_string(Self::path(recording)) {
+      Ok(content) => Ok(serde_json::from_str(&content)?),
+      Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+      Err(error) => Err(error),
+    }
+  }
+
+  /// Writes the log of a recording.
+  pub fn save(&self, recording: &Path) -> io::Result<()> {
+    fs::write(Self::path(recording), serde_json::to_string_pretty(self)?)
+  }
+
+  /// Returns the progress of a recorded session replayed to the ingest and game of `config`.
+  pub fn progress(&mut self, config: &IndigaugeConfig, session: u32) -> &mut ReplayProgress {
+    let key = format!("{} {} {session}", config.api_base(), config.public_key());
+    self.sessions.entry(key).or_default()
+  }
+}
+
+/// Error returned when a replayed session could not be started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayError(String);
+
+impl fmt::Display for ReplayError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "failed to start session: {}", self.0)
+  }
+}
+
+impl std::error::Error for ReplayError {}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct RecordedStart {
+  client_version: Option<String>,
+  sdk_version: Option<String>,
+  player_id: Option<String>,
+  platform: Option<String>,
+  os: Option<String>,
+  cpu_family: Option<String>,
+  cores: Option<String>,
+  memory: Option<String>,
+  gpu: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordedFeedback {
+  message: String,
+  elapsed_ms: u128,
+  question: Option<String>,
+  category: String,
+}
+
+/// Starts a new session with the recorded start payload, then sends its events, metadata updates and feedback in
+/// recording order and ends it if the recording ended it.
+///
+/// Events keep their recorded idempotency keys and elapsed times. If `progress` holds an earlier replay of the
+/// session, its session is reused and only what it did not send is sent, so replaying a session twice is a no-op.
+pub fn replay_session(
+  client: &IndigaugeBlockingRuntimeClient,
+  session: &RecordedSession,
+  options: ReplayOptions<'_>,
+  progress: &mut ReplayProgress,
+) -> Result<ReplayReport, ReplayError> {
+  let token = match &progress.token {
+    Some(token) => token.clone(),
+    None => {
+      let token = start_session(client, session, options)?;
+      progress.token = Some(token.clone());
+      token
+    },
+  };
+  let started = Instant::now();
+  let mut report = ReplayReport::default();
+  let mut batch = Vec::new();
+
+  for (index, entry) in session.entries.iter().enumerate() {
+    let sent = match entry {
+      Entry::Event(event) => progress.sent_events.contains(event.idempotency_key()),
+      Entry::Metadata(_) | Entry::Feedback(_) => progress.sent_entries.contains(&index),
+    };
+    if sent {
+      report.already_sent += 1;
+      continue;
+    }
+
+    match entry {
+      Entry::Event(event) => {
+        if let Some(speed) = options.speed {
+          let due = Duration::from_secs_f64(event.elapsed_ms() as f64 / 1000.0 / speed.max(f64::EPSILON));
+          if let Some(wait) = due.checked_sub(started.elapsed()) {
+            send_events(client, &token, &mut batch, &mut report, progress);
+            thread::sleep(wait);
+          }
+        }
+        batch.push(event.clone());
+        if batch.len() >= client.config().batch_size() {
+          send_events(client, &token, &mut batch, &mut report, progress);
+        }
+      },
+      Entry::Metadata(metadata) => {
+        send_events(client, &token, &mut batch, &mut report, progress);
+        if is_sent(client.update_metadata_value(&token, metadata).ok(), client) {
+          progress.sent_entries.insert(index);
+        } else {
+          report.failed_requests += 1;
+        }
+      },
+      Entry::Feedback(feedback) => {
+        send_events(client, &token, &mut batch, &mut report, progress);
+        if send_feedback(client, &token, feedback) {
+          progress.sent_entries.insert(index);
+        } else {
+          report.failed_requests += 1;
+        }
+      },
+    }
+  }
+  send_events(client, &token, &mut batch, &mut report, progress);
+
+  if let Some(reason) = &session.end_reason
+    && !progress.ended
+  {
+    progress.ended = is_sent(client.end_session(&token, reason).ok(), client);
+    if !progress.ended {
+      report.failed_requests += 1;
+    }
+  }
+  report.session_ended = progress.ended;
+
+  Ok(report)
+}
+
+fn start_session(
+  client: &IndigaugeBlockingRuntimeClient,
+  session: &RecordedSession,
+  options: ReplayOptions<'_>,
+) -> Result<String, ReplayError> {
+  let start = session
+    .start
+    .clone()
+    .map(serde_json::from_value::<RecordedStart>)
+    .transpose()
+    .map_err(|error| ReplayError(format!("invalid start payload: {error}")))?
+    .unwrap_or_default();
+  let payload = StartSessionPayload {
+    client_version: start.client_version.as_deref().unwrap_or(options.client_version),
+    sdk_version: start.sdk_version.as_deref().unwrap_or(SDK_VERSION),
+    player_id: start.player_id.as_ref(),
+    platform: start.platform.as_ref(),
+    os: start.os.as_deref(),
+    cpu_family: start.cpu_family.as_ref(),
+    cores: start.cores.as_deref(),
+    memory: start.memory.as_deref(),
+    gpu: start.gpu.as_ref(),
+    previous_session_token: None,
+  };
+
+  let request = client
+    .start_session(&payload)
+    .map_err(|error| ReplayError(error.to_string()))?;
+  let response = client.send(request).map_err(|error| ReplayError(error.to_string()))?;
+  let status = response.status();
+  match decode_api_response::<StartSessionResponse>(response.body()) {
+    Ok(ApiResponse::Ok(start)) if status.is_success() => Ok(start.session_token),
+    Ok(ApiResponse::Err(error)) => Err(ReplayError(format!("{} ({})", error.message, error.code))),
+    _ => Err(ReplayError(status.to_string())),
+  }
+}
+
+fn send_events(
+  client: &IndigaugeBlockingRuntimeClient,
+  token: &str,
+  batch: &mut Vec<EventPayload>,
+  report: &mut ReplayReport,
+  progress: &mut ReplayProgress,
+) {
+  if batch.is_empty() {
+    return;
+  }
+
+  let payload = BatchEventPayload {
+    events: std::mem::take(batch),
+  };
+  if is_sent(client.event_batch(token, &payload).ok(), client) {
+    report.sent_events += payload.events.len();
+    progress
+      .sent_events
+      .extend(payload.events.iter().map(|event| event.idempotency_key().to_string()));
+  } else {
+    report.failed_events += payload.events.len();
+  }
+}
+
+fn send_feedback(client: &IndigaugeBlockingRuntimeClient, token: &str, feedback: &Value) -> bool {
+  let Ok(feedback) = serde_json::from_value::<RecordedFeedback>(feedback.clone()) else {
+    return false;
+  };
+  let payload = FeedbackPayload {
+    message: &feedback.message,
+    elapsed_ms: feedback.elapsed_ms,
+    question: feedback.question.as_ref(),
+    category: feedback.category,
+  };
+  is_sent(client.feedback(token, &payload).ok(), client)
+}
+
+fn is_sent(request: Option<Request>, client: &IndigaugeBlockingRuntimeClient) -> bool {
+  request
+    .and_then(|request| client.send(request).ok())
+    .is_some_and(|response| response.status().is_success())
+}
+
+#[cfg(test)]
+mod tests {
+  use indigauge_core::test_util::StubIngest;
+  use indigauge_core::types::{IndigaugeConfig, IndigaugeLogLevel};
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn sessions_are_replayed_in_order_with_recorded_payloads() {
+    let ingest = StubIngest::ok(r#"{"sessionToken":"replay-token"}"#);
+    let client = IndigaugeBlockingRuntimeClient::new(
+      IndigaugeConfig::new("replay", "public-key", "1.0.0").with_api_base(&ingest.api_base),
+    );
+    let first = EventPayload::new("level.start", IndigaugeLogLevel::Info, None, 5);
+    let second = EventPayload::new("level.end", IndigaugeLogLevel::Info, None, 10);
+    let session = RecordedSession {
+      id: 0,
+      start: Some(json!({ "clientVersion": "0.9.0", "sdkVersion": "bevy:0.7.0", "playerId": "player-1" })),
+      entries: vec![
+        Entry::Event(first.clone()),
+        Entry::Metadata(json!({ "level": 1 })),
+        Entry::Event(second.clone()),
+      ],
+      end_reason: Some("ended".to_string()),
+    };
+    let options = ReplayOptions {
+      client_version: "unknown",
+      speed: Some(1000.0),
+    };
+
+    let mut progress = ReplayProgress::default();
+    let report = replay_session(&client, &session, options, &mut progress).unwrap();
+
+    assert_eq!(
+      report,
+      ReplayReport {
+        sent_events: 2,
+        failed_events: 0,
+        failed_requests: 0,
+        already_sent: 0,
+        session_ended: true,
+      }
+    );
+    let sent = ingest.take_requests();
+    let paths = sent.iter().map(|request| request.path.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+      paths,
+      [
+        "/v1/sessions/start",
+        "/v1/events/batch",
+        "/v1/sessions",
+        "/v1/events/batch",
+        "/v1/sessions/end"
+      ]
+    );
+    assert_eq!(sent[0].api_key, "public-key");
+    assert_eq!(sent[0].body["clientVersion"], "0.9.0");
+    assert_eq!(sent[0].body["playerId"], "player-1");
+    assert_eq!(sent[1].api_key, "replay-token");
+    assert_eq!(sent[1].body["events"][0], serde_json::to_value(&first).unwrap());
+    assert_eq!(sent[3].body["events"][0]["idempotencyKey"], second.idempotency_key());
+
+    // Replaying the session again with the stored progress sends nothing.
+    let recording = std::env::temp_dir().join(format!("indigauge-replay-test-{}.ndjson", std::process::id()));
+    let mut log = ReplayLog::default();
+    *log.progress(client.config(), session.id) = progress;
+    log.save(&recording).unwrap();
+    let mut log = ReplayLog::load(&recording).unwrap();
+
+    let report = replay_session(&client, &session, options, log.progress(client.config(), session.id)).unwrap();
+
+    assert_eq!(
+      report,
+      ReplayReport {
+        already_sent: 3,
+        session_ended: true,
+        ..ReplayReport::default()
+      }
+    );
+    assert!(ingest.requests().is_empty());
+    let _ = fs::remove_file(ReplayLog::path(&recording));
+  }
+}
@@ -0,0 +1,500 @@
+//! Scanning a source tree for Indigauge event types.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use indigauge_core::event::validate_event_type;
+
+/// Event macros, the index of their level argument (or their fixed level) and the index of their event type.
+const MACROS: [(&str, Level, usize); 14] = [
+  ("enqueue_ig_event_for", Level::Arg(1), 2),
+  ("enqueue_ig_event", Level::Arg(0), 1),
+  ("ig_event_for", Level::Arg(1), 2),
+  ("ig_event", Level::Arg(0), 1),
+  ("ig_trace_for", Level::Fixed("trace"), 1),
+  ("ig_debug_for", Level::Fixed("debug"), 1),
+  ("ig_info_for", Level::Fixed("info"), 1),
+  ("ig_warn_for", Level::Fixed("warn"), 1),
+  ("ig_error_for", Level::Fixed("error"), 1),
+  ("ig_trace", Level::Fixed("trace"), 0),
+  ("ig_debug", Level::Fixed("debug"), 0),
+  ("ig_info", Level::Fixed("info"), 0),
+  ("ig_warn", Level::Fixed("warn"), 0),
+  ("ig_error", Level::Fixed("error"), 0),
+];
+
+#[derive(Clone, Copy)]
+enum Level {
+  Arg(usize),
+  Fixed(&'static str),
+}
+
+/// An event type found in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallSite {
+  pub path: PathBuf,
+  pub line: usize,
+  pub event_type: String,
+  pub level: String,
+  /// `true` for `#[derive(IndigaugeEvent)]` declarations.
+  pub derived: bool,
+}
+
+/// A problem with an event type of a call site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanIssue {
+  pub path: PathBuf,
+  pub line: usize,
+  pub message: String,
+}
+
+impl fmt::Display for ScanIssue {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}:{}: {}", self.path.display(), self.line, self.message)
+  }
+}
+
+/// Returns the `.rs` files below `root`, skipping hidden folders and `target`.
+pub fn source_files(root: &Path) -> io::Result<Vec<PathBuf>> {
+  if root.is_file() {
+    return Ok(vec![root.to_path_buf()]);
+  }
+
+  let mut files = Vec::new();
+  let mut entries = fs::read_dir(root)?.collect::<Result<Vec<_>, _>>()?;
+  entries.sort_by_key(|entry| entry.file_name());
+  for entry in entries {
+    let path = entry.path();
+    let name = entry.file_name();
+    let name = name.to_string_lossy();
+    if entry.file_type()?.is_dir() {
+      if !name.starts_with('.') && name != "target" {
+        files.extend(source_files(&path)?);
+      }
+    } else if name.ends_with(".rs") {
+      files.push(path);
+    }
+  }
+  Ok(files)
+}
+
+/// Event types found in a source tree.
+#[derive(Debug, Default)]
+pub struct Scan {
+  pub sites: Vec<CallSite>,
+  /// Call sites whose event type is a constant without a unique `&str` value in the scanned sources.
+  pub unresolved: Vec<CallSite>,
+  /// Call sites whose event type is a constant, with the name of the constant as event type.
+  constant_sites: Vec<CallSite>,
+  /// Values of the `&str` constants, `None` if constants with the same name have different values.
+  constants: BTreeMap<String, Option<String>>,
+}
+
+impl Scan {
+  /// Adds the `ig_*!` call sites and `#[ig(event_type = "...")]` declarations of `source`, and its `&str` constants.
+  ///
+  /// Fails with [`io::ErrorKind::InvalidData`] if the comments and strings of `source` cannot be masked.
+  pub fn add_source(&mut self, path: &Path, source: &str) -> io::Result<()> {
+    let masked = |strings| {
+      mask(source, strings).map_err(|error| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("cannot mask comments and strings: {error}"))
+      })
+    };
+    let code = masked(false)?;
+    let search = masked(true)?;
+
+    let mut offset = 0;
+    while let Some(found) = search[offset..].find(|c: char| c.is_alphabetic() || c == '_') {
+      let start = offset + found;
+      offset = search[start..]
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(search.len(), |len| start + len);
+      let name = &search[start..offset];
+      let rest = code[offset..].trim_start();
+
+      if name == "const" {
+        self.add_constant(rest);
+        continue;
+      }
+
+      let site = if let Some(args) = rest.strip_prefix('!').map(str::trim_start).and_then(macro_args) {
+        MACROS
+          .iter()
+          .find(|(macro_name, ..)| *macro_name == name)
+          .and_then(|(_, level, event_type)| {
+            let level = match level {
+              Level::Arg(index) => args.get(*index)?.to_string(),
+              Level::Fixed(level) => level.to_string(),
+            };
+            Some((args.get(*event_type)?.to_string(), level, false))
+          })
+      } else if name == "ig" && code[..start].trim_end().ends_with("#[") {
+        macro_args(rest).and_then(|args| {
+          let value = |key: &str| {
+            args.iter().find_map(|arg| {
+              let (name, value) = arg.split_once('=')?;
+              (name.trim() == key).then(|| string_literal(value)).flatten()
+            })
+          };
+          Some((format!("\"{}\"", value("event_type")?), value("level").unwrap_or_else(|| "info".to_string()), true))
+        })
+      } else {
+        None
+      };
+
+      let Some((event_type, level, derived)) = site else {
+        continue;
+      };
+      let mut site = CallSite {
+        path: path.to_path_buf(),
+        line: source[..start].matches('\n').count() + 1,
+        event_type: String::new(),
+        level,
+        derived,
+      };
+      if let Some(literal) = string_literal(&event_type) {
+        site.event_type = literal;
+        self.sites.push(site);
+      } else if let Some(constant) = constant_name(&event_type) {
+        site.event_type = constant.to_string();
+        self.constant_sites.push(site);
+      }
+    }
+    Ok(())
+  }
+
+  /// Resolves the call sites that use constants, once all sources are added.
+  pub fn resolve(mut self) -> Self {
+    for mut site in std::mem::take(&mut self.constant_sites) {
+      match self.constants.get(&site.event_type) {
+        Some(Some(value)) => {
+          site.event_type = value.clone();
+          self.sites.push(site);
+        },
+        _ => self.unresolved.push(site),
+      }
+    }
+    self.sites.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+    self
+  }
+
+  /// Records `NAME: &str = "value";` following a `const` keyword.
+  fn add_constant(&mut self, declaration: &str) {
+    let Some((name, rest)) = declaration.split_once(':') else {
+      return;
+    };
+    let Some((ty, value)) = rest.split(';').next().and_then(|rest| rest.split_once('=')) else {
+      return;
+    };
+    if !ty.contains("str") {
+      return;
+    }
+    if let (Some(name), Some(value)) = (constant_name(name), string_literal(value)) {
+      self
+        .constants
+        .entry(name.to_string())
+        .and_modify(|existing| {
+          if existing.as_ref() != Some(&value) {
+            *existing = None;
+          }
+        })
+        .or_insert(Some(value));
+    }
+  }
+}
+
+/// Reports invalid event types, event types declared by more than one `IndigaugeEvent` and event types used with
+/// different levels.
+pub fn check_call_sites(sites: &[CallSite]) -> Vec<ScanIssue> {
+  let mut issues = Vec::new();
+  let mut by_type = BTreeMap::<&str, Vec<&CallSite>>::new();
+
+  for site in sites {
+    match validate_event_type(&site.event_type) {
+      Ok(()) => by_type.entry(&site.event_type).or_default().push(site),
+      Err(error) => issues.push(ScanIssue {
+        path: site.path.clone(),
+        line: site.line,
+        message: format!("invalid event type '{}': {error}", site.event_type),
+      }),
+    }
+  }
+
+  for (event_type, sites) in by_type {
+    let first = sites[0];
+    let declarations = sites.iter().filter(|site| site.derived).collect::<Vec<_>>();
+    for duplicate in declarations.iter().skip(1) {
+      issues.push(ScanIssue {
+        path: duplicate.path.clone(),
+        line: duplicate.line,
+        message: format!(
+          "duplicate event type '{event_type}', also declared at {}:{}",
+          declarations[0].path.display(),
+          declarations[0].line
+        ),
+      });
+    }
+    for site in sites.iter().filter(|site| site.level != first.level) {
+      issues.push(ScanIssue {
+        path: site.path.clone(),
+        line: site.line,
+        message: format!(
+          "duplicate event type '{event_type}' with level {}, also used with level {} at {}:{}",
+          site.level,
+          first.level,
+          first.path.display(),
+          first.line
+        ),
+      });
+    }
+  }
+
+  issues
+}
+
+/// Replaces comments, and the content of string literals if `strings` is set, with spaces, keeping line breaks and
+/// byte offsets. Raw string literals such as `r#"..."#` are masked like plain ones.
+fn mask(source: &str, strings: bool) -> Result<String, std::string::FromUtf8Error> {
+  let bytes = source.as_bytes();
+  let mut code = bytes.to_vec();
+  let mut i = 0;
+
+  while i < bytes.len() {
+    match bytes[i] {
+      b'"' => {
+        i += 1;
+        while i < bytes.len() && bytes[i] != b'"' {
+          // Escapes are skipped as a whole, including an escaped multi-byte character.
+          let len = match bytes[i] {
+            b'\\' => 1 + source[i + 1..].chars().next().map_or(0, char::len_utf8),
+            _ => 1,
+          };
+          if strings {
+            code[i..(i + len).min(bytes.len())].fill(b' ');
+          }
+          i += len;
+        }
+      },
+      b'r' if !follows_identifier(bytes, i) => {
+        let hashes = bytes[i + 1..].iter().take_while(|&&byte| byte == b'#').count();
+        if bytes.get(i + 1 + hashes) == Some(&b'"') {
+          let start = i + 2 + hashes;
+          let closing = format!("\"{}", "#".repeat(hashes));
+          let end = source[start..].find(&closing).map_or(bytes.len(), |len| start + len);
+          if strings {
+            code[start..end].fill(b' ');
+          }
+          i = (end + closing.len()).min(bytes.len()) - 1;
+        }
+      },
+      b'/' if bytes.get(i + 1) == Some(&b'/') => {
+        while i < bytes.len() && bytes[i] != b'\n' {
+          code[i] = b' ';
+          i += 1;
+        }
+      },
+      b'/' if bytes.get(i + 1) == Some(&b'*') => {
+        while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+          if bytes[i] != b'\n' {
+            code[i] = b' ';
+          }
+          i += 1;
+        }
+        code[i..(i + 2).min(bytes.len())].fill(b' ');
+        i += 1;
+      },
+      b'\'' if bytes.get(i + 2) == Some(&b'\'') => i += 2,
+      b'\'' if bytes.get(i + 1) == Some(&b'\\') => {
+        i += 2;
+        while i < bytes.len() && bytes[i] != b'\'' {
+          i += 1;
+        }
+      },
+      _ => {},
+    }
+    i += 1;
+  }
+
+  // Comments and string contents are replaced as a whole, so the result is valid UTF-8 unless the source is cut in
+  // the middle of a character.
+  String::from_utf8(code)
+}
+
+/// Returns `true` if the byte at `index` continues an identifier, e.g. the `r` of `for`, so it does not start a raw
+/// string. The `b` and `c` prefixes of byte and C strings do not count as identifiers.
+fn follows_identifier(bytes: &[u8], index: usize) -> bool {
+  let is_identifier = |index: usize| bytes[index].is_ascii_alphanumeric() || bytes[index] == b'_';
+  match index.checked_sub(1) {
+    None => false,
+    Some(previous) if matches!(bytes[previous], b'b' | b'c') => previous > 0 && is_identifier(previous - 1),
+    Some(previous) => is_identifier(previous),
+  }
+}
+
+/// Splits the arguments of `(...)`, `[...]` or `{...}` at top-level commas.
+fn macro_args(code: &str) -> Option<Vec<&str>> {
+  if !code.starts_with(['(', '[', '{']) {
+    return None;
+  }
+
+  let mut args = Vec::new();
+  let mut depth = 0;
+  let mut in_string = false;
+  let mut escaped = false;
+  let mut arg_start = 1;
+  for (index, c) in code.char_indices() {
+    if in_string {
+      match c {
+        _ if escaped => escaped = false,
+        '\\' => escaped = true,
+        '"' => in_string = false,
+        _ => {},
+      }
+      continue;
+    }
+    match c {
+      '"' => in_string = true,
+      '(' | '[' | '{' => depth += 1,
+      ')' | ']' | '}' => {
+        depth -= 1;
+        if depth == 0 {
+          args.push(code[arg_start..index].trim());
+          return Some(args);
+        }
+      },
+      ',' if depth == 1 => {
+        args.push(code[arg_start..index].trim());
+        arg_start = index + 1;
+      },
+      _ => {},
+    }
+  }
+  None
+}
+
+/// Returns the name of an `UPPER_CASE` constant referenced by a path such as `EventType::PLAYER_DEATH`.
+fn constant_name(arg: &str) -> Option<&str> {
+  let name = arg.trim().rsplit("::").next()?.trim();
+  let mut chars = name.chars();
+  (chars.next().is_some_and(|c| c.is_ascii_uppercase())
+    && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+  .then_some(name)
+}
+
+/// Returns the content of a plain or raw string literal.
+fn string_literal(arg: &str) -> Option<String> {
+  let arg = arg.trim();
+  if let Some(raw) = arg.strip_prefix('r') {
+    let hashes = "#".repeat(raw.len() - raw.trim_start_matches('#').len());
+    let literal = raw[hashes.len()..]
+      .strip_prefix('"')?
+      .strip_suffix(hashes.as_str())?
+      .strip_suffix('"')?;
+    return Some(literal.to_string());
+  }
+
+  let literal = arg.strip_prefix('"')?.strip_suffix('"')?;
+  (!literal.contains(['"', '\\'])).then(|| literal.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn call_sites_are_found_outside_of_comments() {
+    let source = r#"
+// ig_info!("commented.out");
+fn update(handle: SessionHandle) {
+  ig_info!("level.start", { "level": 1 });
+  ig_warn_for!(handle, "net.lag");
+  let quote = '"';
+  enqueue_ig_event!(trace, "ai.tick", None);
+  ig_event!(debug, "ai.plan");
+  ig_error!(EventType::SAVE_FAILED);
+  ig_error!(UNKNOWN);
+  log!("ig_info!(\"not.a.call\")");
+}
+
+impl EventType {
+  const SAVE_FAILED: &'static str = "save.failed";
+}
+
+#[derive(IndigaugeEvent)]
+#[ig(event_type = "player.death", level = "warn")]
+struct PlayerDeath;
+"#;
+
+    let mut scan = Scan::default();
+    scan.add_source(Path::new("src/main.rs"), source).unwrap();
+    let scan = scan.resolve();
+
+    let found = scan
+      .sites
+      .iter()
+      .map(|site| (site.line, site.event_type.as_str(), site.level.as_str(), site.derived))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      found,
+      [
+        (4, "level.start", "info", false),
+        (5, "net.lag", "warn", false),
+        (7, "ai.tick", "trace", false),
+        (8, "ai.plan", "debug", false),
+        (9, "save.failed", "error", false),
+        (19, "player.death", "warn", true),
+      ]
+    );
+    assert_eq!(scan.unresolved[0].event_type, "UNKNOWN");
+  }
+
+  #[test]
+  fn raw_strings_and_escaped_characters_are_masked() {
+    let source = r##"
+let doc = r#"ig_info!("in.raw"); "quoted" "#;
+let path = br"C:\";
+let escaped = "\é";
+ig_info!(r"raw.event");
+ig_warn!("after.raw");
+"##;
+
+    let mut scan = Scan::default();
+    scan.add_source(Path::new("lib.rs"), source).unwrap();
+
+    let found = scan
+      .resolve()
+      .sites
+      .iter()
+      .map(|site| (site.line, site.event_type.clone()))
+      .collect::<Vec<_>>();
+    assert_eq!(found, [(5, "raw.event".to_string()), (6, "after.raw".to_string())]);
+  }
+
+  #[test]
+  fn invalid_and_duplicate_event_types_are_reported() {
+    let source = r#"
+ig_info!("level.start");
+ig_info!("level.start");
+ig_error!("level.start");
+ig_info!("not valid");
+#[ig(event_type = "player.death")]
+#[ig(event_type = "player.death")]
+"#;
+
+    let mut scan = Scan::default();
+    scan.add_source(Path::new("lib.rs"), source).unwrap();
+    let issues = check_call_sites(&scan.resolve().sites);
+
+    let lines = issues.iter().map(|issue| issue.line).collect::<Vec<_>>();
+    assert_eq!(lines, [5, 4, 7]);
+    assert!(issues[0].message.starts_with("invalid event type 'not valid'"));
+    assert!(
+      issues[1]
+        .to_string()
+        .starts_with("lib.rs:4: duplicate event type 'level.start' with level error")
+    );
+  }
+}
@@ -0,0 +1,211 @@
+//! Inspecting, exporting and purging the SDK state stored on a player's machine.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use indigauge_core::consent::CONSENT_FILE;
+use indigauge_core::http::{PLAYER_ID_FILE, is_valid_player_id};
+use indigauge_core::types::TelemetryConsent;
+use serde_json::json;
+
+/// Kind of the first line written by [`LocalState::export`].
+pub const LOCAL_STATE_KIND: &str = "local_state";
+
+/// A file holding SDK state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateFile {
+  pub path: PathBuf,
+  pub bytes: u64,
+  /// Number of records, for event files.
+  pub records: Option<usize>,
+}
+
+/// The SDK state of one game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalState {
+  /// The game data folder.
+  pub dir: PathBuf,
+  /// The stored player id, even if it is invalid.
+  pub player_id: Option<String>,
+  /// The stored consent, `None` if none was stored or it cannot be parsed.
+  pub consent: Option<TelemetryConsent>,
+  /// The files of the game data folder written by the SDK, then the given event files and their rotated files.
+  pub files: Vec<StateFile>,
+}
+
+impl LocalState {
+  /// Reads the state kept in the game data folder `dir` and the given NDJSON event files.
+  ///
+  /// Only files written by the SDK are considered, as the folder is shared with the game.
+  pub fn read(dir: &Path, event_files: &[PathBuf]) -> io::Result<Self> {
+    let read = |name: &str| {
+      fs::read_to_string(dir.join(name))
+        .ok()
+        .map(|content| content.trim().to_string())
+    };
+
+    let mut files = Vec::new();
+    for path in sdk_files(dir).into_iter().filter(|path| path.is_file()) {
+      files.push(StateFile {
+        bytes: fs::metadata(&path)?.len(),
+        path,
+        records: None,
+      });
+    }
+    for path in event_files.iter().flat_map(|path| rotated_files(path)) {
+      files.push(StateFile {
+        bytes: fs::metadata(&path)?.len(),
+        records: Some(count_records(&path)?),
+        path,
+      });
+    }
+
+    Ok(Self {
+      dir: dir.to_path_buf(),
+      player_id: read(PLAYER_ID_FILE),
+      consent: read(CONSENT_FILE).and_then(|consent| TelemetryConsent::parse(&consent)),
+      files,
+    })
+  }
+
+  /// Returns `true` if the stored player id would be kept by the SDK.
+  pub fn has_valid_player_id(&self) -> bool {
+    self.player_id.as_deref().is_some_and(is_valid_player_id)
+  }
+
+  /// Returns the number of records in the event files.
+  pub fn pending_records(&self) -> usize {
+    self.files.iter().filter_map(|file| file.records).sum()
+  }
+
+  /// Writes the state as NDJSON: a `local_state` record with the player id and consent, then the records of the
+  /// event files from oldest to newest, so the export can be validated and replayed like an event file.
+  pub fn export(&self, out: &mut impl Write) -> io::Result<()> {
+    let record = json!({
+      "kind": LOCAL_STATE_KIND,
+      "timestampMs": SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis()).unwrap_or_default(),
+      "data": {
+        "dir": self.dir,
+        "playerId": self.player_id,
+        "consent": self.consent.map(TelemetryConsent::as_str),
+      },
+    });
+    writeln!(out, "{record}")?;
+
+    for file in self.files.iter().rev().filter(|file| file.records.is_some()) {
+      for line in BufReader::new(fs::File::open(&file.path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+          writeln!(out, "{line}")?;
+        }
+      }
+    }
+    out.flush()
+  }
+
+  /// Deletes the player id and the event files. The stored consent is kept unless `include_consent` is set, so a
+  /// player who declined telemetry stays opted out. Returns the deleted files.
+  pub fn purge(&self, include_consent: bool) -> io::Result<Vec<PathBuf>> {
+    let consent_path = self.dir.join(CONSENT_FILE);
+    let mut deleted = Vec::new();
+
+    for file in &self.files {
+      if file.path == consent_path && !include_consent {
+        continue;
+      }
+      fs::remove_file(&file.path)?;
+      deleted.push(file.path.clone());
+    }
+    Ok(deleted)
+  }
+}
+
+/// The files the SDK may write to the game data folder.
+fn sdk_files(dir: &Path) -> Vec<PathBuf> {
+  [PLAYER_ID_FILE, &format!("{PLAYER_ID_FILE}.tmp"), CONSENT_FILE]
+    .into_iter()
+    .map(|name| dir.join(name))
+    .collect()
+}
+
+/// Returns an event file and its rotated files `path.1`, `path.2`, ... that exist, newest first.
+fn rotated_files(path: &Path) -> Vec<PathBuf> {
+  let rotated = (1..).map(|index| {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{index}"));
+    PathBuf::from(rotated)
+  });
+
+  std::iter::once(path.to_path_buf())
+    .filter(|path| path.is_file())
+    .chain(rotated.take_while(|path| path.is_file()))
+    .collect()
+}
+
+fn count_records(path: &Path) -> io::Result<usize> {
+  let mut records = 0;
+  for line in BufReader::new(fs::File::open(path)?).lines() {
+    records += usize::from(!line?.trim().is_empty());
+  }
+  Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+  use indigauge_core::types::{EventPayload, IndigaugeLogLevel};
+  use serde_json::Value;
+
+  use super::*;
+  use crate::recording::Recording;
+
+  fn temp_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+      "indigauge-cli-state-{}-{}",
+      std::process::id(),
+      SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos()
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  #[test]
+  fn state_is_exported_and_purged_without_consent() {
+    let dir = temp_dir();
+    let event_file = dir.join("events.ndjson");
+    fs::write(dir.join(PLAYER_ID_FILE), "player-1\n").unwrap();
+    fs::write(dir.join(CONSENT_FILE), "denied").unwrap();
+    fs::write(dir.join("settings.ron"), "()").unwrap();
+    let payload = EventPayload::new("level.start", IndigaugeLogLevel::Info, None, 5);
+    let event = json!({ "kind": "event", "timestampMs": 1, "session": 0, "data": payload });
+    fs::write(format!("{}.1", event_file.display()), format!("{event}\n")).unwrap();
+    fs::write(&event_file, format!("{event}\n\n{event}\n")).unwrap();
+
+    let state = LocalState::read(&dir, std::slice::from_ref(&event_file)).unwrap();
+    assert_eq!(state.player_id.as_deref(), Some("player-1"));
+    assert!(state.has_valid_player_id());
+    assert_eq!(state.consent, Some(TelemetryConsent::Denied));
+    assert_eq!(state.files.len(), 4);
+    assert_eq!(state.pending_records(), 3);
+
+    let mut export = Vec::new();
+    state.export(&mut export).unwrap();
+    let export = String::from_utf8(export).unwrap();
+    let first = serde_json::from_str::<Value>(export.lines().next().unwrap()).unwrap();
+    assert_eq!(first["kind"], LOCAL_STATE_KIND);
+    assert_eq!(first["data"]["consent"], "denied");
+    assert_eq!(export.lines().count(), 4);
+    let recording = Recording::parse(&export);
+    assert!(recording.issues.is_empty(), "{:?}", recording.issues);
+
+    let deleted = state.purge(false).unwrap();
+    assert_eq!(deleted.len(), 3);
+    assert!(dir.join(CONSENT_FILE).exists());
+    assert!(dir.join("settings.ron").exists());
+    assert!(!dir.join(PLAYER_ID_FILE).exists());
+    assert_eq!(LocalState::read(&dir, &[event_file]).unwrap().files.len(), 1);
+
+    let _ = fs::remove_dir_all(dir);
+  }
+}
@@ -0,0 +1,64 @@
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use indigauge_types::prelude::TelemetryConsent;
+
+static CONSENT: RwLock<TelemetryConsent> = RwLock::new(TelemetryConsent::Unknown);
+static CONSENT_REQUIRED: AtomicBool = AtomicBool::new(false);
+
+/// Name of the file holding the persisted consent, next to the player id.
+pub const CONSENT_FILE: &str = "consent.txt";
+
+/// Sets the consent given by the player.
+pub fn set_consent(consent: TelemetryConsent) {
+  *CONSENT.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = consent;
+}
+
+/// Returns the consent given by the player, `Unknown` if they have not decided.
+pub fn get_consent() -> TelemetryConsent {
+  *CONSENT.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Sets whether telemetry is denied until the player explicitly consents.
+///
+/// **Defaults to `false`:** while the consent is `Unknown`, telemetry is collected as if it was `Granted`, including
+/// the persistent player id. Set it to `true` where the player must opt in first, e.g. under the GDPR.
+pub fn set_consent_required(required: bool) {
+  CONSENT_REQUIRED.store(required, Ordering::Relaxed);
+}
+
+/// Returns the consent that currently applies, with `Unknown` resolved.
+pub fn effective_consent() -> TelemetryConsent {
+  get_consent().resolve(CONSENT_REQUIRED.load(Ordering::Relaxed))
+}
+
+#[cfg(not(target_family = "wasm"))]
+/// Loads the consent persisted next to the player id, `Unknown` if none was stored.
+pub fn load_consent(game_name: &str) -> TelemetryConsent {
+  crate::utils::game_data_dir(game_name)
+    .and_then(|dir| std::fs::read_to_string(dir.join(CONSENT_FILE)).ok())
+    .and_then(|consent| TelemetryConsent::parse(&consent))
+    .unwrap_or_default()
+}
+
+#[cfg(not(target_family = "wasm"))]
+/// Persists the consent next to the player id so it applies to later launches.
+pub fn store_consent(game_name: &str, consent: TelemetryConsent) -> std::io::Result<()> {
+  let dir = crate::utils::game_data_dir(game_name).ok_or(std::io::ErrorKind::NotFound)?;
+  std::fs::create_dir_all(&dir)?;
+  std::fs::write(dir.join(CONSENT_FILE), consent.as_str())
+}
+
+#[cfg(target_family = "wasm")]
+/// Loads the consent persisted in `localStorage`, `Unknown` if none was stored.
+pub fn load_consent(game_name: &str) -> TelemetryConsent {
+  crate::web::read_item(game_name, CONSENT_FILE)
+    .and_then(|consent| TelemetryConsent::parse(&consent))
+    .unwrap_or_default()
+}
+
+#[cfg(target_family = "wasm")]
+/// Persists the consent in `localStorage` so it applies to later visits.
+pub fn store_consent(game_name: &str, consent: TelemetryConsent) -> std::io::Result<()> {
+  crate::web::write_item(game_name, CONSENT_FILE, consent.as_str())
+}
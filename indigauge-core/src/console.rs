@@ -0,0 +1,235 @@
+//! Human-readable console output of events, e.g. to see what `ig_*` calls emit while wiring up instrumentation.
+
+#[cfg(not(target_family = "wasm"))]
+use std::io::{IsTerminal, Write};
+
+use indigauge_types::prelude::{EventPayload, IndigaugeLogLevel};
+
+use crate::sink::EventSink;
+use crate::state::SessionHandle;
+
+/// Maximum length of the metadata printed with [`ConsoleVerbosity::Normal`].
+const MAX_METADATA_CHARS: usize = 160;
+
+/// How much of each event the [`ConsolePrinter`] prints.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConsoleVerbosity {
+  /// Nothing is printed.
+  Off,
+  /// Elapsed time, level and event type.
+  Compact,
+  /// Also the compact metadata, and the file and line of warnings and errors.
+  #[default]
+  Normal,
+  /// Also the module, with the full metadata pretty-printed.
+  Verbose,
+}
+
+/// Prints events as one line each: elapsed time, level, event type, metadata and, for warnings and errors, the
+/// file and line they were emitted at.
+///
+/// Colours are used when stdout is a terminal, unless `NO_COLOR` is set or `TERM` is `dumb`.
+#[derive(Clone, Debug)]
+pub struct ConsolePrinter {
+  verbosity: ConsoleVerbosity,
+  color: bool,
+}
+
+impl Default for ConsolePrinter {
+  fn default() -> Self {
+    Self {
+      verbosity: ConsoleVerbosity::default(),
+      color: supports_color(),
+    }
+  }
+}
+
+impl ConsolePrinter {
+  /// Creates a printer with [`ConsoleVerbosity::Normal`] that uses colours if the terminal supports them.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Creates a printer with [`ConsoleVerbosity::Normal`] that never uses colours, e.g. to format events for a UI.
+  ///
+  /// Unlike [`Self::new`], it does not inspect the terminal or the environment.
+  pub const fn plain() -> Self {
+    Self {
+      verbosity: ConsoleVerbosity::Normal,
+      color: false,
+    }
+  }
+
+  /// Sets how much of each event is printed.
+  pub fn with_verbosity(mut self, verbosity: ConsoleVerbosity) -> Self {
+    self.verbosity = verbosity;
+    self
+  }
+
+  /// Forces colours on or off.
+  pub fn with_color(mut self, color: bool) -> Self {
+    self.color = color;
+    self
+  }
+
+  /// Returns the verbosity.
+  pub fn verbosity(&self) -> ConsoleVerbosity {
+    self.verbosity
+  }
+
+  /// Returns `true` if colours are used.
+  pub fn color(&self) -> bool {
+    self.color
+  }
+
+  /// Formats an event as printed, without the trailing newline.
+  pub fn format(&self, session: SessionHandle, event: &EventPayload) -> String {
+    let level = event.level();
+    let mut line = format!(
+      "{} {} ",
+      self.paint("2", &format!("{:>8.3}s", event.elapsed_ms() as f64 / 1000.0)),
+      self.paint(level_style(level), &format!("{:<5}", level.as_str().to_uppercase()))
+    );
+    if !session.is_default() {
+      line.push_str(&self.paint("2", &format!("[session {}] ", session.id())));
+    }
+    line.push_str(&self.paint("1", event.event_type()));
+
+    if matches!(self.verbosity, ConsoleVerbosity::Off | ConsoleVerbosity::Compact) {
+      return line;
+    }
+
+    if let Some(context) = event.context() {
+      let mut location = format!("{}:{}", context.file, context.line);
+      if self.verbosity == ConsoleVerbosity::Verbose
+        && let Some(module) = &context.module
+      {
+        location = format!("{module} {location}");
+      }
+      line.push_str(&format!(" {}", self.paint("2", &format!("at {location}"))));
+    }
+
+    if let Some(metadata) = event.metadata() {
+      if self.verbosity == ConsoleVerbosity::Verbose {
+        let pretty = serde_json::to_string_pretty(metadata).unwrap_or_default();
+        for metadata_line in pretty.lines() {
+          line.push_str(&format!("\n           {}", self.paint("2", metadata_line)));
+        }
+      } else {
+        let compact = metadata.to_string();
+        let compact = match compact.char_indices().nth(MAX_METADATA_CHARS) {
+          Some((end, _)) => format!("{}…", &compact[..end]),
+          None => compact,
+        };
+        line.push_str(&format!(" {}", self.paint("2", &compact)));
+      }
+    }
+
+    line
+  }
+
+  fn paint(&self, style: &str, text: &str) -> String {
+    if self.color {
+      format!("\x1b[{style}m{text}\x1b[0m")
+    } else {
+      text.to_string()
+    }
+  }
+}
+
+impl EventSink for ConsolePrinter {
+  fn send(&mut self, session: SessionHandle, events: &[EventPayload]) {
+    if self.verbosity == ConsoleVerbosity::Off {
+      return;
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    {
+      let mut stdout = std::io::stdout().lock();
+      for event in events {
+        let _ = writeln!(stdout, "{}", self.format(session, event));
+      }
+    }
+
+    #[cfg(target_family = "wasm")]
+    for event in events {
+      web_sys::console::log_1(&self.format(session, event).into());
+    }
+  }
+}
+
+/// ANSI style of a level.
+fn level_style(level: IndigaugeLogLevel) -> &'static str {
+  match level {
+    IndigaugeLogLevel::Trace => "35",
+    IndigaugeLogLevel::Debug => "34",
+    IndigaugeLogLevel::Info => "32",
+    IndigaugeLogLevel::Warn => "33",
+    IndigaugeLogLevel::Error => "31",
+    IndigaugeLogLevel::Fatal => "1;31",
+    IndigaugeLogLevel::Silent => "2",
+  }
+}
+
+/// Returns `true` if stdout is a terminal that supports colours.
+#[cfg(not(target_family = "wasm"))]
+fn supports_color() -> bool {
+  std::env::var_os("NO_COLOR").is_none()
+    && std::env::var("TERM").map_or(true, |term| term != "dumb")
+    && std::io::stdout().is_terminal()
+}
+
+#[cfg(target_family = "wasm")]
+fn supports_color() -> bool {
+  false
+}
+
+#[cfg(test)]
+mod tests {
+  use indigauge_types::prelude::EventPayloadCtx;
+  use serde_json::json;
+
+  use super::*;
+
+  fn warning() -> EventPayload {
+    EventPayload::new("combat.miss", IndigaugeLogLevel::Warn, Some(json!({ "target": "slime" })), 1500).with_context(
+      Some(EventPayloadCtx {
+        file: "src/combat.rs".to_string(),
+        line: 42,
+        module: Some("game::combat".into()),
+      }),
+    )
+  }
+
+  #[test]
+  fn events_are_formatted_by_verbosity() {
+    let printer = ConsolePrinter::plain();
+
+    assert_eq!(
+      printer.format(SessionHandle::DEFAULT, &warning()),
+      r#"   1.500s WARN  combat.miss at src/combat.rs:42 {"target":"slime"}"#
+    );
+    assert_eq!(
+      printer
+        .clone()
+        .with_verbosity(ConsoleVerbosity::Compact)
+        .format(SessionHandle::DEFAULT, &warning()),
+      "   1.500s WARN  combat.miss"
+    );
+
+    let verbose = printer
+      .with_verbosity(ConsoleVerbosity::Verbose)
+      .format(SessionHandle::DEFAULT, &warning());
+    assert!(verbose.starts_with("   1.500s WARN  combat.miss at game::combat src/combat.rs:42\n"));
+    assert!(verbose.contains(r#""target": "slime""#));
+  }
+
+  #[test]
+  fn colours_are_applied_when_enabled() {
+    let line = ConsolePrinter::new()
+      .with_color(true)
+      .format(SessionHandle::DEFAULT, &warning());
+    assert!(line.contains("\x1b[33mWARN "));
+    assert!(line.ends_with("\x1b[0m"));
+  }
+}
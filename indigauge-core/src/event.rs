@@ -1,18 +1,49 @@
-use std::sync::OnceLock;
+use std::panic::Location;
+use std::sync::{OnceLock, RwLock};
 
-use indigauge_types::prelude::EventPayload;
+use indigauge_types::prelude::{EventPayload, IndigaugeLogLevel};
 use serde_json::Value;
 
+use crate::state::SessionHandle;
+
+#[cfg(feature = "derive")]
+pub use indigauge_derive::IndigaugeEvent;
+
 /// Queued event with basic validation helpers.
 #[derive(Clone, Debug)]
 pub struct QueuedEvent {
   payload: EventPayload,
+  session: SessionHandle,
 }
 
 impl QueuedEvent {
-  /// Creates a new queued event wrapper.
+  /// Creates a new queued event wrapper for the default session.
   pub fn new(payload: EventPayload) -> Self {
-    Self { payload }
+    Self {
+      payload,
+      session: SessionHandle::DEFAULT,
+    }
+  }
+
+  /// Routes the event to the given session.
+  pub fn with_session(mut self, session: SessionHandle) -> Self {
+    self.session = session;
+    self
+  }
+
+  /// Returns the session the event belongs to.
+  pub fn session(&self) -> SessionHandle {
+    self.session
+  }
+
+  /// Returns the inner event payload.
+  pub fn payload(&self) -> &EventPayload {
+    &self.payload
+  }
+
+  /// Returns the inner event payload mutably.
+  pub fn payload_mut(&mut self) -> &mut EventPayload {
+    &mut self.payload
   }
 
   /// Unwraps and returns the inner event payload.
@@ -20,14 +51,16 @@ impl QueuedEvent {
     self.payload
   }
 
-  /// Validates the event type format.
+  /// Validates the event type against the runtime [`EventNamingPolicy`].
   pub fn validate(&self) -> Result<(), String> {
-    validate_event_type(self.payload.event_type()).map_err(str::to_string)
+    event_naming_policy()
+      .validate(self.payload.event_type())
+      .map_err(str::to_string)
   }
 }
 
 type DispatchFn = fn(
-  level: &'static str,
+  level: IndigaugeLogLevel,
   event_type: &str,
   metadata: Option<Value>,
   file: &'static str,
@@ -45,7 +78,7 @@ pub fn set_event_dispatcher(dispatch: DispatchFn) {
 
 /// Dispatches an event through the registered dispatcher if available.
 pub fn dispatch_event(
-  level: &'static str,
+  level: IndigaugeLogLevel,
   event_type: &str,
   metadata: Option<Value>,
   file: &'static str,
@@ -58,48 +91,250 @@ pub fn dispatch_event(
     .unwrap_or(false)
 }
 
-/// Runtime validation for event types.
-pub const fn validate_event_type(s: &str) -> Result<(), &'static str> {
-  let bytes = s.as_bytes();
-  let len = bytes.len();
-
-  if len < 3 {
-    return Err("Invalid event type: too short (expected 'a.b')");
-  }
-
-  let mut dot_index: Option<usize> = None;
-  let mut i = 0;
-
-  while i < len {
-    let b = bytes[i];
-    match b {
-      b'a'..=b'z' | b'A'..=b'Z' => {},
-      b'.' => {
-        if dot_index.is_some() {
-          return Err("Invalid event type: multiple '.' found");
-        }
-        dot_index = Some(i);
-      },
-      _ => return Err("Invalid event type: only letters and a single '.' are allowed"),
-    }
-    i += 1;
+type SessionDispatchFn = fn(
+  session: SessionHandle,
+  level: IndigaugeLogLevel,
+  event_type: &str,
+  metadata: Option<Value>,
+  file: &'static str,
+  line: u32,
+  module: &'static str,
+) -> bool;
+
+static SESSION_DISPATCH: OnceLock<SessionDispatchFn> = OnceLock::new();
+
+/// Registers the dispatcher used by the `ig_*_for` macros to route events to a specific session.
+/// Can be set once; subsequent calls are ignored.
+pub fn set_session_event_dispatcher(dispatch: SessionDispatchFn) {
+  let _ = SESSION_DISPATCH.set(dispatch);
+}
+
+/// Dispatches an event for a specific session through the registered session dispatcher.
+///
+/// Events for [`SessionHandle::DEFAULT`] fall back to the regular dispatcher if no session dispatcher is set.
+pub fn dispatch_session_event(
+  session: SessionHandle,
+  level: IndigaugeLogLevel,
+  event_type: &str,
+  metadata: Option<Value>,
+  file: &'static str,
+  line: u32,
+  module: &'static str,
+) -> bool {
+  match SESSION_DISPATCH.get() {
+    Some(dispatch) => dispatch(session, level, event_type, metadata, file, line, module),
+    None if session.is_default() => dispatch_event(level, event_type, metadata, file, line, module),
+    None => false,
   }
+}
 
-  let dot_pos = match dot_index {
-    Some(p) => p,
-    None => return Err("Invalid event type: must contain one '.'"),
+/// Field names the tracing layer and the `log` bridge read the event type from.
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub(crate) const EVENT_TYPE_FIELDS: &[&str] = &["ig", "event_type"];
+
+/// Sink abstraction used by the tracing layer and the `log` bridge to emit events.
+///
+/// Metadata is passed as recorded; [`enqueue`](crate::state::enqueue) redacts it when the event is queued.
+pub trait IndigaugeSink: Send + Sync + 'static {
+  /// Emits a structured tracing or log event.
+  fn log(
+    &self,
+    level: IndigaugeLogLevel,
+    event_type: &str,
+    metadata: Option<serde_json::Value>,
+    file: &'static str,
+    line: u32,
+    module: &'static str,
+  );
+}
+
+/// An event with a fixed type and level whose fields are sent as metadata.
+///
+/// Usually derived with `#[derive(IndigaugeEvent)]` (feature `derive`) and emitted with [`ig_emit!`](crate::ig_emit)
+/// or [`IndigaugeEvent::emit`].
+pub trait IndigaugeEvent {
+  /// The event type in `namespace.event` format.
+  const EVENT_TYPE: &'static str;
+  /// The level the event is emitted at.
+  const LEVEL: IndigaugeLogLevel;
+  /// The module reported by [`Self::emit`] and [`Self::emit_for`]. The derive sets it to the module defining the
+  /// event; [`ig_emit!`](crate::ig_emit) reports the calling module instead.
+  const MODULE: &'static str = "";
+
+  /// Returns the event's fields as a JSON object.
+  fn metadata(&self) -> Value;
+
+  /// Returns the JSON schema of the event's metadata.
+  fn schema() -> Value
+  where
+    Self: Sized;
+
+  /// Enqueues the event for the default session. Returns `true` if it was queued.
+  #[track_caller]
+  fn emit(&self) -> bool {
+    let caller = Location::caller();
+    emit_event(self, caller.file(), caller.line(), Self::MODULE)
+  }
+
+  /// Enqueues the event for the given session. Returns `true` if it was queued.
+  #[track_caller]
+  fn emit_for(&self, session: SessionHandle) -> bool {
+    let caller = Location::caller();
+    emit_session_event(session, self, caller.file(), caller.line(), Self::MODULE)
+  }
+}
+
+/// Dispatches a typed event through the registered dispatcher. Used by [`ig_emit!`](crate::ig_emit).
+pub fn emit_event<E: IndigaugeEvent + ?Sized>(event: &E, file: &'static str, line: u32, module: &'static str) -> bool {
+  dispatch_event(E::LEVEL, E::EVENT_TYPE, Some(event.metadata()), file, line, module)
+}
+
+/// Dispatches a typed event for a specific session. Used by [`ig_emit_for!`](crate::ig_emit_for).
+pub fn emit_session_event<E: IndigaugeEvent + ?Sized>(
+  session: SessionHandle,
+  event: &E,
+  file: &'static str,
+  line: u32,
+  module: &'static str,
+) -> bool {
+  dispatch_session_event(session, E::LEVEL, E::EVENT_TYPE, Some(event.metadata()), file, line, module)
+}
+
+/// Rules that event types must follow, e.g. `ui.menu.open` or `level_3.complete`.
+///
+/// Event types are made of `.`-separated segments. Each segment starts with an ASCII letter, followed by letters and,
+/// if allowed, digits and `_`. All checks are `const`, so a policy can be used in compile-time assertions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventNamingPolicy {
+  /// Minimum number of segments, at least 1.
+  pub min_segments: usize,
+  /// Maximum number of segments.
+  pub max_segments: usize,
+  /// Maximum length of a single segment.
+  pub max_segment_len: usize,
+  /// Maximum length of the whole event type.
+  pub max_len: usize,
+  /// Allows ASCII digits after the first character of a segment.
+  pub allow_digits: bool,
+  /// Allows `_` after the first character of a segment.
+  pub allow_underscores: bool,
+}
+
+impl EventNamingPolicy {
+  /// Up to four segments of letters, digits and `_`, 32 characters per segment and 128 in total.
+  pub const DEFAULT: Self = Self {
+    min_segments: 2,
+    max_segments: 4,
+    max_segment_len: 32,
+    max_len: 128,
+    allow_digits: true,
+    allow_underscores: true,
+  };
+
+  /// Exactly two segments of letters only (`namespace.event`).
+  pub const STRICT: Self = Self {
+    min_segments: 2,
+    max_segments: 2,
+    max_segment_len: 64,
+    max_len: 128,
+    allow_digits: false,
+    allow_underscores: false,
   };
 
-  if dot_pos == 0 || dot_pos == len - 1 {
-    return Err("Invalid event type: '.' cannot be the first or last character");
+  /// Checks an event type against the policy.
+  pub const fn validate(&self, s: &str) -> Result<(), &'static str> {
+    let bytes = s.as_bytes();
+    let len = bytes.len();
+
+    if len == 0 {
+      return Err("Invalid event type: empty");
+    }
+    if len > self.max_len {
+      return Err("Invalid event type: too long");
+    }
+
+    let mut segments = 1;
+    let mut segment_len = 0;
+    let mut i = 0;
+
+    while i < len {
+      let b = bytes[i];
+      match b {
+        b'.' => {
+          if segment_len == 0 {
+            return Err("Invalid event type: segments cannot be empty");
+          }
+          segments += 1;
+          segment_len = 0;
+        },
+        b'a'..=b'z' | b'A'..=b'Z' => segment_len += 1,
+        b'0'..=b'9' if self.allow_digits => {
+          if segment_len == 0 {
+            return Err("Invalid event type: segments must start with a letter");
+          }
+          segment_len += 1;
+        },
+        b'_' if self.allow_underscores => {
+          if segment_len == 0 {
+            return Err("Invalid event type: segments must start with a letter");
+          }
+          segment_len += 1;
+        },
+        _ => return Err("Invalid event type: contains a character the naming policy does not allow"),
+      }
+      if segment_len > self.max_segment_len {
+        return Err("Invalid event type: segment too long");
+      }
+      i += 1;
+    }
+
+    if segment_len == 0 {
+      return Err("Invalid event type: segments cannot be empty");
+    }
+    if segments < self.min_segments {
+      return Err("Invalid event type: too few '.'-separated segments");
+    }
+    if segments > self.max_segments {
+      return Err("Invalid event type: too many '.'-separated segments");
+    }
+
+    Ok(())
   }
+}
 
-  Ok(())
+impl Default for EventNamingPolicy {
+  fn default() -> Self {
+    Self::DEFAULT
+  }
+}
+
+static NAMING_POLICY: RwLock<EventNamingPolicy> = RwLock::new(EventNamingPolicy::DEFAULT);
+
+/// Sets the naming policy used to validate queued and tracing-derived event types at runtime.
+///
+/// The `ig_*` macros always check literals against [`EventNamingPolicy::DEFAULT`] at compile time.
+pub fn set_event_naming_policy(policy: EventNamingPolicy) {
+  *NAMING_POLICY.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = policy;
+}
+
+/// Returns the naming policy used at runtime.
+pub fn event_naming_policy() -> EventNamingPolicy {
+  *NAMING_POLICY.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Validates an event type against [`EventNamingPolicy::DEFAULT`].
+pub const fn validate_event_type(s: &str) -> Result<(), &'static str> {
+  EventNamingPolicy::DEFAULT.validate(s)
 }
 
 /// Compile-time validation for event types used in macros.
 pub const fn validate_event_type_compile_time(s: &str) -> &str {
-  if let Err(err) = validate_event_type(s) {
+  validate_event_type_compile_time_with(&EventNamingPolicy::DEFAULT, s)
+}
+
+/// Compile-time validation against a custom policy, for games that enforce stricter names in their own macros.
+pub const fn validate_event_type_compile_time_with<'a>(policy: &EventNamingPolicy, s: &'a str) -> &'a str {
+  if let Err(err) = policy.validate(s) {
     panic!("{}", err);
   }
   s
@@ -107,6 +342,27 @@ pub const fn validate_event_type_compile_time(s: &str) -> &str {
 
 /// Public macros for emitting Indigauge events with optional JSON metadata.
 pub mod macros {
+  /// Converts a level identifier (`trace`, `debug`, `info`, `warn` or `error`) into an
+  /// [`IndigaugeLogLevel`](crate::types::IndigaugeLogLevel). Other identifiers are rejected at compile time.
+  #[macro_export]
+  macro_rules! ig_level {
+    (trace) => {
+      $crate::types::IndigaugeLogLevel::Trace
+    };
+    (debug) => {
+      $crate::types::IndigaugeLogLevel::Debug
+    };
+    (info) => {
+      $crate::types::IndigaugeLogLevel::Info
+    };
+    (warn) => {
+      $crate::types::IndigaugeLogLevel::Warn
+    };
+    (error) => {
+      $crate::types::IndigaugeLogLevel::Error
+    };
+  }
+
   /// Low-level helper used by the `ig_*` macros to queue an event.
   ///
   /// Validates the `event_type` at compile time and forwards it to the dispatcher
@@ -115,10 +371,10 @@ pub mod macros {
   ///
   /// # Examples
   /// ```rust,no_run
-  /// use indigauge_core::{enqueue_ig_event, event::set_event_dispatcher};
+  /// use indigauge_core::{enqueue_ig_event, event::set_event_dispatcher, types::IndigaugeLogLevel};
   /// use serde_json::Value;
   ///
-  /// fn dispatcher(_: &'static str, _: &str, _: Option<Value>, _: &'static str, _: u32, _: &'static str) -> bool {
+  /// fn dispatcher(_: IndigaugeLogLevel, _: &str, _: Option<Value>, _: &'static str, _: u32, _: &'static str) -> bool {
   ///   true
   /// }
   ///
@@ -131,7 +387,8 @@ pub mod macros {
   macro_rules! enqueue_ig_event {
     ($level: ident, $etype:expr, $metadata:expr) => {{
       const _VALID: &str = $crate::event::validate_event_type_compile_time($etype);
-      let _ = $crate::event::dispatch_event(stringify!($level), $etype, $metadata, file!(), line!(), module_path!());
+      let _ =
+        $crate::event::dispatch_event($crate::ig_level!($level), $etype, $metadata, file!(), line!(), module_path!());
     }};
   }
 
@@ -140,8 +397,8 @@ pub mod macros {
   /// Usage example: `ig_event!(info, "ui.click", { "button": btn_id, "x": x, "y": y });`
   ///
   /// The `event_type` must be a string literal in the form `"namespace.event"` and
-  /// is compile-time validated to ensure it contains exactly one `.` and
-  /// only letters on each side. Metadata is encoded as
+  /// is compile-time validated against
+  /// [`EventNamingPolicy::DEFAULT`](crate::event::EventNamingPolicy::DEFAULT). Metadata is encoded as
   /// JSON using a shorthand object literal with string keys.
   #[macro_export]
   macro_rules! ig_event {
@@ -162,8 +419,8 @@ pub mod macros {
   /// ```
   ///
   /// * `<event_type>` — must be a string literal formatted as "namespace.event",
-  ///   e.g. "ui.click", "gameplay.start". The value is compile-time validated
-  ///   to ensure it contains exactly one `.` and only letters on each side.
+  ///   e.g. "ui.click", "ui.menu.open". The value is compile-time validated
+  ///   against [`EventNamingPolicy::DEFAULT`](crate::event::EventNamingPolicy::DEFAULT).
   /// * Optional metadata can be passed as a JSON-like key/value list.
   ///
   /// # Examples
@@ -184,8 +441,8 @@ pub mod macros {
   /// ```
   ///
   /// * `<event_type>` — must be a string literal formatted as "namespace.event",
-  ///   e.g. "ui.click", "gameplay.start". The value is compile-time validated
-  ///   to ensure it contains exactly one `.` and only letters on each side.
+  ///   e.g. "ui.click", "ui.menu.open". The value is compile-time validated
+  ///   against [`EventNamingPolicy::DEFAULT`](crate::event::EventNamingPolicy::DEFAULT).
   /// * Optional metadata can be passed as a JSON-like key/value list.
   ///
   /// # Examples
@@ -208,8 +465,8 @@ pub mod macros {
   /// ```
   ///
   /// * `<event_type>` — must be a string literal formatted as "namespace.event",
-  ///   e.g. "ui.click", "gameplay.start". The value is compile-time validated
-  ///   to ensure it contains exactly one `.` and only letters on each side.
+  ///   e.g. "ui.click", "ui.menu.open". The value is compile-time validated
+  ///   against [`EventNamingPolicy::DEFAULT`](crate::event::EventNamingPolicy::DEFAULT).
   /// * Optional metadata can be passed as a JSON-like key/value list.
   ///
   /// # Examples
@@ -233,8 +490,8 @@ pub mod macros {
   /// ```
   ///
   /// * `<event_type>` — must be a string literal formatted as "namespace.event",
-  ///   e.g. "ui.click", "gameplay.start". The value is compile-time validated
-  ///   to ensure it contains exactly one `.` and only letters on each side.
+  ///   e.g. "ui.click", "ui.menu.open". The value is compile-time validated
+  ///   against [`EventNamingPolicy::DEFAULT`](crate::event::EventNamingPolicy::DEFAULT).
   /// * Optional metadata can be passed as a JSON-like key/value list.
   ///
   /// # Examples
@@ -257,8 +514,8 @@ pub mod macros {
   /// ```
   ///
   /// * `<event_type>` — must be a string literal formatted as "namespace.event",
-  ///   e.g. "ui.click", "gameplay.start". The value is compile-time validated
-  ///   to ensure it contains exactly one `.` and only letters on each side.
+  ///   e.g. "ui.click", "ui.menu.open". The value is compile-time validated
+  ///   against [`EventNamingPolicy::DEFAULT`](crate::event::EventNamingPolicy::DEFAULT).
   /// * Optional metadata can be passed as a JSON-like key/value list.
   ///
   /// # Examples
@@ -272,6 +529,88 @@ pub mod macros {
   macro_rules! ig_error {
       ($($tt:tt)*) => { $crate::ig_event!(error, $($tt)*); }
   }
+
+  /// Like [`enqueue_ig_event!`], but routes the event to the session behind the given
+  /// [`SessionHandle`](crate::state::SessionHandle) instead of the default session.
+  #[macro_export]
+  macro_rules! enqueue_ig_event_for {
+    ($session:expr, $level: ident, $etype:expr, $metadata:expr) => {{
+      const _VALID: &str = $crate::event::validate_event_type_compile_time($etype);
+      let _ = $crate::event::dispatch_session_event(
+        $session,
+        $crate::ig_level!($level),
+        $etype,
+        $metadata,
+        file!(),
+        line!(),
+        module_path!(),
+      );
+    }};
+  }
+
+  /// Emit an Indigauge event for a specific session, e.g. the session of one local player.
+  ///
+  /// Usage example: `ig_event_for!(player.handle(), info, "ui.click", { "button": btn_id });`
+  ///
+  /// Accepts the same event type and metadata as [`ig_event!`].
+  #[macro_export]
+  macro_rules! ig_event_for {
+    ($session:expr, $level:ident, $etype:expr $(,)?) => {{
+      $crate::enqueue_ig_event_for!($session, $level, $etype, None);
+    }};
+    ($session:expr, $level:ident, $etype:expr $(, { $($key:tt : $value:expr),* $(,)? })? ) => {{
+      let meta = serde_json::json!({ $($($key : $value),*)? });
+      $crate::enqueue_ig_event_for!($session, $level, $etype, Some(meta));
+    }};
+  }
+
+  /// Session-routed variant of [`ig_trace!`]: `ig_trace_for!(handle, "ui.hover")`.
+  #[macro_export]
+  macro_rules! ig_trace_for {
+      ($session:expr, $($tt:tt)*) => { $crate::ig_event_for!($session, trace, $($tt)*); }
+  }
+
+  /// Session-routed variant of [`ig_debug!`]: `ig_debug_for!(handle, "pathfinding.step")`.
+  #[macro_export]
+  macro_rules! ig_debug_for {
+      ($session:expr, $($tt:tt)*) => { $crate::ig_event_for!($session, debug, $($tt)*); }
+  }
+
+  /// Session-routed variant of [`ig_info!`]: `ig_info_for!(handle, "gameplay.start")`.
+  #[macro_export]
+  macro_rules! ig_info_for {
+      ($session:expr, $($tt:tt)*) => { $crate::ig_event_for!($session, info, $($tt)*); }
+  }
+
+  /// Session-routed variant of [`ig_warn!`]: `ig_warn_for!(handle, "save.failed")`.
+  #[macro_export]
+  macro_rules! ig_warn_for {
+      ($session:expr, $($tt:tt)*) => { $crate::ig_event_for!($session, warn, $($tt)*); }
+  }
+
+  /// Session-routed variant of [`ig_error!`]: `ig_error_for!(handle, "network.disconnect")`.
+  #[macro_export]
+  macro_rules! ig_error_for {
+      ($session:expr, $($tt:tt)*) => { $crate::ig_event_for!($session, error, $($tt)*); }
+  }
+
+  /// Emit a typed [`IndigaugeEvent`](crate::event::IndigaugeEvent), using its fields as metadata.
+  ///
+  /// Usage example: `ig_emit!(PlayerDeath { cause: "lava".into(), level: 3 });`
+  #[macro_export]
+  macro_rules! ig_emit {
+    ($event:expr $(,)?) => {{
+      let _ = $crate::event::emit_event(&$event, file!(), line!(), module_path!());
+    }};
+  }
+
+  /// Session-routed variant of [`ig_emit!`]: `ig_emit_for!(handle, PlayerJoined { slot: 2 })`.
+  #[macro_export]
+  macro_rules! ig_emit_for {
+    ($session:expr, $event:expr $(,)?) => {{
+      let _ = $crate::event::emit_session_event($session, &$event, file!(), line!(), module_path!());
+    }};
+  }
 }
 
 #[cfg(test)]
@@ -287,12 +626,37 @@ mod tests {
     assert!(validate_event_type(".start").is_err());
     assert!(validate_event_type("game.").is_err());
     assert!(validate_event_type("game..start").is_err());
+    assert!(validate_event_type("level_3.complete").is_ok());
+    assert!(validate_event_type("ui.menu.open").is_ok());
+    assert!(validate_event_type("economy.coins_spent").is_ok());
+    assert!(validate_event_type("3d.start").is_err());
+    assert!(validate_event_type("ui._menu").is_err());
+    assert!(validate_event_type("a.b.c.d.e").is_err());
+    assert!(validate_event_type("game.start-now").is_err());
+  }
+
+  #[test]
+  fn policy_limits_apply() {
+    let policy = EventNamingPolicy {
+      max_segment_len: 4,
+      max_len: 9,
+      ..EventNamingPolicy::DEFAULT
+    };
+    assert!(policy.validate("game.jump").is_ok());
+    assert!(policy.validate("game.jumps").is_err());
+    assert!(policy.validate("ab.cd.efg").is_ok());
+    assert!(policy.validate("ab.cd.efgh").is_err());
+
+    assert!(EventNamingPolicy::STRICT.validate("game.start").is_ok());
+    assert!(EventNamingPolicy::STRICT.validate("game.start_now").is_err());
+    assert!(EventNamingPolicy::STRICT.validate("ui.menu.open").is_err());
+    assert_eq!(validate_event_type_compile_time_with(&EventNamingPolicy::STRICT, "ui.click"), "ui.click");
   }
 
   #[test]
   fn queued_event_validation_uses_same_rules() {
-    let invalid = QueuedEvent::new(EventPayload::new("game.start_now", "info", None, 0));
-    let valid = QueuedEvent::new(EventPayload::new("game.start", "info", None, 0));
+    let invalid = QueuedEvent::new(EventPayload::new("game.start-now", IndigaugeLogLevel::Info, None, 0));
+    let valid = QueuedEvent::new(EventPayload::new("game.start_now", IndigaugeLogLevel::Info, None, 0));
 
     assert!(invalid.validate().is_err());
     assert!(valid.validate().is_ok());
@@ -0,0 +1,218 @@
+//! Per-target level directives for the log bridges, in the syntax of `tracing_subscriber::EnvFilter`.
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use indigauge_types::prelude::IndigaugeLogLevel;
+
+/// Environment variable read by [`LevelDirectives::from_env`].
+pub const LOG_ENV_VAR: &str = "INDIGAUGE_LOG";
+
+/// Error returned when a directive string cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectiveParseError(String);
+
+impl fmt::Display for DirectiveParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "invalid log directive: {}", self.0)
+  }
+}
+
+impl std::error::Error for DirectiveParseError {}
+
+/// Minimum levels per target, e.g. `my_game::ai=warn,my_game::net=debug,bevy_render=off`.
+///
+/// A bare level (`info`) sets the default for all targets, a bare target (`my_game`) enables all of its levels and
+/// `off` drops everything. The most specific target wins; a target matches itself and its `::` children.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LevelDirectives {
+  default: Option<IndigaugeLogLevel>,
+  targets: Vec<(String, IndigaugeLogLevel)>,
+}
+
+impl LevelDirectives {
+  /// Parses a comma-separated list of directives.
+  pub fn parse(spec: &str) -> Result<Self, DirectiveParseError> {
+    let mut directives = Self::default();
+
+    for directive in spec.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+      match directive.split_once('=') {
+        Some((target, level)) => {
+          let target = target.trim();
+          if target.is_empty() {
+            return Err(DirectiveParseError(directive.to_string()));
+          }
+          let level = parse_level(level).ok_or_else(|| DirectiveParseError(directive.to_string()))?;
+          directives.set_target(target, level);
+        },
+        None => match parse_level(directive) {
+          Some(level) => directives.default = Some(level),
+          None => directives.set_target(directive, IndigaugeLogLevel::Trace),
+        },
+      }
+    }
+
+    Ok(directives)
+  }
+
+  /// Directives that drop every event.
+  pub fn off() -> Self {
+    Self {
+      default: Some(IndigaugeLogLevel::Silent),
+      targets: Vec::new(),
+    }
+  }
+
+  /// Parses the directives in the `INDIGAUGE_LOG` environment variable, if it is set.
+  pub fn from_env() -> Option<Result<Self, DirectiveParseError>> {
+    std::env::var(LOG_ENV_VAR).ok().map(|spec| Self::parse(&spec))
+  }
+
+  /// Returns `true` if no directive is set.
+  pub fn is_empty(&self) -> bool {
+    self.default.is_none() && self.targets.is_empty()
+  }
+
+  /// Returns the minimum level for a target, or `None` if no directive applies.
+  pub fn level_for(&self, target: &str) -> Option<IndigaugeLogLevel> {
+    self
+      .targets
+      .iter()
+      .filter(|(prefix, _)| target_matches(prefix, target))
+      .max_by_key(|(prefix, _)| prefix.len())
+      .map(|(_, level)| *level)
+      .or(self.default)
+  }
+
+  /// Returns whether an event passes the directives, or `None` if no directive applies to its target.
+  pub fn enabled(&self, target: &str, level: IndigaugeLogLevel) -> Option<bool> {
+    self
+      .level_for(target)
+      .map(|min| min != IndigaugeLogLevel::Silent && level >= min)
+  }
+
+  fn set_target(&mut self, target: &str, level: IndigaugeLogLevel) {
+    self.targets.retain(|(prefix, _)| prefix != target);
+    self.targets.push((target.to_string(), level));
+  }
+}
+
+impl fmt::Display for LevelDirectives {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let level = |level: IndigaugeLogLevel| match level {
+      IndigaugeLogLevel::Silent => "off",
+      level => level.as_str(),
+    };
+    let directives = self
+      .default
+      .map(|default| level(default).to_string())
+      .into_iter()
+      .chain(
+        self
+          .targets
+          .iter()
+          .map(|(target, min)| format!("{target}={}", level(*min))),
+      )
+      .collect::<Vec<_>>();
+    f.write_str(&directives.join(","))
+  }
+}
+
+impl std::str::FromStr for LevelDirectives {
+  type Err = DirectiveParseError;
+
+  fn from_str(spec: &str) -> Result<Self, Self::Err> {
+    Self::parse(spec)
+  }
+}
+
+fn parse_level(level: &str) -> Option<IndigaugeLogLevel> {
+  match level.trim().to_ascii_lowercase().as_str() {
+    "off" => Some(IndigaugeLogLevel::Silent),
+    level => IndigaugeLogLevel::parse(level),
+  }
+}
+
+fn target_matches(prefix: &str, target: &str) -> bool {
+  target
+    .strip_prefix(prefix)
+    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+/// Shared, reloadable [`LevelDirectives`]. Clones refer to the same directives, so a handle kept by the game changes
+/// the filtering of the layer it was taken from.
+#[derive(Clone, Debug, Default)]
+pub struct FilterHandle(Arc<RwLock<LevelDirectives>>);
+
+impl FilterHandle {
+  /// Creates a handle with the given directives.
+  pub fn new(directives: LevelDirectives) -> Self {
+    Self(Arc::new(RwLock::new(directives)))
+  }
+
+  /// Replaces the directives.
+  pub fn reload(&self, directives: LevelDirectives) {
+    *self.0.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = directives;
+  }
+
+  /// Parses and replaces the directives. The current directives are kept if the spec is invalid.
+  pub fn reload_str(&self, spec: &str) -> Result<(), DirectiveParseError> {
+    self.reload(LevelDirectives::parse(spec)?);
+    Ok(())
+  }
+
+  /// Returns a copy of the current directives.
+  pub fn directives(&self) -> LevelDirectives {
+    self.0.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+  }
+
+  /// Returns whether an event passes the current directives, or `None` if no directive applies to its target.
+  pub fn enabled(&self, target: &str, level: IndigaugeLogLevel) -> Option<bool> {
+    self
+      .0
+      .read()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .enabled(target, level)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn most_specific_directive_wins() {
+    let directives = LevelDirectives::parse("info, my_game::ai=warn,my_game::net=debug,bevy_render=off").unwrap();
+
+    assert_eq!(directives.level_for("my_game::ai::planner"), Some(IndigaugeLogLevel::Warn));
+    assert_eq!(directives.level_for("my_game::net"), Some(IndigaugeLogLevel::Debug));
+    assert_eq!(directives.level_for("my_game::network"), Some(IndigaugeLogLevel::Info));
+    assert_eq!(directives.enabled("my_game::ai", IndigaugeLogLevel::Info), Some(false));
+    assert_eq!(directives.enabled("bevy_render::view", IndigaugeLogLevel::Error), Some(false));
+    assert_eq!(directives.to_string(), "info,my_game::ai=warn,my_game::net=debug,bevy_render=off");
+  }
+
+  #[test]
+  fn bare_targets_enable_all_levels() {
+    let directives = LevelDirectives::parse("my_game").unwrap();
+
+    assert_eq!(directives.enabled("my_game::ai", IndigaugeLogLevel::Trace), Some(true));
+    assert_eq!(directives.enabled("other", IndigaugeLogLevel::Error), None);
+    assert!(LevelDirectives::parse("").unwrap().is_empty());
+    assert_eq!(LevelDirectives::parse("off").unwrap(), LevelDirectives::off());
+    assert!(LevelDirectives::parse("my_game=loud").is_err());
+    assert!(LevelDirectives::parse("=info").is_err());
+  }
+
+  #[test]
+  fn handles_share_reloaded_directives() {
+    let handle = FilterHandle::default();
+    let layer_handle = handle.clone();
+
+    assert_eq!(layer_handle.enabled("my_game", IndigaugeLogLevel::Debug), None);
+    handle.reload_str("my_game=debug").unwrap();
+    assert_eq!(layer_handle.enabled("my_game", IndigaugeLogLevel::Debug), Some(true));
+    assert!(handle.reload_str("my_game=?").is_err());
+    assert_eq!(layer_handle.directives().to_string(), "my_game=debug");
+  }
+}
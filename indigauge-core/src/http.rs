@@ -2,8 +2,8 @@ use std::fmt::Debug;
 
 use bytes::Bytes;
 use indigauge_types::prelude::{
-  ApiResponse, BatchEventPayload, EventPayload, FeedbackPayload, IndigaugeConfig, IndigaugeLogLevel,
-  StartSessionPayload,
+  ApiResponse, BatchEventPayload, ErrorBody, EventPayload, FeedbackPayload, IdentifyPayload, IndigaugeConfig,
+  IndigaugeLogLevel, StartSessionPayload,
 };
 #[cfg(not(target_family = "wasm"))]
 use reqwest::blocking::{Client as BlockingClient, Request as BlockingRequest};
@@ -92,6 +92,35 @@ pub fn response_disposition_for_level(level: &IndigaugeLogLevel, status: StatusC
   }
 }
 
+/// Returns true if a failed request with this status may succeed when retried later.
+pub fn is_retryable_status(status: StatusCode) -> bool {
+  status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// Error codes returned by the ingest when a session token is no longer accepted.
+pub const SESSION_INVALIDATION_CODES: &[&str] = &[
+  "session_expired",
+  "session_ended",
+  "session_not_found",
+  "invalid_session",
+];
+
+/// Returns true if a response signals that the session token has expired or was invalidated server-side.
+///
+/// Only `401`/`403` responses whose [`ErrorBody::code`] is one of [`SESSION_INVALIDATION_CODES`] qualify, so a
+/// revoked public key or other authorization failure is not mistaken for an expired session.
+pub fn is_session_invalidated(status: StatusCode, body: &[u8]) -> bool {
+  if status != StatusCode::UNAUTHORIZED && status != StatusCode::FORBIDDEN {
+    return false;
+  }
+
+  decode_json_body::<ErrorBody>(body).is_ok_and(|error| {
+    SESSION_INVALIDATION_CODES
+      .iter()
+      .any(|code| error.code.eq_ignore_ascii_case(code))
+  })
+}
+
 /// Returns true if a transport error should be logged for the given log level.
 pub fn should_log_transport_error(level: &IndigaugeLogLevel) -> bool {
   level <= &IndigaugeLogLevel::Error
@@ -184,6 +213,11 @@ impl<'a> SdkHttpClient<'a> {
     self.json_request(Method::POST, "sessions/heartbeat", session_token, &json!({}))
   }
 
+  /// Builds a request linking the player of an active session to an account.
+  pub fn identify(&self, session_token: &str, payload: &IdentifyPayload<'_>) -> Result<Request, SdkBuildError> {
+    self.json_request(Method::POST, "sessions/identify", session_token, payload)
+  }
+
   /// Builds an event batch ingest request.
   pub fn event_batch(&self, session_token: &str, payload: &BatchEventPayload) -> Result<Request, SdkBuildError> {
     self.json_request(Method::POST, "events/batch", session_token, payload)
@@ -210,6 +244,19 @@ impl<'a> SdkHttpClient<'a> {
     self.json_request(Method::POST, "feedback", session_token, payload)
   }
 
+  /// Builds a request fetching the remote config document of the game for an active session.
+  pub fn remote_config(&self, session_token: &str) -> Result<Request, SdkBuildError> {
+    let request = self
+      .client
+      .get(self.config.api_url("config"))
+      .timeout(self.config.request_timeout())
+      .header("Accept", "application/json")
+      .header("X-Indigauge-Key", session_token)
+      .build()?;
+
+    Ok(request)
+  }
+
   /// Builds a screenshot upload request for an existing feedback record.
   pub fn feedback_screenshot(
     &self,
@@ -277,6 +324,11 @@ impl<'a> SdkBlockingHttpClient<'a> {
     self.json_request(Method::POST, "sessions/heartbeat", session_token, &json!({}))
   }
 
+  /// Builds a blocking request linking the player of an active session to an account.
+  pub fn identify(&self, session_token: &str, payload: &IdentifyPayload<'_>) -> Result<BlockingRequest, SdkBuildError> {
+    self.json_request(Method::POST, "sessions/identify", session_token, payload)
+  }
+
   /// Builds a blocking event batch ingest request.
   pub fn event_batch(
     &self,
@@ -311,6 +363,19 @@ impl<'a> SdkBlockingHttpClient<'a> {
     self.json_request(Method::POST, "feedback", session_token, payload)
   }
 
+  /// Builds a blocking request fetching the remote config document of the game for an active session.
+  pub fn remote_config(&self, session_token: &str) -> Result<BlockingRequest, SdkBuildError> {
+    let request = self
+      .client
+      .get(self.config.api_url("config"))
+      .timeout(self.config.request_timeout())
+      .header("Accept", "application/json")
+      .header("X-Indigauge-Key", session_token)
+      .build()?;
+
+    Ok(request)
+  }
+
   /// Builds a blocking screenshot upload request for an existing feedback record.
   pub fn feedback_screenshot(
     &self,
@@ -351,26 +416,124 @@ pub fn send_request_blocking(client: &BlockingClient, request: BlockingRequest)
   Ok(SdkResponse { body, status, headers })
 }
 
+/// Name of the file holding the player id in the [game data folder](crate::utils::game_data_dir).
+pub const PLAYER_ID_FILE: &str = "player_id.txt";
+
+/// Returns `true` if `player_id` is a usable id: non-empty, at most 128 printable ASCII characters.
+pub fn is_valid_player_id(player_id: &str) -> bool {
+  !player_id.is_empty() && player_id.len() <= 128 && player_id.chars().all(|c| c.is_ascii_graphic())
+}
+
 #[cfg(not(target_family = "wasm"))]
-/// Loads or creates a stable per-player id in platform preference storage.
+/// Loads or creates a stable per-player id in the [game data folder](crate::utils::game_data_dir).
+///
+/// Surrounding whitespace in the id file is ignored, and a missing or corrupt file is replaced with a new id.
 pub fn get_or_init_player_id(game_name: &str) -> String {
-  use std::fs;
-  use uuid::Uuid;
+  let Some(game_folder_path) = crate::utils::game_data_dir(game_name) else {
+    return uuid::Uuid::new_v4().to_string();
+  };
 
-  let game_folder_path = dirs::preference_dir().map(|dir| dir.join(game_name));
+  if let Ok(player_id) = std::fs::read_to_string(game_folder_path.join(PLAYER_ID_FILE))
+    && is_valid_player_id(player_id.trim())
+  {
+    return player_id.trim().to_string();
+  }
 
-  if let Some(game_folder_path) = game_folder_path {
-    let player_id_file_path = game_folder_path.join("player_id.txt");
+  let new_player_id = uuid::Uuid::new_v4().to_string();
+  let _ = write_player_id(&game_folder_path, &new_player_id);
+  new_player_id
+}
+
+/// Replaces the stored player id with a new one, e.g. when switching accounts, and returns it.
+///
+/// If the player did not consent to a persistent id, the stored id is deleted instead and `None` is returned.
+pub fn reset_player_id(game_name: &str) -> Option<String> {
+  if !crate::consent::effective_consent().allows_player_id() {
+    let _ = delete_player_id(game_name);
+    return None;
+  }
 
-    if let Ok(player_id) = fs::read_to_string(&player_id_file_path) {
-      player_id
-    } else {
-      let new_player_id = Uuid::new_v4().to_string();
-      let _ = fs::create_dir_all(&game_folder_path);
-      let _ = fs::write(&player_id_file_path, &new_player_id);
-      new_player_id
+  #[cfg(not(target_family = "wasm"))]
+  {
+    let new_player_id = uuid::Uuid::new_v4().to_string();
+    if let Some(game_folder_path) = crate::utils::game_data_dir(game_name) {
+      let _ = write_player_id(&game_folder_path, &new_player_id);
     }
-  } else {
-    Uuid::new_v4().to_string()
+    Some(new_player_id)
+  }
+
+  #[cfg(target_family = "wasm")]
+  {
+    let new_player_id = crate::web::random_uuid();
+    let _ = crate::web::write_item(game_name, PLAYER_ID_FILE, &new_player_id);
+    Some(new_player_id)
+  }
+}
+
+#[cfg(target_family = "wasm")]
+/// Loads or creates a stable per-player id in `localStorage`, replacing a missing or corrupt id with a new one.
+pub fn get_or_init_player_id(game_name: &str) -> String {
+  if let Some(player_id) = crate::web::read_item(game_name, PLAYER_ID_FILE)
+    && is_valid_player_id(player_id.trim())
+  {
+    return player_id.trim().to_string();
+  }
+
+  let new_player_id = crate::web::random_uuid();
+  let _ = crate::web::write_item(game_name, PLAYER_ID_FILE, &new_player_id);
+  new_player_id
+}
+
+#[cfg(not(target_family = "wasm"))]
+/// Deletes the stored player id, e.g. when the player revokes consent. A missing id is not an error.
+pub fn delete_player_id(game_name: &str) -> std::io::Result<()> {
+  let Some(game_folder_path) = crate::utils::game_data_dir(game_name) else {
+    return Ok(());
+  };
+  match std::fs::remove_file(game_folder_path.join(PLAYER_ID_FILE)) {
+    Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error),
+    _ => Ok(()),
+  }
+}
+
+#[cfg(target_family = "wasm")]
+/// Deletes the stored player id, e.g. when the player revokes consent.
+pub fn delete_player_id(game_name: &str) -> std::io::Result<()> {
+  crate::web::remove_item(game_name, PLAYER_ID_FILE);
+  Ok(())
+}
+
+#[cfg(not(target_family = "wasm"))]
+/// Writes the id through a temporary file so an interrupted write cannot leave a truncated id behind.
+fn write_player_id(game_folder_path: &std::path::Path, player_id: &str) -> std::io::Result<()> {
+  std::fs::create_dir_all(game_folder_path)?;
+  let tmp_path = game_folder_path.join(format!("{PLAYER_ID_FILE}.tmp"));
+  std::fs::write(&tmp_path, player_id)?;
+  std::fs::rename(tmp_path, game_folder_path.join(PLAYER_ID_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn detects_session_invalidation_by_error_code() {
+    let expired = br#"{"code":"SESSION_EXPIRED","message":"Session expired"}"#;
+    let revoked_key = br#"{"code":"invalid_key","message":"Public key revoked"}"#;
+
+    assert!(is_session_invalidated(StatusCode::UNAUTHORIZED, expired));
+    assert!(is_session_invalidated(StatusCode::FORBIDDEN, expired));
+    assert!(!is_session_invalidated(StatusCode::UNAUTHORIZED, revoked_key));
+    assert!(!is_session_invalidated(StatusCode::BAD_REQUEST, expired));
+    assert!(!is_session_invalidated(StatusCode::UNAUTHORIZED, b"not json"));
+  }
+
+  #[test]
+  fn rejects_corrupt_player_ids() {
+    assert!(is_valid_player_id("3f2b8c1e-5d4a-4c1b-9e7f-2a6d8b0c4e1f"));
+    assert!(!is_valid_player_id(""));
+    assert!(!is_valid_player_id("player id"));
+    assert!(!is_valid_player_id("\0\0\0"));
+    assert!(!is_valid_player_id(&"a".repeat(129)));
   }
 }
@@ -1,41 +1,93 @@
 #![doc = include_str!("../README.md")]
 
+pub mod consent;
+pub mod console;
 pub mod event;
+pub mod filter;
 pub mod hardware;
 pub mod http;
+pub mod ndjson;
+pub mod redact;
+pub mod remote_config;
 pub mod runtime;
+pub mod schema;
+pub mod sink;
 pub mod state;
 pub mod types {
   pub use indigauge_types::prelude::*;
 }
 pub mod utils;
+#[cfg(target_family = "wasm")]
+pub mod web;
 
 #[cfg(feature = "panic_handler")]
 pub mod panic;
 
+#[cfg(feature = "log")]
+pub mod log;
+
+#[cfg(feature = "otlp")]
+pub mod otlp;
+
 #[cfg(feature = "tracing")]
 pub mod tracing;
 
+#[cfg(all(feature = "test-util", not(target_family = "wasm")))]
+pub mod test_util;
+
 pub mod prelude {
-  pub use crate::event::{QueuedEvent, validate_event_type, validate_event_type_compile_time};
+  pub use crate::consent::{
+    effective_consent, get_consent, load_consent, set_consent, set_consent_required, store_consent,
+  };
+  pub use crate::console::{ConsolePrinter, ConsoleVerbosity};
+  pub use crate::event::{
+    EventNamingPolicy, IndigaugeEvent, QueuedEvent, event_naming_policy, set_event_naming_policy, validate_event_type,
+    validate_event_type_compile_time, validate_event_type_compile_time_with,
+  };
+  pub use crate::filter::{DirectiveParseError, FilterHandle, LevelDirectives};
   pub use crate::hardware::{bucket_cores, bucket_ram_gb, coarsen_cpu_name};
   pub use crate::http::{
     ResponseDisposition, SdkBuildError, SdkHttpClient, SdkResponse, classify_status, decode_api_response,
-    decode_json_body, decode_utf8_body, response_disposition_for_level, send_request, should_log_transport_error,
+    decode_json_body, decode_utf8_body, delete_player_id, get_or_init_player_id, is_retryable_status,
+    is_session_invalidated, is_valid_player_id, reset_player_id, response_disposition_for_level, send_request,
+    should_log_transport_error,
   };
   #[cfg(not(target_family = "wasm"))]
   pub use crate::http::{SdkBlockingHttpClient, send_request_blocking};
   #[cfg(not(target_family = "wasm"))]
-  pub use crate::runtime::IndigaugeBlockingRuntimeClient;
+  pub use crate::ndjson::NdjsonWriter;
+  pub use crate::ndjson::{NdjsonConfig, NdjsonRecordKind};
+  pub use crate::redact::{Redactor, clear_redactor, redact_metadata, redact_text, set_redactor};
+  pub use crate::remote_config::{
+    FLAG_EXPOSURE_EVENT, REMOTE_CONFIG_FILE, RemoteConfig, load_remote_config, store_remote_config,
+  };
   pub use crate::runtime::IndigaugeRuntimeClient;
-  pub use crate::state::{clear_pending_event_count, drain_pending_events, track_pending_event};
+  #[cfg(not(target_family = "wasm"))]
+  pub use crate::runtime::{FinalFlushReport, IndigaugeBlockingRuntimeClient};
+  pub use crate::schema::{MetadataSchema, SchemaError, SchemaRegistry, SchemaViolation};
+  pub use crate::sink::{EventSink, EventSinks, SinkOptions};
+  pub use crate::state::{
+    SessionHandle, clear_pending_event_count, drain_pending_events, dropped_event_count, get_session_start,
+    get_session_token, min_event_level, register_session, reset_session, set_min_event_level, set_session_token,
+    track_pending_event, unregister_session,
+  };
   pub use crate::types::*;
-  pub use crate::utils::select;
-  pub use crate::{enqueue_ig_event, ig_debug, ig_error, ig_event, ig_info, ig_trace, ig_warn};
+  #[cfg(not(target_family = "wasm"))]
+  pub use crate::utils::game_data_dir;
+  pub use crate::utils::{Instant, select};
+  pub use crate::{
+    enqueue_ig_event, enqueue_ig_event_for, ig_debug, ig_debug_for, ig_emit, ig_emit_for, ig_error, ig_error_for,
+    ig_event, ig_event_for, ig_info, ig_info_for, ig_level, ig_trace, ig_trace_for, ig_warn, ig_warn_for,
+  };
 
   #[cfg(feature = "panic_handler")]
-  pub use crate::panic::{panic_handler, panic_handler_with_config};
+  pub use crate::panic::{panic_handler, panic_handler_for_current_session, panic_handler_with_config};
 
+  pub use crate::event::IndigaugeSink;
+  #[cfg(feature = "log")]
+  pub use crate::log::IndigaugeLogger;
+  #[cfg(feature = "otlp")]
+  pub use crate::otlp::{OtlpConfig, OtlpExporter, OtlpMode, OtlpResource};
   #[cfg(feature = "tracing")]
-  pub use crate::tracing::{IndigaugeLayer, IndigaugeSink};
+  pub use crate::tracing::IndigaugeLayer;
 }
@@ -0,0 +1,379 @@
+//! Bridge for the `log` facade, for games, tools and libraries that do not use `tracing`.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use indigauge_types::prelude::IndigaugeLogLevel;
+use log::kv::{self, Key, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use serde_json::{Value, json};
+
+use crate::event::{EVENT_TYPE_FIELDS, IndigaugeSink, event_naming_policy};
+use crate::filter::{FilterHandle, LOG_ENV_VAR, LevelDirectives};
+use crate::state::enqueue;
+
+/// `log::Log` implementation that forwards records to an [`IndigaugeSink`].
+///
+/// Records are filtered, mapped and named like the tracing layer: module filters and per-target directives matched
+/// against the record target, or the forwarded levels, and an `ig`/`event_type` key-value as event type
+/// (`log.<level>` otherwise). Other key-values
+/// and the message become metadata.
+pub struct IndigaugeLogger {
+  filters: Vec<String>,
+  levels: Vec<IndigaugeLogLevel>,
+  event_type_required: bool,
+  directives: FilterHandle,
+  sink: Arc<dyn IndigaugeSink>,
+  next: Option<Box<dyn Log>>,
+}
+
+impl IndigaugeLogger {
+  /// Creates a logger that queues events with [`enqueue`], with default filters and log levels.
+  pub fn new() -> Self {
+    Self::new_with_sink(Arc::new(EnqueueSink))
+  }
+
+  /// Creates a logger with default filters and log levels.
+  pub fn new_with_sink(sink: Arc<dyn IndigaugeSink>) -> Self {
+    Self {
+      filters: vec!["indigauge".to_string()],
+      levels: vec![
+        IndigaugeLogLevel::Info,
+        IndigaugeLogLevel::Warn,
+        IndigaugeLogLevel::Error,
+      ],
+      event_type_required: false,
+      directives: FilterHandle::default(),
+      sink,
+      next: None,
+    }
+  }
+
+  /// Creates a logger with the filters, levels, directives and sink of a tracing layer.
+  ///
+  /// The directives are shared, so the layer's [`FilterHandle`] changes both.
+  #[cfg(feature = "tracing")]
+  pub fn from_layer(layer: &crate::tracing::IndigaugeLayer) -> Self {
+    Self {
+      filters: layer.filters.clone(),
+      levels: layer.levels.clone(),
+      event_type_required: layer.event_type_required,
+      directives: layer.directives.clone(),
+      sink: layer.sink.clone(),
+      next: None,
+    }
+  }
+
+  /// Requires an explicit `ig`/`event_type` key-value to forward records.
+  pub fn with_event_type_required(mut self, event_type_required: bool) -> Self {
+    self.event_type_required = event_type_required;
+    self
+  }
+
+  /// Adds module prefix filters to exclude records from specific modules.
+  pub fn with_filters<T>(mut self, filters: Vec<T>) -> Self
+  where
+    T: Into<String>,
+  {
+    filters.into_iter().for_each(|filter| self.filters.push(filter.into()));
+    self
+  }
+
+  /// Overrides which log levels are forwarded.
+  pub fn with_levels(mut self, levels: Vec<IndigaugeLogLevel>) -> Self {
+    self.levels = levels;
+    self
+  }
+
+  /// Sets per-target minimum levels, see [`IndigaugeLayer::with_directives`].
+  ///
+  /// [`IndigaugeLayer::with_directives`]: crate::tracing::IndigaugeLayer::with_directives
+  pub fn with_directives(self, directives: LevelDirectives) -> Self {
+    self.directives.reload(directives);
+    self
+  }
+
+  /// Applies the directives of the `INDIGAUGE_LOG` environment variable, if it is set.
+  ///
+  /// Invalid directives are ignored with a warning on stderr, since the logger is not installed yet.
+  pub fn with_env_directives(self) -> Self {
+    match LevelDirectives::from_env() {
+      Some(Ok(directives)) => self.with_directives(directives),
+      Some(Err(error)) => {
+        eprintln!("Ignoring {LOG_ENV_VAR}: {error}");
+        self
+      },
+      None => self,
+    }
+  }
+
+  /// Returns a handle that changes the directives of this logger at runtime.
+  pub fn filter_handle(&self) -> FilterHandle {
+    self.directives.clone()
+  }
+
+  /// Also passes every record to an existing logger, e.g. `env_logger`, which keeps its own filtering.
+  pub fn chain(mut self, next: impl Log + 'static) -> Self {
+    self.next = Some(Box::new(next));
+    self
+  }
+
+  /// Installs the logger as the global `log` logger and lets all levels through to its filters.
+  pub fn init(self) -> Result<(), SetLoggerError> {
+    log::set_boxed_logger(Box::new(self))?;
+    log::set_max_level(LevelFilter::Trace);
+    Ok(())
+  }
+
+  /// Filters by target, which defaults to the module path: `enabled` only sees the metadata of a record, so
+  /// `log` and `enabled` must use the same key.
+  fn is_forwarded(&self, metadata: &Metadata<'_>) -> bool {
+    let target = metadata.target();
+    if self.filters.iter().any(|filter| target.starts_with(filter)) {
+      return false;
+    }
+
+    let level = level_to_log_level(metadata.level());
+    self
+      .directives
+      .enabled(target, level)
+      .unwrap_or_else(|| self.levels.contains(&level))
+  }
+
+  fn forward(&self, record: &Record<'_>) {
+    let module = record.module_path_static().unwrap_or_default();
+    if !self.is_forwarded(record.metadata()) {
+      return;
+    }
+
+    let mut visitor = KeyValueVisitor::default();
+    let _ = record.key_values().visit(&mut visitor);
+
+    if self.event_type_required && visitor.event_type.is_none() {
+      return;
+    }
+
+    let level = level_to_log_level(record.level());
+    let message = record.args().to_string();
+    if !message.is_empty() {
+      visitor.fields.insert("message".to_string(), Value::String(message));
+    }
+
+    let event_type = visitor.event_type.unwrap_or_else(|| format!("log.{}", level.as_str()));
+    let event_metadata =
+      (!visitor.fields.is_empty()).then(|| serde_json::to_value(&visitor.fields).unwrap_or_default());
+    let file = record.file_static().unwrap_or("unknown file");
+    let line = record.line().unwrap_or_default();
+
+    self.sink.log(level, &event_type, event_metadata, file, line, module);
+  }
+}
+
+impl Default for IndigaugeLogger {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Log for IndigaugeLogger {
+  fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+    self.next.as_ref().is_some_and(|next| next.enabled(metadata)) || self.is_forwarded(metadata)
+  }
+
+  fn log(&self, record: &Record<'_>) {
+    self.forward(record);
+    if let Some(next) = &self.next
+      && next.enabled(record.metadata())
+    {
+      next.log(record);
+    }
+  }
+
+  fn flush(&self) {
+    if let Some(next) = &self.next {
+      next.flush();
+    }
+  }
+}
+
+/// Sink that queues events for the default session with [`enqueue`].
+struct EnqueueSink;
+
+impl IndigaugeSink for EnqueueSink {
+  fn log(
+    &self,
+    level: IndigaugeLogLevel,
+    event_type: &str,
+    metadata: Option<Value>,
+    file: &'static str,
+    line: u32,
+    module: &'static str,
+  ) {
+    enqueue(level, event_type, metadata, file, line, module);
+  }
+}
+
+fn level_to_log_level(level: Level) -> IndigaugeLogLevel {
+  match level {
+    Level::Error => IndigaugeLogLevel::Error,
+    Level::Warn => IndigaugeLogLevel::Warn,
+    Level::Info => IndigaugeLogLevel::Info,
+    Level::Debug => IndigaugeLogLevel::Debug,
+    Level::Trace => IndigaugeLogLevel::Trace,
+  }
+}
+
+#[derive(Default, Debug)]
+struct KeyValueVisitor {
+  event_type: Option<String>,
+  fields: BTreeMap<String, Value>,
+}
+
+impl<'kvs> VisitSource<'kvs> for KeyValueVisitor {
+  fn visit_pair(&mut self, key: Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+    let name = key.as_str();
+
+    if self.event_type.is_none() && EVENT_TYPE_FIELDS.contains(&name) {
+      let value = value.to_string();
+      if event_naming_policy().validate(&value).is_ok() {
+        self.event_type = Some(value);
+      }
+      return Ok(());
+    }
+
+    let value = if let Some(value) = value.to_bool() {
+      Value::Bool(value)
+    } else if let Some(value) = value.to_u64() {
+      json!(value)
+    } else if let Some(value) = value.to_i64() {
+      json!(value)
+    } else if let Some(value) = value.to_f64() {
+      json!(value)
+    } else {
+      Value::String(value.to_string())
+    };
+    self.fields.insert(name.to_string(), value);
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Mutex;
+
+  use super::*;
+
+  type Logged = (IndigaugeLogLevel, String, Option<Value>);
+
+  const NO_KVS: &[(&str, &str)] = &[];
+
+  #[derive(Default)]
+  struct TestSink {
+    events: Mutex<Vec<Logged>>,
+  }
+
+  impl IndigaugeSink for TestSink {
+    fn log(
+      &self,
+      level: IndigaugeLogLevel,
+      event_type: &str,
+      metadata: Option<Value>,
+      _: &'static str,
+      _: u32,
+      _: &'static str,
+    ) {
+      self
+        .events
+        .lock()
+        .unwrap()
+        .push((level, event_type.to_string(), metadata));
+    }
+  }
+
+  #[derive(Default)]
+  struct CountingLogger(Mutex<usize>);
+
+  impl Log for &'static CountingLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+      metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, _: &Record<'_>) {
+      *self.0.lock().unwrap() += 1;
+    }
+
+    fn flush(&self) {}
+  }
+
+  fn record(
+    logger: &IndigaugeLogger,
+    level: Level,
+    target: &str,
+    message: std::fmt::Arguments<'_>,
+    kvs: &dyn kv::Source,
+  ) {
+    logger.log(
+      &Record::builder()
+        .level(level)
+        .target(target)
+        .module_path_static(Some("my_game::ai"))
+        .file_static(Some("ai.rs"))
+        .line(Some(12))
+        .key_values(kvs)
+        .args(message)
+        .build(),
+    );
+  }
+
+  #[test]
+  fn records_are_forwarded_with_event_type_and_key_values() {
+    let sink = Arc::new(TestSink::default());
+    let logger = IndigaugeLogger::new_with_sink(sink.clone());
+
+    let kvs: &[(&str, kv::Value<'_>)] = &[("ig", kv::Value::from("ai.stuck")), ("agent", kv::Value::from(7u64))];
+    record(&logger, Level::Warn, "my_game::ai", format_args!("agent stuck"), &kvs);
+    record(&logger, Level::Debug, "my_game::ai", format_args!("planning"), &NO_KVS);
+    record(&logger, Level::Error, "my_game::ai", format_args!("no path"), &[("ig", "bad event")]);
+
+    let events = sink.events.lock().unwrap();
+    assert_eq!(
+      *events,
+      [
+        (IndigaugeLogLevel::Warn, "ai.stuck".to_string(), Some(json!({ "agent": 7, "message": "agent stuck" }))),
+        (IndigaugeLogLevel::Error, "log.error".to_string(), Some(json!({ "message": "no path" }))),
+      ]
+    );
+  }
+
+  #[test]
+  fn directives_and_chained_logger_apply() {
+    static NEXT: CountingLogger = CountingLogger(Mutex::new(0));
+    let sink = Arc::new(TestSink::default());
+    let logger = IndigaugeLogger::new_with_sink(sink.clone())
+      .with_directives(LevelDirectives::parse("my_game::net=debug,my_game::ai=off").unwrap())
+      .chain(&NEXT);
+
+    record(&logger, Level::Debug, "my_game::net", format_args!("packet"), &NO_KVS);
+    record(&logger, Level::Error, "my_game::ai", format_args!("ignored"), &NO_KVS);
+    record(&logger, Level::Warn, "other", format_args!("forwarded"), &NO_KVS);
+
+    assert!(logger.enabled(&Metadata::builder().level(Level::Debug).target("my_game::net").build()));
+    assert!(!logger.enabled(&Metadata::builder().level(Level::Info).target("my_game::ai").build()));
+    assert_eq!(sink.events.lock().unwrap().len(), 2);
+    assert_eq!(*NEXT.0.lock().unwrap(), 2);
+  }
+
+  #[test]
+  fn module_filters_match_the_target_in_enabled_and_log() {
+    let sink = Arc::new(TestSink::default());
+    let logger = IndigaugeLogger::new_with_sink(sink.clone()).with_filters(vec!["my_game::ai"]);
+
+    assert!(!logger.enabled(&Metadata::builder().level(Level::Warn).target("my_game::ai").build()));
+    assert!(logger.enabled(&Metadata::builder().level(Level::Warn).target("replay").build()));
+    record(&logger, Level::Warn, "my_game::ai", format_args!("filtered"), &NO_KVS);
+    // The test records come from the `my_game::ai` module, but only the target is matched.
+    record(&logger, Level::Warn, "replay", format_args!("forwarded"), &NO_KVS);
+
+    let events = sink.events.lock().unwrap();
+    assert_eq!(*events, [(IndigaugeLogLevel::Warn, "log.warn".to_string(), Some(json!({ "message": "forwarded" })))]);
+  }
+}
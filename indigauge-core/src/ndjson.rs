@@ -0,0 +1,261 @@
+//! Newline-delimited JSON file sink, to inspect payloads in Dev mode and analyse playtests offline.
+
+#[cfg(not(target_family = "wasm"))]
+use std::fs::{self, File, OpenOptions};
+#[cfg(not(target_family = "wasm"))]
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+#[cfg(not(target_family = "wasm"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_family = "wasm"))]
+use crate::state::SessionHandle;
+
+/// Default size after which the file is rotated.
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Default number of files kept, including the current one.
+pub const DEFAULT_MAX_FILES: usize = 5;
+
+/// What an [`NdjsonRecord`] describes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NdjsonRecordKind {
+  /// A session started, with its start payload.
+  SessionStart,
+  /// A session ended.
+  SessionEnd,
+  /// An event payload.
+  Event,
+  /// The session metadata was updated.
+  Metadata,
+  /// Feedback was submitted.
+  Feedback,
+}
+
+/// One line of an NDJSON file.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NdjsonRecord<'a, T> {
+  pub kind: NdjsonRecordKind,
+  /// Milliseconds since the unix epoch.
+  pub timestamp_ms: u128,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub session: Option<u32>,
+  pub data: &'a T,
+}
+
+/// File location and rotation of an [`NdjsonWriter`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NdjsonConfig {
+  path: PathBuf,
+  max_file_bytes: u64,
+  max_files: usize,
+}
+
+impl NdjsonConfig {
+  /// Writes to `path`, rotating it to `path.1`, `path.2`, ... once it exceeds [`DEFAULT_MAX_FILE_BYTES`].
+  pub fn new(path: impl Into<PathBuf>) -> Self {
+    Self {
+      path: path.into(),
+      max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+      max_files: DEFAULT_MAX_FILES,
+    }
+  }
+
+  /// Sets the size after which the file is rotated.
+  pub fn with_max_file_bytes(mut self, max_file_bytes: u64) -> Self {
+    self.max_file_bytes = max_file_bytes;
+    self
+  }
+
+  /// Sets the number of files kept, including the current one. The oldest file is deleted on rotation.
+  pub fn with_max_files(mut self, max_files: usize) -> Self {
+    self.max_files = max_files.max(1);
+    self
+  }
+
+  /// Returns the path of the current file.
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  #[cfg(not(target_family = "wasm"))]
+  fn rotated_path(&self, index: usize) -> PathBuf {
+    let mut path = self.path.clone().into_os_string();
+    path.push(format!(".{index}"));
+    path.into()
+  }
+}
+
+/// Appends [`NdjsonRecord`]s to a file, one JSON object per line, e.g. for `jq` or `pandas.read_json(lines=True)`.
+///
+/// Not available on the web, where there is no file system.
+#[cfg(not(target_family = "wasm"))]
+#[derive(Debug)]
+pub struct NdjsonWriter {
+  config: NdjsonConfig,
+  file: Option<File>,
+  written: u64,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl NdjsonWriter {
+  /// Creates a writer. The file is opened, and its parent directory created, on the first write.
+  pub fn new(config: NdjsonConfig) -> Self {
+    Self {
+      config,
+      file: None,
+      written: 0,
+    }
+  }
+
+  /// Returns the configuration of the writer.
+  pub fn config(&self) -> &NdjsonConfig {
+    &self.config
+  }
+
+  /// Appends a record, rotating the file first if the record would exceed the maximum file size.
+  pub fn write<T: Serialize>(
+    &mut self,
+    kind: NdjsonRecordKind,
+    session: Option<SessionHandle>,
+    data: &T,
+  ) -> io::Result<()> {
+    let record = NdjsonRecord {
+      kind,
+      timestamp_ms: SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default(),
+      session: session.map(SessionHandle::id),
+      data,
+    };
+    let mut line = serde_json::to_vec(&record)?;
+    line.push(b'\n');
+
+    if self.file.is_none() {
+      self.open()?;
+    }
+    if self.written > 0 && self.written + line.len() as u64 > self.config.max_file_bytes {
+      self.rotate()?;
+    }
+
+    let Some(file) = self.file.as_mut() else {
+      return Ok(());
+    };
+    file.write_all(&line)?;
+    self.written += line.len() as u64;
+    Ok(())
+  }
+
+  /// Flushes the current file.
+  pub fn flush(&mut self) -> io::Result<()> {
+    self.file.as_mut().map_or(Ok(()), Write::flush)
+  }
+
+  /// Deletes the current and the rotated files, e.g. when the player revokes consent. The next write starts a new
+  /// file.
+  pub fn clear(&mut self) -> io::Result<()> {
+    self.file = None;
+    self.written = 0;
+
+    for path in std::iter::once(self.config.path.clone())
+      .chain((1..self.config.max_files).map(|index| self.config.rotated_path(index)))
+    {
+      match fs::remove_file(path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+        _ => {},
+      }
+    }
+    Ok(())
+  }
+
+  fn open(&mut self) -> io::Result<()> {
+    if let Some(parent) = self
+      .config
+      .path
+      .parent()
+      .filter(|parent| !parent.as_os_str().is_empty())
+    {
+      fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new().create(true).append(true).open(&self.config.path)?;
+    self.written = file.metadata()?.len();
+    self.file = Some(file);
+    Ok(())
+  }
+
+  fn rotate(&mut self) -> io::Result<()> {
+    self.file = None;
+
+    let max_files = self.config.max_files;
+    if max_files > 1 {
+      let _ = fs::remove_file(self.config.rotated_path(max_files - 1));
+      for index in (1..max_files - 1).rev() {
+        let from = self.config.rotated_path(index);
+        if from.exists() {
+          fs::rename(from, self.config.rotated_path(index + 1))?;
+        }
+      }
+      fs::rename(&self.config.path, self.config.rotated_path(1))?;
+    } else {
+      fs::remove_file(&self.config.path)?;
+    }
+
+    self.open()
+  }
+}
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+  use serde_json::{Value, json};
+
+  use super::*;
+
+  fn read_lines(path: &Path) -> Vec<Value> {
+    fs::read_to_string(path)
+      .unwrap()
+      .lines()
+      .map(|line| serde_json::from_str(line).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn records_are_appended_and_rotated() {
+    let dir = std::env::temp_dir().join(format!("indigauge-ndjson-{}", uuid::Uuid::new_v4()));
+    let config = NdjsonConfig::new(dir.join("events.ndjson"))
+      .with_max_file_bytes(200)
+      .with_max_files(2);
+    let mut writer = NdjsonWriter::new(config.clone());
+
+    for index in 0..6 {
+      writer
+        .write(NdjsonRecordKind::Event, Some(SessionHandle::DEFAULT), &json!({ "index": index, "pad": "x".repeat(40) }))
+        .unwrap();
+    }
+    writer.flush().unwrap();
+
+    let current = read_lines(config.path());
+    let rotated = read_lines(&config.rotated_path(1));
+    assert!(!config.rotated_path(2).exists());
+    assert_eq!(current.last().unwrap()["data"]["index"], 5);
+    assert_eq!(current[0]["kind"], "event");
+    assert_eq!(current[0]["session"], 0);
+    assert!(current[0]["timestampMs"].is_u64());
+    assert_eq!(rotated.last().unwrap()["data"]["index"], current[0]["data"]["index"].as_u64().unwrap() - 1);
+
+    writer.clear().unwrap();
+    assert!(!config.path().exists());
+    assert!(!config.rotated_path(1).exists());
+    writer
+      .write(NdjsonRecordKind::SessionEnd, None, &json!({ "reason": "ended" }))
+      .unwrap();
+    assert_eq!(read_lines(config.path()).len(), 1);
+
+    let _ = fs::remove_dir_all(dir);
+  }
+}
@@ -0,0 +1,396 @@
+//! Export of events as OpenTelemetry log records over OTLP/HTTP JSON, e.g. to a collector that already receives
+//! server telemetry.
+
+use std::time::Duration;
+#[cfg(not(target_family = "wasm"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use indigauge_types::prelude::{EventPayload, IndigaugeLogLevel};
+#[cfg(not(target_family = "wasm"))]
+use reqwest::blocking::{Client as BlockingClient, Request as BlockingRequest};
+use reqwest::{Client, Request};
+use serde_json::{Value, json};
+#[cfg(target_family = "wasm")]
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::http::SdkBuildError;
+use crate::state::{SessionHandle, get_session_start};
+
+/// Logs endpoint of a collector running locally with the default OTLP/HTTP port.
+pub const DEFAULT_OTLP_ENDPOINT: &str = "http://localhost:4318/v1/logs";
+
+/// Name of the instrumentation scope of exported log records.
+const SCOPE_NAME: &str = "indigauge";
+
+/// Whether OTLP export complements or replaces sending event batches to Indigauge.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OtlpMode {
+  /// Events are sent to Indigauge and exported.
+  #[default]
+  FanOut,
+  /// Events are only exported. Sessions, metadata and feedback are still sent to Indigauge.
+  Replace,
+}
+
+/// Endpoint, headers and mode of an [`OtlpExporter`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OtlpConfig {
+  endpoint: String,
+  headers: Vec<(String, String)>,
+  mode: OtlpMode,
+  timeout: Duration,
+}
+
+impl OtlpConfig {
+  /// Exports to the full logs URL of a collector, e.g. [`DEFAULT_OTLP_ENDPOINT`].
+  pub fn new(endpoint: impl Into<String>) -> Self {
+    Self {
+      endpoint: endpoint.into(),
+      headers: Vec::new(),
+      mode: OtlpMode::default(),
+      timeout: Duration::from_secs(10),
+    }
+  }
+
+  /// Adds a header to every export request, e.g. for collector authentication.
+  pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+    self.headers.push((name.into(), value.into()));
+    self
+  }
+
+  /// Sets whether events are also sent to Indigauge.
+  pub fn with_mode(mut self, mode: OtlpMode) -> Self {
+    self.mode = mode;
+    self
+  }
+
+  /// Sets the timeout of export requests.
+  pub fn with_timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = timeout;
+    self
+  }
+
+  /// Returns the logs URL.
+  pub fn endpoint(&self) -> &str {
+    &self.endpoint
+  }
+
+  /// Returns the additional request headers.
+  pub fn headers(&self) -> &[(String, String)] {
+    &self.headers
+  }
+
+  /// Returns whether events are also sent to Indigauge.
+  pub fn mode(&self) -> OtlpMode {
+    self.mode
+  }
+
+  /// Returns the timeout of export requests.
+  pub fn timeout(&self) -> Duration {
+    self.timeout
+  }
+}
+
+/// Resource of exported log records: the game, the session and the player.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OtlpResource {
+  /// Exported as `service.name`.
+  pub service_name: String,
+  /// Exported as `service.version`.
+  pub service_version: String,
+  /// Exported as `session.id`.
+  pub session_id: Option<String>,
+  /// Exported as `user.id`.
+  pub player_id: Option<String>,
+  /// Wall-clock start of the session in nanoseconds since the unix epoch. Log record times are offset from it by
+  /// the elapsed time of the event; without it, only the observed time is set.
+  pub start_time_unix_nano: Option<u128>,
+}
+
+impl OtlpResource {
+  /// Creates a resource for a game, see [`IndigaugeConfig`](crate::types::IndigaugeConfig).
+  pub fn new(service_name: impl Into<String>, service_version: impl Into<String>) -> Self {
+    Self {
+      service_name: service_name.into(),
+      service_version: service_version.into(),
+      ..Default::default()
+    }
+  }
+
+  /// Sets the session id.
+  pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+    self.session_id = Some(session_id.into());
+    self
+  }
+
+  /// Sets the player id.
+  pub fn with_player_id(mut self, player_id: Option<String>) -> Self {
+    self.player_id = player_id;
+    self
+  }
+
+  /// Sets the start time from the recorded start of a session.
+  pub fn with_session_start(mut self, session: SessionHandle) -> Self {
+    self.start_time_unix_nano = get_session_start(session).and_then(|start| {
+      let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+      Some(now.checked_sub(start.elapsed())?.as_nanos())
+    });
+    self
+  }
+}
+
+/// Builds OTLP/HTTP JSON requests that export events as log records.
+#[derive(Clone, Debug)]
+pub struct OtlpExporter {
+  config: OtlpConfig,
+}
+
+impl OtlpExporter {
+  /// Creates an exporter.
+  pub fn new(config: OtlpConfig) -> Self {
+    Self { config }
+  }
+
+  /// Returns the configuration of the exporter.
+  pub fn config(&self) -> &OtlpConfig {
+    &self.config
+  }
+
+  /// Builds a request exporting `events` with the given resource.
+  pub fn logs_request(
+    &self,
+    client: &Client,
+    resource: &OtlpResource,
+    events: &[EventPayload],
+  ) -> Result<Request, SdkBuildError> {
+    let mut request = client
+      .post(&self.config.endpoint)
+      .timeout(self.config.timeout)
+      .header("Content-Type", "application/json");
+    for (name, value) in &self.config.headers {
+      request = request.header(name, value);
+    }
+
+    Ok(request.json(&logs_payload(resource, events)).build()?)
+  }
+
+  /// Builds a blocking request exporting `events` with the given resource.
+  #[cfg(not(target_family = "wasm"))]
+  pub fn logs_request_blocking(
+    &self,
+    client: &BlockingClient,
+    resource: &OtlpResource,
+    events: &[EventPayload],
+  ) -> Result<BlockingRequest, SdkBuildError> {
+    let mut request = client
+      .post(&self.config.endpoint)
+      .timeout(self.config.timeout)
+      .header("Content-Type", "application/json");
+    for (name, value) in &self.config.headers {
+      request = request.header(name, value);
+    }
+
+    Ok(request.json(&logs_payload(resource, events)).build()?)
+  }
+}
+
+/// Returns a new random session id for [`OtlpResource::with_session_id`].
+pub fn new_session_id() -> String {
+  #[cfg(not(target_family = "wasm"))]
+  return uuid::Uuid::new_v4().to_string();
+  #[cfg(target_family = "wasm")]
+  return crate::web::random_uuid();
+}
+
+/// Returns the body of an OTLP/HTTP JSON logs request with one log record per event.
+///
+/// The level maps to the severity, the event type to `event.name` and the body, metadata keys to attributes and the
+/// source context to the `code.*` attributes.
+pub fn logs_payload(resource: &OtlpResource, events: &[EventPayload]) -> Value {
+  let mut resource_attributes = vec![
+    attribute("service.name", &json!(resource.service_name)),
+    attribute("service.version", &json!(resource.service_version)),
+    attribute("telemetry.sdk.name", &json!(SCOPE_NAME)),
+    attribute("telemetry.sdk.language", &json!("rust")),
+  ];
+  if let Some(session_id) = &resource.session_id {
+    resource_attributes.push(attribute("session.id", &json!(session_id)));
+  }
+  if let Some(player_id) = &resource.player_id {
+    resource_attributes.push(attribute("user.id", &json!(player_id)));
+  }
+
+  let observed_time = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|elapsed| elapsed.as_nanos())
+    .unwrap_or_default();
+
+  json!({
+    "resourceLogs": [{
+      "resource": { "attributes": resource_attributes },
+      "scopeLogs": [{
+        "scope": { "name": SCOPE_NAME, "version": env!("CARGO_PKG_VERSION") },
+        "logRecords": events
+          .iter()
+          .map(|event| log_record(event, resource.start_time_unix_nano, observed_time))
+          .collect::<Vec<_>>(),
+      }],
+    }],
+  })
+}
+
+fn log_record(event: &EventPayload, start_time: Option<u128>, observed_time: u128) -> Value {
+  let mut attributes = vec![
+    attribute("event.name", &json!(event.event_type())),
+    attribute("indigauge.idempotency_key", &json!(event.idempotency_key())),
+    attribute("indigauge.elapsed_ms", &json!(event.elapsed_ms() as u64)),
+  ];
+  if let Some(Value::Object(metadata)) = event.metadata() {
+    attributes.extend(metadata.iter().map(|(key, value)| attribute(key, value)));
+  }
+  if let Some(context) = event.context() {
+    attributes.push(attribute("code.filepath", &json!(context.file)));
+    attributes.push(attribute("code.lineno", &json!(context.line)));
+    if let Some(module) = &context.module {
+      attributes.push(attribute("code.namespace", &json!(module)));
+    }
+  }
+
+  let (severity_number, severity_text) = severity(event.level());
+  let time = start_time.map_or(observed_time, |start| start + event.elapsed_ms() * 1_000_000);
+  json!({
+    // OTLP/JSON encodes 64-bit integers as strings.
+    "timeUnixNano": time.to_string(),
+    "observedTimeUnixNano": observed_time.to_string(),
+    "severityNumber": severity_number,
+    "severityText": severity_text,
+    "body": { "stringValue": event.event_type() },
+    "attributes": attributes,
+  })
+}
+
+/// Maps a level to the first OpenTelemetry severity number of its range and its severity text.
+fn severity(level: IndigaugeLogLevel) -> (u8, &'static str) {
+  match level {
+    IndigaugeLogLevel::Trace => (1, "TRACE"),
+    IndigaugeLogLevel::Debug => (5, "DEBUG"),
+    IndigaugeLogLevel::Info => (9, "INFO"),
+    IndigaugeLogLevel::Warn => (13, "WARN"),
+    IndigaugeLogLevel::Error => (17, "ERROR"),
+    IndigaugeLogLevel::Fatal => (21, "FATAL"),
+    IndigaugeLogLevel::Silent => (0, "UNSPECIFIED"),
+  }
+}
+
+fn attribute(key: &str, value: &Value) -> Value {
+  json!({ "key": key, "value": any_value(value) })
+}
+
+/// Converts JSON to an OTLP `AnyValue`.
+fn any_value(value: &Value) -> Value {
+  match value {
+    Value::Null => json!({}),
+    Value::Bool(value) => json!({ "boolValue": value }),
+    Value::Number(number) => match number.as_i64() {
+      Some(int) => json!({ "intValue": int.to_string() }),
+      None => json!({ "doubleValue": number.as_f64() }),
+    },
+    Value::String(value) => json!({ "stringValue": value }),
+    Value::Array(values) => json!({ "arrayValue": { "values": values.iter().map(any_value).collect::<Vec<_>>() } }),
+    Value::Object(map) => json!({
+      "kvlistValue": { "values": map.iter().map(|(key, value)| attribute(key, value)).collect::<Vec<_>>() },
+    }),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use indigauge_types::prelude::EventPayloadCtx;
+
+  use super::*;
+
+  #[test]
+  fn events_are_mapped_to_log_records() {
+    let event = EventPayload::new(
+      "player.death",
+      IndigaugeLogLevel::Warn,
+      Some(json!({ "cause": "lava", "level": 3, "speed": 1.5, "tags": ["boss"], "pos": { "x": 1 } })),
+      250,
+    )
+    .with_context(Some(EventPayloadCtx {
+      file: "src/main.rs".to_string(),
+      line: 42,
+      module: Some("game::player".into()),
+    }));
+    let resource = OtlpResource {
+      start_time_unix_nano: Some(1_000_000_000),
+      ..OtlpResource::new("My Game", "1.2.0")
+        .with_session_id("session-1")
+        .with_player_id(Some("player-1".to_string()))
+    };
+
+    let payload = logs_payload(&resource, std::slice::from_ref(&event));
+
+    let resource_logs = &payload["resourceLogs"][0];
+    let resource_attributes = &resource_logs["resource"]["attributes"];
+    assert!(
+      resource_attributes
+        .as_array()
+        .unwrap()
+        .contains(&attribute("service.name", &json!("My Game")))
+    );
+    assert!(
+      resource_attributes
+        .as_array()
+        .unwrap()
+        .contains(&attribute("session.id", &json!("session-1")))
+    );
+    assert!(
+      resource_attributes
+        .as_array()
+        .unwrap()
+        .contains(&attribute("user.id", &json!("player-1")))
+    );
+
+    let record = &resource_logs["scopeLogs"][0]["logRecords"][0];
+    assert_eq!(record["timeUnixNano"], "1250000000");
+    assert_eq!(record["severityNumber"], 13);
+    assert_eq!(record["severityText"], "WARN");
+    assert_eq!(record["body"]["stringValue"], "player.death");
+    let attributes = record["attributes"].as_array().unwrap();
+    let value = |key: &str| {
+      attributes
+        .iter()
+        .find(|attribute| attribute["key"] == key)
+        .map(|attribute| attribute["value"].clone())
+        .unwrap()
+    };
+    assert_eq!(value("event.name"), json!({ "stringValue": "player.death" }));
+    assert_eq!(value("indigauge.idempotency_key"), json!({ "stringValue": event.idempotency_key() }));
+    assert_eq!(value("cause"), json!({ "stringValue": "lava" }));
+    assert_eq!(value("level"), json!({ "intValue": "3" }));
+    assert_eq!(value("speed"), json!({ "doubleValue": 1.5 }));
+    assert_eq!(value("tags"), json!({ "arrayValue": { "values": [{ "stringValue": "boss" }] } }));
+    assert_eq!(value("pos"), json!({ "kvlistValue": { "values": [{ "key": "x", "value": { "intValue": "1" } }] } }));
+    assert_eq!(value("code.lineno"), json!({ "intValue": "42" }));
+    assert_eq!(value("code.namespace"), json!({ "stringValue": "game::player" }));
+  }
+
+  #[test]
+  fn requests_carry_the_configured_headers() {
+    let exporter = OtlpExporter::new(
+      OtlpConfig::new("http://collector:4318/v1/logs")
+        .with_header("Authorization", "Bearer secret")
+        .with_mode(OtlpMode::Replace),
+    );
+
+    let request = exporter
+      .logs_request(&Client::new(), &OtlpResource::new("game", "1.0.0"), &[])
+      .unwrap();
+
+    assert_eq!(request.url().as_str(), "http://collector:4318/v1/logs");
+    assert_eq!(request.headers()["authorization"], "Bearer secret");
+    assert_eq!(request.headers()["content-type"], "application/json");
+    assert_eq!(exporter.config().mode(), OtlpMode::Replace);
+  }
+}
@@ -1,15 +1,12 @@
+use crate::redact::redact_text;
 #[cfg(not(target_family = "wasm"))]
 use crate::runtime::IndigaugeBlockingRuntimeClient;
-#[cfg(not(target_family = "wasm"))]
-use crate::state::drain_pending_events;
-#[cfg(not(target_family = "wasm"))]
+use crate::state::{drain_pending_events, get_session_start_instant, get_session_token};
 use crate::types::BatchEventPayload;
+use crate::utils::Instant;
 use indigauge_types::prelude::IndigaugeConfig;
-#[cfg(not(target_family = "wasm"))]
-use indigauge_types::prelude::{EventPayload, EventPayloadCtx, StartSessionResponse};
-#[cfg(not(target_family = "wasm"))]
+use indigauge_types::prelude::{EventPayload, EventPayloadCtx, IndigaugeLogLevel, StartSessionResponse};
 use serde_json::json;
-use std::time::Instant;
 
 /// Panic hook that ships a crash event and session end to the Indigauge backend.
 /// Caller decides whether to run it (e.g., not in dev mode) and provides the session start instant.
@@ -21,53 +18,123 @@ pub fn panic_handler_with_config(
 ) -> impl Fn(&std::panic::PanicHookInfo) + Send + Sync + 'static {
   let sdk_client = IndigaugeBlockingRuntimeClient::new(config);
 
-  move |info| {
-    if session_api_key == StartSessionResponse::dev().session_token {
-      return;
-    }
-
-    let pending_events = drain_pending_events()
-      .into_iter()
-      .map(|event| event.into_inner())
-      .collect::<Vec<_>>();
+  move |info| report_crash(&sdk_client, &session_api_key, session_start, info)
+}
 
-    if !pending_events.is_empty() {
-      let payload = BatchEventPayload { events: pending_events };
+/// Panic hook that reports against whichever session is active when the panic happens.
+///
+/// Reads the token from [`get_session_token`] at panic time, so it only needs to be installed once and stays
+/// correct when the session is renewed or restarted. Does nothing while no session is active.
+#[cfg(not(target_family = "wasm"))]
+pub fn panic_handler_for_current_session(
+  config: IndigaugeConfig,
+) -> impl Fn(&std::panic::PanicHookInfo) + Send + Sync + 'static {
+  let sdk_client = IndigaugeBlockingRuntimeClient::new(config);
 
-      if let Ok(request) = sdk_client.event_batch(&session_api_key, &payload) {
-        let _ = sdk_client.send(request);
-      }
+  move |info| {
+    if let (Some(session_api_key), Some(session_start)) = (get_session_token(), get_session_start_instant()) {
+      report_crash(&sdk_client, &session_api_key, session_start, info);
     }
+  }
+}
 
-    let elapsed_ms = Instant::now().duration_since(session_start).as_millis();
-
-    let metadata = info
-      .payload()
-      .downcast_ref::<&str>()
-      .map(|s| json!({"message": s.to_string()}));
+#[cfg(not(target_family = "wasm"))]
+fn report_crash(
+  sdk_client: &IndigaugeBlockingRuntimeClient,
+  session_api_key: &str,
+  session_start: Instant,
+  info: &std::panic::PanicHookInfo,
+) {
+  if session_api_key == StartSessionResponse::dev().session_token {
+    return;
+  }
 
-    let context = info.location().map(|loc| EventPayloadCtx {
-      file: loc.file().to_string(),
-      line: loc.line(),
-      module: None,
-    });
+  let mut events = drain_pending_events()
+    .into_iter()
+    .map(|event| event.into_inner())
+    .collect::<Vec<_>>();
+  events.push(crash_event(session_start, info));
 
-    let _payload = EventPayload::new("game.crash", "fatal", metadata, elapsed_ms).with_context(context);
+  if let Ok(request) = sdk_client.event_batch(session_api_key, &BatchEventPayload { events }) {
+    let _ = sdk_client.send(request);
+  }
 
-    if let Ok(request) = sdk_client.end_session(&session_api_key, "crashed") {
-      let _ = sdk_client.send(request);
-    }
+  if let Ok(request) = sdk_client.end_session(session_api_key, "crashed") {
+    let _ = sdk_client.send(request);
   }
 }
 
+/// Panic hook that logs the panic to the browser console and ships pending events, a crash event and the session
+/// end with `keepalive` requests, which do not block and complete even if the page is closed.
 #[cfg(target_family = "wasm")]
 pub fn panic_handler_with_config(
   config: IndigaugeConfig,
   session_api_key: String,
   session_start: Instant,
 ) -> impl Fn(&std::panic::PanicHookInfo) + Send + Sync + 'static {
-  let _ = (config, session_api_key, session_start);
-  move |_info| {}
+  move |info| {
+    crate::web::log_panic(info);
+    report_crash(&config, &session_api_key, session_start, info);
+  }
+}
+
+/// Panic hook that reports against whichever session is active when the panic happens.
+///
+/// Reads the token from [`get_session_token`] at panic time, so it only needs to be installed once and stays
+/// correct when the session is renewed or restarted. Only logs to the console while no session is active.
+#[cfg(target_family = "wasm")]
+pub fn panic_handler_for_current_session(
+  config: IndigaugeConfig,
+) -> impl Fn(&std::panic::PanicHookInfo) + Send + Sync + 'static {
+  move |info| {
+    crate::web::log_panic(info);
+    if let (Some(session_api_key), Some(session_start)) = (get_session_token(), get_session_start_instant()) {
+      report_crash(&config, &session_api_key, session_start, info);
+    }
+  }
+}
+
+#[cfg(target_family = "wasm")]
+fn report_crash(
+  config: &IndigaugeConfig,
+  session_api_key: &str,
+  session_start: Instant,
+  info: &std::panic::PanicHookInfo,
+) {
+  use crate::web::send_keepalive;
+
+  if session_api_key == StartSessionResponse::dev().session_token {
+    return;
+  }
+
+  let mut events = drain_pending_events()
+    .into_iter()
+    .map(|event| event.into_inner())
+    .collect::<Vec<_>>();
+  events.push(crash_event(session_start, info));
+
+  send_keepalive(config, "events/batch", session_api_key, &BatchEventPayload { events });
+  send_keepalive(config, "sessions/end", session_api_key, &json!({"reason": "crashed"}));
+}
+
+/// Builds the `game.crash` event with the redacted panic message and location.
+fn crash_event(session_start: Instant, info: &std::panic::PanicHookInfo) -> EventPayload {
+  let elapsed_ms = Instant::now().duration_since(session_start).as_millis();
+
+  let metadata = info
+    .payload()
+    .downcast_ref::<&str>()
+    .copied()
+    .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+    .map(|s| json!({"message": redact_text(s)}));
+
+  let context = info.location().map(|loc| EventPayloadCtx {
+    file: loc.file().to_string(),
+    line: loc.line(),
+    module: None,
+  });
+
+  EventPayload::new("game.crash", IndigaugeLogLevel::Fatal, metadata, elapsed_ms).with_context(context)
 }
 
 /// Legacy panic hook constructor using explicit API origin.
@@ -83,20 +150,7 @@ pub fn panic_handler(
       return;
     }
 
-    let elapsed_ms = Instant::now().duration_since(session_start).as_millis();
-
-    let metadata = info
-      .payload()
-      .downcast_ref::<&str>()
-      .map(|s| json!({"message": s.to_string()}));
-
-    let context = info.location().map(|loc| EventPayloadCtx {
-      file: loc.file().to_string(),
-      line: loc.line(),
-      module: None,
-    });
-
-    let payload = EventPayload::new("game.crash", "fatal", metadata, elapsed_ms).with_context(context);
+    let payload = crash_event(session_start, info);
 
     let single_event_endpoint = format!("{}/v1/events", host_origin);
     let client = reqwest::blocking::Client::new();
@@ -0,0 +1,308 @@
+use std::sync::{Arc, LazyLock, RwLock};
+
+use regex::Regex;
+use serde_json::Value;
+
+/// Replacement for values of denied keys.
+pub const REDACTED: &str = "[REDACTED]";
+
+const STANDARD_DENIED_KEYS: &[&str] = &[
+  "password",
+  "passwd",
+  "secret",
+  "token",
+  "api_key",
+  "authorization",
+  "cookie",
+  "email",
+];
+
+static EMAIL: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").expect("valid email pattern"));
+static IPV4: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b").expect("valid ipv4 pattern")
+});
+static IPV6: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"\b(?:[0-9A-Fa-f]{1,4}:){7}[0-9A-Fa-f]{1,4}\b").expect("valid ipv6 pattern"));
+static HOME_PATH: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r#"(?i)(/home/|/Users/|[A-Z]:\\Users\\|[A-Z]:/Users/)[^/\\\s"']+"#).expect("valid home path pattern")
+});
+
+static REDACTOR: RwLock<Option<Arc<Redactor>>> = RwLock::new(None);
+
+type RedactHook = dyn Fn(&mut Value) + Send + Sync;
+
+/// Redaction stage applied to event metadata, crash messages and feedback text before they leave the process.
+///
+/// Values of denied keys are replaced with [`REDACTED`], string values are run through the scrubbers, and the
+/// optional hook runs last on the whole value.
+#[derive(Clone, Default)]
+pub struct Redactor {
+  denied_keys: Vec<Vec<String>>,
+  scrubbers: Vec<(Regex, String)>,
+  hook: Option<Arc<RedactHook>>,
+}
+
+impl Redactor {
+  /// Creates a redactor without any rules.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Creates a redactor that denies common secret keys and scrubs emails, IP addresses and home directory paths.
+  pub fn standard() -> Self {
+    STANDARD_DENIED_KEYS
+      .iter()
+      .fold(Self::new(), |redactor, key| redactor.deny_key(*key))
+      .scrub_emails()
+      .scrub_ip_addresses()
+      .scrub_home_paths()
+  }
+
+  /// Redacts the value of every object key that is `key` or contains it as whole `_`-, `.`- or camelCase-separated
+  /// segments (case-insensitive), e.g. `token` matches `auth_token`, `session.token` and `authToken` but not
+  /// `tokens_earned`.
+  pub fn deny_key(mut self, key: impl Into<String>) -> Self {
+    self.denied_keys.push(key_segments(&key.into()));
+    self
+  }
+
+  /// Replaces matches of `pattern` in string values with `replacement` (supports `$1`-style groups).
+  pub fn scrub(mut self, pattern: &str, replacement: impl Into<String>) -> Result<Self, regex::Error> {
+    self.scrubbers.push((Regex::new(pattern)?, replacement.into()));
+    Ok(self)
+  }
+
+  /// Replaces email addresses with `[EMAIL]`.
+  pub fn scrub_emails(mut self) -> Self {
+    self.scrubbers.push((EMAIL.clone(), "[EMAIL]".to_string()));
+    self
+  }
+
+  /// Replaces IPv4 and full-form IPv6 addresses with `[IP]`.
+  pub fn scrub_ip_addresses(mut self) -> Self {
+    self.scrubbers.push((IPV4.clone(), "[IP]".to_string()));
+    self.scrubbers.push((IPV6.clone(), "[IP]".to_string()));
+    self
+  }
+
+  /// Replaces the user name in home directory paths, e.g. `/home/alice/save.dat` becomes `/home/[USER]/save.dat`.
+  pub fn scrub_home_paths(mut self) -> Self {
+    self.scrubbers.push((HOME_PATH.clone(), "${1}[USER]".to_string()));
+    self
+  }
+
+  /// Adds a custom hook that runs after the built-in rules.
+  pub fn with_hook(mut self, hook: impl Fn(&mut Value) + Send + Sync + 'static) -> Self {
+    self.hook = Some(Arc::new(hook));
+    self
+  }
+
+  /// Redacts a JSON value in place.
+  pub fn redact(&self, value: &mut Value) {
+    self.apply_rules(value);
+    if let Some(hook) = &self.hook {
+      hook(value);
+    }
+  }
+
+  /// Redacts free-form text such as a crash or feedback message.
+  pub fn redact_str(&self, text: &str) -> String {
+    let mut value = Value::String(text.to_string());
+    self.redact(&mut value);
+    match value {
+      Value::String(text) => text,
+      other => other.to_string(),
+    }
+  }
+
+  fn apply_rules(&self, value: &mut Value) {
+    match value {
+      Value::Object(map) => {
+        for (key, value) in map.iter_mut() {
+          let segments = key_segments(key);
+          if self
+            .denied_keys
+            .iter()
+            .any(|denied| segments.windows(denied.len()).any(|window| window == denied))
+          {
+            *value = Value::String(REDACTED.to_string());
+          } else {
+            self.apply_rules(value);
+          }
+        }
+      },
+      Value::Array(values) => values.iter_mut().for_each(|value| self.apply_rules(value)),
+      Value::String(text) => {
+        for (pattern, replacement) in &self.scrubbers {
+          if let std::borrow::Cow::Owned(scrubbed) = pattern.replace_all(text, replacement.as_str()) {
+            *text = scrubbed;
+          }
+        }
+      },
+      _ => {},
+    }
+  }
+}
+
+/// Splits a key into lowercase segments at `_`, `.` and camelCase boundaries, e.g. `playerAPIKey` into `player`,
+/// `api` and `key`.
+fn key_segments(key: &str) -> Vec<String> {
+  let chars = key.chars().collect::<Vec<_>>();
+  let mut segments = vec![String::new()];
+  for (index, &c) in chars.iter().enumerate() {
+    if c == '_' || c == '.' {
+      segments.push(String::new());
+      continue;
+    }
+
+    let previous = index.checked_sub(1).map(|index| chars[index]);
+    let next = chars.get(index + 1);
+    let starts_word = c.is_uppercase()
+      && previous.is_some_and(|previous| {
+        previous.is_lowercase()
+          || previous.is_ascii_digit()
+          || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+      });
+    if starts_word {
+      segments.push(String::new());
+    }
+    if let Some(segment) = segments.last_mut() {
+      segment.extend(c.to_lowercase());
+    }
+  }
+  segments
+}
+
+/// Installs the process-wide redactor used before events are queued.
+pub fn set_redactor(redactor: Redactor) {
+  *REDACTOR.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(redactor));
+}
+
+/// Removes the process-wide redactor.
+pub fn clear_redactor() {
+  *REDACTOR.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+fn current_redactor() -> Option<Arc<Redactor>> {
+  REDACTOR.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// Redacts metadata with the process-wide redactor, if one is installed.
+pub fn redact_metadata(metadata: &mut Value) {
+  if let Some(redactor) = current_redactor() {
+    redactor.redact(metadata);
+  }
+}
+
+/// Redacts text with the process-wide redactor, if one is installed.
+pub fn redact_text(text: &str) -> String {
+  match current_redactor() {
+    Some(redactor) => redactor.redact_str(text),
+    None => text.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn standard_redactor_scrubs_keys_and_values() {
+    let mut metadata = json!({
+      "user_email": "alice@example.com",
+      "path": "C:\\Users\\alice\\save.dat",
+      "peer": "connected to 192.168.1.20 from /home/alice/game",
+      "nested": [{ "password": "hunter2" }],
+      "version": "1.2.3",
+      "score": 10,
+    });
+
+    Redactor::standard().redact(&mut metadata);
+
+    assert_eq!(
+      metadata,
+      json!({
+        "user_email": REDACTED,
+        "path": "C:\\Users\\[USER]\\save.dat",
+        "peer": "connected to [IP] from /home/[USER]/game",
+        "nested": [{ "password": REDACTED }],
+        "version": "1.2.3",
+        "score": 10,
+      })
+    );
+  }
+
+  #[test]
+  fn denied_keys_match_whole_segments() {
+    let mut metadata = json!({
+      "auth_token": "abc",
+      "session.token": "def",
+      "Player_API_Key": "ghi",
+      "tokens_earned": 12,
+      "secrets_found": 3,
+      "api_keyboard": "qwerty",
+    });
+
+    Redactor::standard().redact(&mut metadata);
+
+    assert_eq!(
+      metadata,
+      json!({
+        "auth_token": REDACTED,
+        "session.token": REDACTED,
+        "Player_API_Key": REDACTED,
+        "tokens_earned": 12,
+        "secrets_found": 3,
+        "api_keyboard": "qwerty",
+      })
+    );
+  }
+
+  #[test]
+  fn denied_keys_match_camel_case_segments() {
+    let mut metadata = json!({
+      "authToken": "abc",
+      "apiKey": "def",
+      "sessionToken": "ghi",
+      "playerAPIKey": "jkl",
+      "tokensEarned": 12,
+      "apiKeyboard": "qwerty",
+    });
+
+    Redactor::standard().redact(&mut metadata);
+
+    assert_eq!(
+      metadata,
+      json!({
+        "authToken": REDACTED,
+        "apiKey": REDACTED,
+        "sessionToken": REDACTED,
+        "playerAPIKey": REDACTED,
+        "tokensEarned": 12,
+        "apiKeyboard": "qwerty",
+      })
+    );
+
+    let mut metadata = json!({ "refresh_token": "abc", "refresh": "def" });
+    Redactor::new().deny_key("refreshToken").redact(&mut metadata);
+    assert_eq!(metadata, json!({ "refresh_token": REDACTED, "refresh": "def" }));
+  }
+
+  #[test]
+  fn custom_scrubbers_and_hook_apply_to_text() {
+    let redactor = Redactor::new()
+      .scrub(r"steam:\d+", "steam:[ID]")
+      .expect("valid pattern")
+      .with_hook(|value| {
+        if let Value::String(text) = value {
+          *text = text.to_uppercase();
+        }
+      });
+
+    assert_eq!(redactor.redact_str("player steam:123 left"), "PLAYER STEAM:[ID] LEFT");
+    assert_eq!(Redactor::new().redact_str("mail bob@example.org"), "mail bob@example.org");
+  }
+}
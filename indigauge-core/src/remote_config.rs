@@ -0,0 +1,165 @@
+//! Remote configuration and feature flags, fetched at session start and cached for offline launches.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+/// Name of the file caching the last fetched remote config, next to the player id.
+pub const REMOTE_CONFIG_FILE: &str = "remote_config.json";
+
+/// Event type of the exposure events recorded when a feature flag is evaluated.
+pub const FLAG_EXPOSURE_EVENT: &str = "flag.exposure";
+
+/// A remote config document, e.g. `{ "version": "12", "values": { "enemy_speed": 1.5, "new_hud": true } }`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RemoteConfig {
+  /// Version of the document, if the server sends one. Sent with exposure events.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub version: Option<String>,
+  #[serde(default)]
+  pub values: Map<String, Value>,
+}
+
+impl RemoteConfig {
+  /// Creates an empty config, e.g. to hold the defaults used until a config was fetched.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Sets a value.
+  pub fn with_value(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+    self.values.insert(key.into(), value.into());
+    self
+  }
+
+  /// Parses a document.
+  pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+    serde_json::from_str(json)
+  }
+
+  /// Returns a value.
+  pub fn get(&self, key: &str) -> Option<&Value> {
+    self.values.get(key)
+  }
+
+  /// Returns a boolean value.
+  pub fn get_bool(&self, key: &str) -> Option<bool> {
+    self.get(key).and_then(Value::as_bool)
+  }
+
+  /// Returns an integer value.
+  pub fn get_i64(&self, key: &str) -> Option<i64> {
+    self.get(key).and_then(Value::as_i64)
+  }
+
+  /// Returns a number value.
+  pub fn get_f64(&self, key: &str) -> Option<f64> {
+    self.get(key).and_then(Value::as_f64)
+  }
+
+  /// Returns a string value.
+  pub fn get_str(&self, key: &str) -> Option<&str> {
+    self.get(key).and_then(Value::as_str)
+  }
+
+  /// Deserializes a value, e.g. into a struct of tuning values.
+  pub fn get_as<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+    self
+      .get(key)
+      .and_then(|value| serde_json::from_value(value.clone()).ok())
+  }
+
+  /// Returns a copy with the values of `other` replacing those of `self`, and the version of `other`.
+  pub fn merged(&self, other: &RemoteConfig) -> RemoteConfig {
+    let mut merged = self.clone();
+    merged.version = other.version.clone();
+    merged
+      .values
+      .extend(other.values.iter().map(|(key, value)| (key.clone(), value.clone())));
+    merged
+  }
+
+  /// Returns the keys whose value differs between `self` and `other`, sorted.
+  pub fn changed_keys(&self, other: &RemoteConfig) -> Vec<String> {
+    let mut keys = self
+      .values
+      .keys()
+      .chain(other.values.keys())
+      .filter(|key| self.values.get(*key) != other.values.get(*key))
+      .cloned()
+      .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    keys
+  }
+
+  /// Returns the metadata of the exposure event recorded when `flag` is evaluated.
+  pub fn exposure_metadata(&self, flag: &str) -> Value {
+    json!({
+      "flag": flag,
+      "value": self.get(flag).cloned().unwrap_or(Value::Null),
+      "version": self.version,
+    })
+  }
+}
+
+#[cfg(not(target_family = "wasm"))]
+/// Loads the remote config cached next to the player id, if any.
+pub fn load_remote_config(game_name: &str) -> Option<RemoteConfig> {
+  crate::utils::game_data_dir(game_name)
+    .and_then(|dir| std::fs::read_to_string(dir.join(REMOTE_CONFIG_FILE)).ok())
+    .and_then(|config| RemoteConfig::parse(&config).ok())
+}
+
+#[cfg(not(target_family = "wasm"))]
+/// Caches the remote config next to the player id for offline launches.
+pub fn store_remote_config(game_name: &str, config: &RemoteConfig) -> std::io::Result<()> {
+  let dir = crate::utils::game_data_dir(game_name).ok_or(std::io::ErrorKind::NotFound)?;
+  std::fs::create_dir_all(&dir)?;
+  let tmp_path = dir.join(format!("{REMOTE_CONFIG_FILE}.tmp"));
+  std::fs::write(&tmp_path, serde_json::to_vec(config)?)?;
+  std::fs::rename(tmp_path, dir.join(REMOTE_CONFIG_FILE))
+}
+
+#[cfg(target_family = "wasm")]
+/// Loads the remote config cached in `localStorage`, if any.
+pub fn load_remote_config(game_name: &str) -> Option<RemoteConfig> {
+  crate::web::read_item(game_name, REMOTE_CONFIG_FILE).and_then(|config| RemoteConfig::parse(&config).ok())
+}
+
+#[cfg(target_family = "wasm")]
+/// Caches the remote config in `localStorage` for offline visits.
+pub fn store_remote_config(game_name: &str, config: &RemoteConfig) -> std::io::Result<()> {
+  crate::web::write_item(game_name, REMOTE_CONFIG_FILE, &serde_json::to_string(config)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn fetched_values_replace_defaults() {
+    let defaults = RemoteConfig::new()
+      .with_value("enemy_speed", 1.0)
+      .with_value("new_hud", false)
+      .with_value("shop", "a");
+    let fetched =
+      RemoteConfig::parse(r#"{ "version": "7", "values": { "new_hud": true, "waves": { "count": 3 } } }"#).unwrap();
+
+    let config = defaults.merged(&fetched);
+    assert_eq!(config.version.as_deref(), Some("7"));
+    assert_eq!(config.get_f64("enemy_speed"), Some(1.0));
+    assert_eq!(config.get_bool("new_hud"), Some(true));
+    assert_eq!(config.get_str("shop"), Some("a"));
+    assert_eq!(config.get_i64("enemy_speed"), None);
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Waves {
+      count: u32,
+    }
+    assert_eq!(config.get_as::<Waves>("waves"), Some(Waves { count: 3 }));
+
+    assert_eq!(defaults.changed_keys(&config), ["new_hud", "waves"]);
+    assert_eq!(config.exposure_metadata("new_hud"), json!({ "flag": "new_hud", "value": true, "version": "7" }));
+  }
+}
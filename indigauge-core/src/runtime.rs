@@ -1,10 +1,12 @@
 use indigauge_types::prelude::{
-  BatchEventPayload, EventPayload, FeedbackPayload, IndigaugeConfig, StartSessionPayload,
+  BatchEventPayload, EventPayload, FeedbackPayload, IdentifyPayload, IndigaugeConfig, StartSessionPayload,
 };
 use reqwest::{Client, Request};
 
 #[cfg(not(target_family = "wasm"))]
 use reqwest::blocking::{Client as BlockingClient, Request as BlockingRequest};
+#[cfg(not(target_family = "wasm"))]
+use std::time::{Duration, Instant};
 
 use crate::http::{SdkBuildError, SdkHttpClient, SdkResponse};
 
@@ -67,6 +69,11 @@ impl IndigaugeRuntimeClient {
     self.http().heartbeat(session_token)
   }
 
+  /// Builds an identify request.
+  pub fn identify(&self, session_token: &str, payload: &IdentifyPayload<'_>) -> Result<Request, SdkBuildError> {
+    self.http().identify(session_token, payload)
+  }
+
   /// Builds an event batch request.
   pub fn event_batch(&self, session_token: &str, payload: &BatchEventPayload) -> Result<Request, SdkBuildError> {
     self.http().event_batch(session_token, payload)
@@ -100,6 +107,11 @@ impl IndigaugeRuntimeClient {
     self.http().feedback(session_token, payload)
   }
 
+  /// Builds a remote config request.
+  pub fn remote_config(&self, session_token: &str) -> Result<Request, SdkBuildError> {
+    self.http().remote_config(session_token)
+  }
+
   /// Builds a feedback screenshot request.
   pub fn feedback_screenshot(
     &self,
@@ -111,6 +123,18 @@ impl IndigaugeRuntimeClient {
   }
 }
 
+/// Outcome of [`IndigaugeBlockingRuntimeClient::flush_and_end_session`].
+#[cfg(not(target_family = "wasm"))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FinalFlushReport {
+  /// Number of events accepted by the ingest.
+  pub sent_events: usize,
+  /// Number of events that failed to send or were skipped because the deadline passed.
+  pub dropped_events: usize,
+  /// Whether the end-session request was accepted.
+  pub session_ended: bool,
+}
+
 /// Framework-agnostic blocking runtime client for native game loops and crash paths.
 #[cfg(not(target_family = "wasm"))]
 pub struct IndigaugeBlockingRuntimeClient {
@@ -168,6 +192,11 @@ impl IndigaugeBlockingRuntimeClient {
     self.http().heartbeat(session_token)
   }
 
+  /// Builds a blocking identify request.
+  pub fn identify(&self, session_token: &str, payload: &IdentifyPayload<'_>) -> Result<BlockingRequest, SdkBuildError> {
+    self.http().identify(session_token, payload)
+  }
+
   /// Builds an event batch request.
   pub fn event_batch(
     &self,
@@ -205,6 +234,11 @@ impl IndigaugeBlockingRuntimeClient {
     self.http().feedback(session_token, payload)
   }
 
+  /// Builds a remote config request.
+  pub fn remote_config(&self, session_token: &str) -> Result<BlockingRequest, SdkBuildError> {
+    self.http().remote_config(session_token)
+  }
+
   /// Builds a feedback screenshot request.
   pub fn feedback_screenshot(
     &self,
@@ -214,4 +248,60 @@ impl IndigaugeBlockingRuntimeClient {
   ) -> Result<BlockingRequest, SdkBuildError> {
     self.http().feedback_screenshot(session_token, feedback_id, png_bytes)
   }
+
+  /// Sends `events` in batches and ends the session, giving up once `deadline` has elapsed.
+  ///
+  /// Intended for shutdown paths where async requests would not complete before the process exits.
+  /// Each request is bounded by the remaining time budget, so this never blocks longer than `deadline`
+  /// (plus connection teardown).
+  pub fn flush_and_end_session(
+    &self,
+    session_token: &str,
+    events: Vec<EventPayload>,
+    reason: &str,
+    deadline: Duration,
+  ) -> FinalFlushReport {
+    let started = Instant::now();
+    let remaining = || {
+      deadline
+        .saturating_sub(started.elapsed())
+        .min(self.config.request_timeout())
+    };
+    let mut report = FinalFlushReport::default();
+    let mut events = events.into_iter().peekable();
+
+    while events.peek().is_some() {
+      let payload = BatchEventPayload {
+        events: events.by_ref().take(self.config.batch_size().max(1)).collect(),
+      };
+      let count = payload.events.len();
+
+      let budget = remaining();
+      if budget.is_zero() {
+        report.dropped_events += count + events.len();
+        return report;
+      }
+
+      match self.event_batch(session_token, &payload) {
+        Ok(mut request) => {
+          *request.timeout_mut() = Some(budget);
+          match self.send(request) {
+            Ok(response) if response.status().is_success() => report.sent_events += count,
+            _ => report.dropped_events += count,
+          }
+        },
+        Err(_) => report.dropped_events += count,
+      }
+    }
+
+    let budget = remaining();
+    if !budget.is_zero()
+      && let Ok(mut request) = self.end_session(session_token, reason)
+    {
+      *request.timeout_mut() = Some(budget);
+      report.session_ended = self.send(request).is_ok_and(|response| response.status().is_success());
+    }
+
+    report
+  }
 }
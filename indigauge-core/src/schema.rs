@@ -0,0 +1,435 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::json;
+pub use serde_json::{Map, Value};
+
+use crate::event::IndigaugeEvent;
+
+/// JSON schema dialect of the schemas generated for typed events.
+pub const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Describes how a metadata value is represented in the JSON schema of a typed event.
+///
+/// Implemented for primitives, strings, collections and [`Value`]. Implement it for your own types to use them as
+/// fields of an `IndigaugeEvent`.
+pub trait MetadataSchema {
+  /// Whether the key must be present. `Option` fields are optional and left out of the metadata when `None`.
+  const REQUIRED: bool = true;
+
+  /// Returns the JSON schema of the value.
+  fn schema() -> Value;
+}
+
+macro_rules! impl_metadata_schema {
+  ($schema:tt => $($ty:ty),+) => {
+    $(
+      impl MetadataSchema for $ty {
+        fn schema() -> Value {
+          json!($schema)
+        }
+      }
+    )+
+  };
+}
+
+impl_metadata_schema!({ "type": "boolean" } => bool);
+impl_metadata_schema!({ "type": "integer" } => i8, i16, i32, i64, i128, isize);
+impl_metadata_schema!({ "type": "integer", "minimum": 0 } => u8, u16, u32, u64, u128, usize);
+impl_metadata_schema!({ "type": "number" } => f32, f64);
+impl_metadata_schema!({ "type": "string" } => str, String, char);
+impl_metadata_schema!({} => Value);
+
+impl<T: MetadataSchema> MetadataSchema for Option<T> {
+  const REQUIRED: bool = false;
+
+  fn schema() -> Value {
+    T::schema()
+  }
+}
+
+macro_rules! impl_metadata_schema_pointer {
+  ($($ty:ty),+) => {
+    $(
+      impl<T: MetadataSchema + ?Sized> MetadataSchema for $ty {
+        const REQUIRED: bool = T::REQUIRED;
+
+        fn schema() -> Value {
+          T::schema()
+        }
+      }
+    )+
+  };
+}
+
+impl_metadata_schema_pointer!(&T, Box<T>, Rc<T>, Arc<T>);
+
+impl<T: MetadataSchema + ToOwned + ?Sized> MetadataSchema for Cow<'_, T> {
+  const REQUIRED: bool = T::REQUIRED;
+
+  fn schema() -> Value {
+    T::schema()
+  }
+}
+
+macro_rules! impl_metadata_schema_array {
+  ($($ty:ty),+) => {
+    $(
+      impl<T: MetadataSchema> MetadataSchema for $ty {
+        fn schema() -> Value {
+          json!({ "type": "array", "items": T::schema() })
+        }
+      }
+    )+
+  };
+}
+
+impl_metadata_schema_array!([T], Vec<T>, VecDeque<T>, BTreeSet<T>, HashSet<T>);
+
+impl<T: MetadataSchema, const N: usize> MetadataSchema for [T; N] {
+  fn schema() -> Value {
+    json!({ "type": "array", "items": T::schema(), "minItems": N, "maxItems": N })
+  }
+}
+
+macro_rules! impl_metadata_schema_map {
+  ($($ty:ty),+) => {
+    $(
+      impl<K: AsRef<str>, V: MetadataSchema> MetadataSchema for $ty {
+        fn schema() -> Value {
+          json!({ "type": "object", "additionalProperties": V::schema() })
+        }
+      }
+    )+
+  };
+}
+
+impl_metadata_schema_map!(BTreeMap<K, V>, HashMap<K, V>);
+
+/// Serializes a field of a typed event into `metadata`. Optional fields that are `None` are left out.
+pub fn insert_field<T: MetadataSchema + Serialize + ?Sized>(metadata: &mut Map<String, Value>, key: &str, value: &T) {
+  match serde_json::to_value(value) {
+    Ok(Value::Null) if !T::REQUIRED => {},
+    Ok(value) => {
+      metadata.insert(key.to_string(), value);
+    },
+    Err(_) => {},
+  }
+}
+
+/// Builds the JSON schema of a typed event from its `(key, schema, required)` fields.
+pub fn event_schema(event_type: &str, fields: &[(&str, Value, bool)]) -> Value {
+  let properties = fields
+    .iter()
+    .map(|(key, schema, _)| (key.to_string(), schema.clone()))
+    .collect::<Map<_, _>>();
+  let required = fields
+    .iter()
+    .filter(|(_, _, required)| *required)
+    .map(|(key, _, _)| *key)
+    .collect::<Vec<_>>();
+
+  json!({
+    "$schema": SCHEMA_DIALECT,
+    "title": event_type,
+    "type": "object",
+    "properties": properties,
+    "required": required,
+    "additionalProperties": false,
+  })
+}
+
+/// A mismatch between an event's metadata and the schema registered for its event type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaViolation {
+  /// No schema is registered for the event type.
+  UnknownEvent,
+  /// The metadata is not a JSON object.
+  NotAnObject,
+  /// A required key is missing.
+  MissingKey(String),
+  /// A key is not declared by a schema that disallows additional properties.
+  UnknownKey(String),
+  /// A value does not have the declared type.
+  TypeMismatch {
+    key: String,
+    expected: String,
+    found: &'static str,
+  },
+}
+
+impl fmt::Display for SchemaViolation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SchemaViolation::UnknownEvent => write!(f, "no schema registered for event type"),
+      SchemaViolation::NotAnObject => write!(f, "metadata is not an object"),
+      SchemaViolation::MissingKey(key) => write!(f, "missing required key '{key}'"),
+      SchemaViolation::UnknownKey(key) => write!(f, "unknown key '{key}'"),
+      SchemaViolation::TypeMismatch { key, expected, found } => {
+        write!(f, "key '{key}' should be {expected}, found {found}")
+      },
+    }
+  }
+}
+
+/// Error returned when schemas cannot be loaded.
+#[derive(Debug)]
+pub enum SchemaError {
+  Io(std::io::Error),
+  Json(serde_json::Error),
+  Invalid(String),
+}
+
+impl fmt::Display for SchemaError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SchemaError::Io(err) => write!(f, "io error: {}", err),
+      SchemaError::Json(err) => write!(f, "json error: {}", err),
+      SchemaError::Invalid(err) => write!(f, "invalid schema: {}", err),
+    }
+  }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<std::io::Error> for SchemaError {
+  fn from(value: std::io::Error) -> Self {
+    Self::Io(value)
+  }
+}
+
+impl From<serde_json::Error> for SchemaError {
+  fn from(value: serde_json::Error) -> Self {
+    Self::Json(value)
+  }
+}
+
+/// Expected metadata shape per event type, used to catch drifting call sites.
+///
+/// Schemas are the JSON schemas generated by [`IndigaugeEvent::schema`] or written by hand. Only `properties`,
+/// their `type`, `required` and `additionalProperties: false` are checked.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaRegistry {
+  schemas: HashMap<String, Value>,
+}
+
+impl SchemaRegistry {
+  /// Creates an empty registry.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Registers the schema of a typed event.
+  pub fn register<E: IndigaugeEvent>(self) -> Self {
+    self.register_schema(E::EVENT_TYPE, E::schema())
+  }
+
+  /// Registers a JSON schema for an event type, replacing any previous one.
+  pub fn register_schema(mut self, event_type: impl Into<String>, schema: Value) -> Self {
+    self.schemas.insert(event_type.into(), schema);
+    self
+  }
+
+  /// Registers the schemas of a JSON document: either an object keyed by event type, or an array of schemas
+  /// whose `title` is the event type.
+  pub fn load_json(mut self, json: &str) -> Result<Self, SchemaError> {
+    match serde_json::from_str::<Value>(json)? {
+      Value::Object(schemas) if !schemas.contains_key("$schema") => {
+        self.schemas.extend(schemas);
+      },
+      Value::Array(schemas) => {
+        for schema in schemas {
+          let event_type = schema
+            .get("title")
+            .and_then(Value::as_str)
+            .ok_or_else(|| SchemaError::Invalid("schema without a 'title' event type".to_string()))?
+            .to_string();
+          self.schemas.insert(event_type, schema);
+        }
+      },
+      _ => {
+        return Err(SchemaError::Invalid("expected an object keyed by event type or an array of schemas".to_string()));
+      },
+    }
+    Ok(self)
+  }
+
+  #[cfg(not(target_family = "wasm"))]
+  /// Registers the schemas of a JSON file, see [`SchemaRegistry::load_json`].
+  pub fn load_file(self, path: impl AsRef<std::path::Path>) -> Result<Self, SchemaError> {
+    let json = std::fs::read_to_string(path)?;
+    self.load_json(&json)
+  }
+
+  /// Returns the schema registered for an event type.
+  pub fn get(&self, event_type: &str) -> Option<&Value> {
+    self.schemas.get(event_type)
+  }
+
+  /// Checks metadata against the schema of its event type.
+  pub fn validate(&self, event_type: &str, metadata: Option<&Value>) -> Vec<SchemaViolation> {
+    let Some(schema) = self.schemas.get(event_type) else {
+      return vec![SchemaViolation::UnknownEvent];
+    };
+
+    let empty = Map::new();
+    let metadata = match metadata {
+      None => &empty,
+      Some(Value::Object(metadata)) => metadata,
+      Some(_) => return vec![SchemaViolation::NotAnObject],
+    };
+
+    let mut violations = required_keys(schema)
+      .filter(|key| !metadata.contains_key(*key))
+      .map(|key| SchemaViolation::MissingKey(key.to_string()))
+      .collect::<Vec<_>>();
+
+    for (key, value) in metadata {
+      match property(schema, key) {
+        Some(property) => {
+          if let Some(expected) = type_mismatch(property, value) {
+            violations.push(SchemaViolation::TypeMismatch {
+              key: key.clone(),
+              expected,
+              found: json_type(value),
+            });
+          }
+        },
+        None if !allows_additional(schema) => violations.push(SchemaViolation::UnknownKey(key.clone())),
+        None => {},
+      }
+    }
+
+    violations
+  }
+
+  /// Removes keys that are unknown or have the wrong type. Returns the number of removed keys.
+  ///
+  /// Events without a registered schema are left untouched.
+  pub fn strip(&self, event_type: &str, metadata: &mut Value) -> usize {
+    let (Some(schema), Value::Object(metadata)) = (self.schemas.get(event_type), metadata) else {
+      return 0;
+    };
+
+    let before = metadata.len();
+    metadata.retain(|key, value| match property(schema, key) {
+      Some(property) => type_mismatch(property, value).is_none(),
+      None => allows_additional(schema),
+    });
+    before - metadata.len()
+  }
+}
+
+fn required_keys(schema: &Value) -> impl Iterator<Item = &str> {
+  schema
+    .get("required")
+    .and_then(Value::as_array)
+    .into_iter()
+    .flatten()
+    .filter_map(Value::as_str)
+}
+
+fn property<'a>(schema: &'a Value, key: &str) -> Option<&'a Value> {
+  schema.get("properties")?.get(key)
+}
+
+fn allows_additional(schema: &Value) -> bool {
+  schema.get("additionalProperties") != Some(&Value::Bool(false))
+}
+
+/// Returns the expected type if `value` does not match the `type` of `property`.
+fn type_mismatch(property: &Value, value: &Value) -> Option<String> {
+  let expected = match property.get("type")? {
+    Value::String(expected) => vec![expected.as_str()],
+    Value::Array(expected) => expected.iter().filter_map(Value::as_str).collect(),
+    _ => return None,
+  };
+
+  let found = json_type(value);
+  let matches = expected
+    .iter()
+    .any(|expected| *expected == found || (*expected == "number" && found == "integer"));
+  (!matches).then(|| expected.join(" or "))
+}
+
+fn json_type(value: &Value) -> &'static str {
+  match value {
+    Value::Null => "null",
+    Value::Bool(_) => "boolean",
+    Value::Number(number) if number.is_f64() => "number",
+    Value::Number(_) => "integer",
+    Value::String(_) => "string",
+    Value::Array(_) => "array",
+    Value::Object(_) => "object",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn registry() -> SchemaRegistry {
+    SchemaRegistry::new().register_schema(
+      "player.death",
+      event_schema(
+        "player.death",
+        &[
+          ("cause", String::schema(), true),
+          ("level", u32::schema(), true),
+          ("killer", String::schema(), false),
+        ],
+      ),
+    )
+  }
+
+  #[test]
+  fn reports_schema_violations() {
+    let registry = registry();
+
+    assert!(
+      registry
+        .validate("player.death", Some(&json!({ "cause": "lava", "level": 3 })))
+        .is_empty()
+    );
+    assert_eq!(registry.validate("player.spawn", None), vec![SchemaViolation::UnknownEvent]);
+    assert_eq!(
+      registry.validate("player.death", Some(&json!({ "cause": 7, "lvl": 3 }))),
+      vec![
+        SchemaViolation::MissingKey("level".to_string()),
+        SchemaViolation::TypeMismatch {
+          key: "cause".to_string(),
+          expected: "string".to_string(),
+          found: "integer",
+        },
+        SchemaViolation::UnknownKey("lvl".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn strips_nonconforming_keys() {
+    let mut metadata = json!({ "cause": "lava", "level": "three", "debug": true });
+
+    assert_eq!(registry().strip("player.death", &mut metadata), 2);
+    assert_eq!(metadata, json!({ "cause": "lava" }));
+  }
+
+  #[test]
+  fn loads_schemas_from_json() {
+    let registry = SchemaRegistry::new()
+      .load_json(r#"{ "ui.click": { "properties": { "button": { "type": "string" } }, "required": ["button"] } }"#)
+      .expect("valid schemas");
+
+    assert!(
+      registry
+        .validate("ui.click", Some(&json!({ "button": "ok" })))
+        .is_empty()
+    );
+    assert_eq!(registry.validate("ui.click", None), vec![SchemaViolation::MissingKey("button".to_string())]);
+    assert!(SchemaRegistry::new().load_json("[{}]").is_err());
+  }
+}
@@ -0,0 +1,389 @@
+//! Output-side event sinks, so one emitted event can be sent to several destinations.
+//!
+//! Every destination of validated events is a sink registered in [`EventSinks`]: the Indigauge ingest, a local
+//! NDJSON event file, a console printer, an in-game overlay or a custom backend. Each sink receives the events that
+//! pass its own [`SinkOptions`] filter, in batches of its own size and interval.
+//!
+//! A sink is fire-and-forget: it cannot report a failed delivery. Destinations that retry failed batches, like the
+//! ingest of the Bevy plugin, register a sink that hands the events to their own delivery queue.
+
+use std::fmt;
+use std::time::Duration;
+
+use indigauge_types::prelude::{EventPayload, IndigaugeLogLevel};
+
+#[cfg(not(target_family = "wasm"))]
+use crate::ndjson::{NdjsonRecordKind, NdjsonWriter};
+use crate::state::SessionHandle;
+use crate::utils::Instant;
+
+/// Default interval after which a sink receives its buffered events, even if the batch is not full.
+pub const DEFAULT_SINK_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A destination for validated events, e.g. a file, the console, an overlay or a custom backend.
+///
+/// Closures taking the session and a batch of events are sinks too.
+pub trait EventSink: Send + 'static {
+  /// Receives a batch of events of one session that passed the filter of the sink.
+  fn send(&mut self, session: SessionHandle, events: &[EventPayload]);
+
+  /// Called after the last batch, when the game exits.
+  fn flush(&mut self) {}
+}
+
+impl<F> EventSink for F
+where
+  F: FnMut(SessionHandle, &[EventPayload]) + Send + 'static,
+{
+  fn send(&mut self, session: SessionHandle, events: &[EventPayload]) {
+    self(session, events);
+  }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl EventSink for NdjsonWriter {
+  fn send(&mut self, session: SessionHandle, events: &[EventPayload]) {
+    for event in events {
+      if self.write(NdjsonRecordKind::Event, Some(session), event).is_err() {
+        return;
+      }
+    }
+  }
+
+  fn flush(&mut self) {
+    let _ = NdjsonWriter::flush(self);
+  }
+}
+
+/// Filter and batching of a registered sink. By default, a sink receives every event as soon as it is dispatched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SinkOptions {
+  min_level: IndigaugeLogLevel,
+  namespaces: Vec<String>,
+  batch_size: usize,
+  flush_interval: Duration,
+}
+
+impl Default for SinkOptions {
+  fn default() -> Self {
+    Self {
+      min_level: IndigaugeLogLevel::Trace,
+      namespaces: Vec::new(),
+      batch_size: 1,
+      flush_interval: DEFAULT_SINK_FLUSH_INTERVAL,
+    }
+  }
+}
+
+impl SinkOptions {
+  /// Creates options that pass every event without batching.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Drops events below the given level.
+  pub fn with_min_level(mut self, level: IndigaugeLogLevel) -> Self {
+    self.min_level = level;
+    self
+  }
+
+  /// Only passes events in the given namespaces, e.g. `ui` for `ui.menu.open`. A namespace matches itself and its
+  /// `.` children. All namespaces pass by default.
+  pub fn with_namespaces<I, S>(mut self, namespaces: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    self.namespaces = namespaces.into_iter().map(Into::into).collect();
+    self
+  }
+
+  /// Sends events once this many are buffered (at least 1).
+  pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+    self.batch_size = batch_size.max(1);
+    self
+  }
+
+  /// Sends buffered events after this interval, even if the batch is not full.
+  pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+    self.flush_interval = flush_interval;
+    self
+  }
+
+  /// Returns the minimum level.
+  pub fn min_level(&self) -> IndigaugeLogLevel {
+    self.min_level
+  }
+
+  /// Returns the namespaces that pass, empty if all pass.
+  pub fn namespaces(&self) -> &[String] {
+    &self.namespaces
+  }
+
+  /// Returns the batch size.
+  pub fn batch_size(&self) -> usize {
+    self.batch_size
+  }
+
+  /// Returns the flush interval.
+  pub fn flush_interval(&self) -> Duration {
+    self.flush_interval
+  }
+
+  /// Returns `true` if an event passes the filter.
+  pub fn accepts(&self, event: &EventPayload) -> bool {
+    let event_type = event.event_type();
+    event.level() != IndigaugeLogLevel::Silent
+      && event.level() >= self.min_level
+      && (self.namespaces.is_empty()
+        || self.namespaces.iter().any(|namespace| {
+          event_type
+            .strip_prefix(namespace.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        }))
+  }
+}
+
+struct RegisteredSink {
+  name: String,
+  sink: Box<dyn EventSink>,
+  options: SinkOptions,
+  buffer: Vec<(SessionHandle, EventPayload)>,
+  last_flush: Instant,
+}
+
+impl RegisteredSink {
+  /// Sends the buffered events, one batch per run of events of the same session.
+  fn send_buffered(&mut self) {
+    self.last_flush = Instant::now();
+    let mut buffer = std::mem::take(&mut self.buffer);
+
+    while let Some(&(session, _)) = buffer.first() {
+      let len = buffer
+        .iter()
+        .position(|(other, _)| *other != session)
+        .unwrap_or(buffer.len());
+      let events = buffer.drain(..len).map(|(_, event)| event).collect::<Vec<_>>();
+      self.sink.send(session, &events);
+    }
+  }
+}
+
+/// The registered [`EventSink`]s and the events buffered for each of them.
+#[derive(Default)]
+pub struct EventSinks {
+  sinks: Vec<RegisteredSink>,
+}
+
+impl fmt::Debug for EventSinks {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list()
+      .entries(self.sinks.iter().map(|sink| (&sink.name, &sink.options)))
+      .finish()
+  }
+}
+
+impl EventSinks {
+  /// Creates an empty registry.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Registers a sink under a name. A sink already registered under the name is flushed and replaced.
+  pub fn register(&mut self, name: impl Into<String>, sink: impl EventSink, options: SinkOptions) {
+    let name = name.into();
+    self.remove(&name);
+    self.sinks.push(RegisteredSink {
+      name,
+      sink: Box::new(sink),
+      options,
+      buffer: Vec::new(),
+      last_flush: Instant::now(),
+    });
+  }
+
+  /// Flushes and removes the sink registered under a name. Returns `true` if there was one.
+  pub fn remove(&mut self, name: &str) -> bool {
+    let Some(index) = self.sinks.iter().position(|sink| sink.name == name) else {
+      return false;
+    };
+    let mut sink = self.sinks.remove(index);
+    sink.send_buffered();
+    sink.sink.flush();
+    true
+  }
+
+  /// Returns the options of the sink registered under a name.
+  pub fn options(&self, name: &str) -> Option<&SinkOptions> {
+    self
+      .sinks
+      .iter()
+      .find(|sink| sink.name == name)
+      .map(|sink| &sink.options)
+  }
+
+  /// Replaces the options of the sink registered under a name, sending its buffered events first. Returns `true` if
+  /// there was one.
+  pub fn set_options(&mut self, name: &str, options: SinkOptions) -> bool {
+    let Some(sink) = self.sinks.iter_mut().find(|sink| sink.name == name) else {
+      return false;
+    };
+    sink.send_buffered();
+    sink.options = options;
+    true
+  }
+
+  /// Returns the names of the registered sinks, in registration order.
+  pub fn names(&self) -> impl Iterator<Item = &str> {
+    self.sinks.iter().map(|sink| sink.name.as_str())
+  }
+
+  /// Returns the number of registered sinks.
+  pub fn len(&self) -> usize {
+    self.sinks.len()
+  }
+
+  /// Returns `true` if no sink is registered.
+  pub fn is_empty(&self) -> bool {
+    self.sinks.is_empty()
+  }
+
+  /// Buffers an event for every sink whose filter it passes, sending full batches right away.
+  pub fn dispatch(&mut self, session: SessionHandle, event: &EventPayload) {
+    for sink in self.sinks.iter_mut().filter(|sink| sink.options.accepts(event)) {
+      sink.buffer.push((session, event.clone()));
+      if sink.buffer.len() >= sink.options.batch_size {
+        sink.send_buffered();
+      }
+    }
+  }
+
+  /// Drops the buffered events that do not pass `filter`, e.g. events the player no longer consents to.
+  pub fn retain(&mut self, mut filter: impl FnMut(SessionHandle, &EventPayload) -> bool) {
+    for sink in &mut self.sinks {
+      sink.buffer.retain(|(session, event)| filter(*session, event));
+    }
+  }
+
+  /// Sends the buffered events of the sinks whose flush interval has elapsed.
+  pub fn flush_due(&mut self) {
+    for sink in self
+      .sinks
+      .iter_mut()
+      .filter(|sink| sink.last_flush.elapsed() >= sink.options.flush_interval)
+    {
+      if sink.buffer.is_empty() {
+        sink.last_flush = Instant::now();
+      } else {
+        sink.send_buffered();
+      }
+    }
+  }
+
+  /// Sends all buffered events and flushes every sink, e.g. when the game exits.
+  pub fn flush_all(&mut self) {
+    for sink in &mut self.sinks {
+      sink.send_buffered();
+      sink.sink.flush();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::{Arc, Mutex};
+
+  use super::*;
+
+  type Received = Arc<Mutex<Vec<(SessionHandle, Vec<String>)>>>;
+
+  fn recorder() -> (Received, impl EventSink) {
+    let received = Received::default();
+    let sink = {
+      let received = received.clone();
+      move |session: SessionHandle, events: &[EventPayload]| {
+        let types = events.iter().map(|event| event.event_type().to_string()).collect();
+        received.lock().unwrap().push((session, types));
+      }
+    };
+    (received, sink)
+  }
+
+  fn event(event_type: &str, level: IndigaugeLogLevel) -> EventPayload {
+    EventPayload::new(event_type, level, None, 0)
+  }
+
+  #[test]
+  fn events_are_filtered_per_sink() {
+    let (all, all_sink) = recorder();
+    let (ui_warnings, ui_warnings_sink) = recorder();
+    let mut sinks = EventSinks::new();
+    sinks.register("all", all_sink, SinkOptions::new());
+    sinks.register(
+      "ui",
+      ui_warnings_sink,
+      SinkOptions::new()
+        .with_min_level(IndigaugeLogLevel::Warn)
+        .with_namespaces(["ui"]),
+    );
+
+    sinks.dispatch(SessionHandle::DEFAULT, &event("ui.menu.open", IndigaugeLogLevel::Info));
+    sinks.dispatch(SessionHandle::DEFAULT, &event("ui.menu.error", IndigaugeLogLevel::Error));
+    sinks.dispatch(SessionHandle::DEFAULT, &event("uix.crash", IndigaugeLogLevel::Error));
+
+    assert_eq!(all.lock().unwrap().len(), 3);
+    assert_eq!(*ui_warnings.lock().unwrap(), vec![(SessionHandle::DEFAULT, vec!["ui.menu.error".to_string()])]);
+  }
+
+  #[test]
+  fn buffered_events_are_sent_in_batches_per_session() {
+    let (received, sink) = recorder();
+    let mut sinks = EventSinks::new();
+    sinks.register(
+      "batched",
+      sink,
+      SinkOptions::new()
+        .with_batch_size(3)
+        .with_flush_interval(Duration::ZERO),
+    );
+    let player = SessionHandle::next();
+
+    sinks.dispatch(SessionHandle::DEFAULT, &event("a.one", IndigaugeLogLevel::Info));
+    sinks.dispatch(player, &event("a.two", IndigaugeLogLevel::Info));
+    assert!(received.lock().unwrap().is_empty());
+
+    sinks.flush_due();
+    assert_eq!(
+      *received.lock().unwrap(),
+      vec![
+        (SessionHandle::DEFAULT, vec!["a.one".to_string()]),
+        (player, vec!["a.two".to_string()])
+      ]
+    );
+
+    sinks.dispatch(player, &event("a.three", IndigaugeLogLevel::Info));
+    sinks.dispatch(player, &event("a.debug", IndigaugeLogLevel::Debug));
+    sinks.retain(|_, event| event.level() >= IndigaugeLogLevel::Info);
+    assert!(sinks.remove("batched"));
+    assert!(sinks.is_empty());
+    assert_eq!(received.lock().unwrap()[2], (player, vec!["a.three".to_string()]));
+  }
+
+  #[test]
+  fn options_of_a_registered_sink_can_be_replaced() {
+    let (received, sink) = recorder();
+    let mut sinks = EventSinks::new();
+    sinks.register("batched", sink, SinkOptions::new().with_batch_size(10));
+    sinks.dispatch(SessionHandle::DEFAULT, &event("a.one", IndigaugeLogLevel::Info));
+
+    let warnings = SinkOptions::new().with_min_level(IndigaugeLogLevel::Warn);
+    assert!(sinks.set_options("batched", warnings.clone()));
+    assert!(!sinks.set_options("missing", SinkOptions::new()));
+    assert_eq!(sinks.options("batched"), Some(&warnings));
+    assert_eq!(received.lock().unwrap().len(), 1);
+
+    sinks.dispatch(SessionHandle::DEFAULT, &event("a.two", IndigaugeLogLevel::Info));
+    sinks.dispatch(SessionHandle::DEFAULT, &event("a.three", IndigaugeLogLevel::Warn));
+    assert_eq!(received.lock().unwrap()[1], (SessionHandle::DEFAULT, vec!["a.three".to_string()]));
+  }
+}
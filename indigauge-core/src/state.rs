@@ -1,15 +1,49 @@
-use std::sync::OnceLock;
-use std::time::Instant;
-use std::{collections::VecDeque, sync::Mutex};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
 
 use crossbeam_channel::{Receiver, Sender, bounded};
-use indigauge_types::prelude::{EventPayload, EventPayloadCtx};
+use indigauge_types::prelude::{EventPayload, EventPayloadCtx, IndigaugeLogLevel};
 
-use crate::event::{QueuedEvent, set_event_dispatcher};
+use crate::consent::effective_consent;
+use crate::event::{QueuedEvent, set_event_dispatcher, set_session_event_dispatcher};
+use crate::redact::redact_metadata;
+use crate::utils::Instant;
 
 pub(crate) static GLOBAL_TX: OnceLock<Sender<QueuedEvent>> = OnceLock::new();
-pub(crate) static SESSION_START_INSTANT: OnceLock<Instant> = OnceLock::new();
+pub(crate) static SESSION_START_INSTANT: RwLock<Option<Instant>> = RwLock::new(None);
 pub(crate) static PENDING_EVENTS: OnceLock<Mutex<VecDeque<QueuedEvent>>> = OnceLock::new();
+pub(crate) static SESSION_TOKEN: RwLock<Option<String>> = RwLock::new(None);
+pub(crate) static SESSION_STARTS: RwLock<BTreeMap<SessionHandle, Instant>> = RwLock::new(BTreeMap::new());
+static MIN_EVENT_LEVEL: RwLock<IndigaugeLogLevel> = RwLock::new(IndigaugeLogLevel::Trace);
+static DROPPED_EVENTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Identifies one of several sessions that can be active at once, e.g. one per local player in split-screen.
+///
+/// [`SessionHandle::DEFAULT`] is the process-wide session targeted by the `ig_*` macros.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SessionHandle(u32);
+
+impl SessionHandle {
+  /// The default session.
+  pub const DEFAULT: Self = Self(0);
+
+  /// Allocates a new, process-unique handle.
+  pub fn next() -> Self {
+    static NEXT: AtomicU32 = AtomicU32::new(1);
+    Self(NEXT.fetch_add(1, Ordering::Relaxed))
+  }
+
+  /// Returns the numeric id of the handle.
+  pub fn id(self) -> u32 {
+    self.0
+  }
+
+  /// Returns `true` for [`SessionHandle::DEFAULT`].
+  pub fn is_default(self) -> bool {
+    self == Self::DEFAULT
+  }
+}
 
 fn pending_events_lock() -> &'static Mutex<VecDeque<QueuedEvent>> {
   PENDING_EVENTS.get_or_init(|| Mutex::new(VecDeque::new()))
@@ -33,6 +67,22 @@ pub fn clear_pending_event_count(count: usize) {
   }
 }
 
+/// Returns a copy of the currently tracked pending events, oldest first.
+pub fn pending_events() -> Vec<QueuedEvent> {
+  let pending = pending_events_lock()
+    .lock()
+    .unwrap_or_else(|poisoned| poisoned.into_inner());
+  pending.iter().cloned().collect()
+}
+
+/// Stops tracking the pending events with the given idempotency keys, e.g. after they were delivered out of band.
+pub fn untrack_pending_events(idempotency_keys: &[&str]) {
+  let mut pending = pending_events_lock()
+    .lock()
+    .unwrap_or_else(|poisoned| poisoned.into_inner());
+  pending.retain(|event| !idempotency_keys.contains(&event.payload().idempotency_key()));
+}
+
 /// Drains and returns all currently tracked pending events.
 pub fn drain_pending_events() -> Vec<QueuedEvent> {
   let mut pending = pending_events_lock()
@@ -51,6 +101,7 @@ pub fn init(max_queue: usize) -> Option<Receiver<QueuedEvent>> {
   let (tx, rx) = bounded(max_queue);
   if GLOBAL_TX.set(tx).is_ok() {
     set_event_dispatcher(enqueue);
+    set_session_event_dispatcher(enqueue_for);
     Some(rx)
   } else {
     None
@@ -65,18 +116,119 @@ pub fn get_global_tx() -> Option<&'static Sender<QueuedEvent>> {
   GLOBAL_TX.get()
 }
 
+/// Sets the start instant of the default session. Fails if a session is already started; see [`reset_session`].
 pub fn set_session_start_instant(instant: Instant) -> Result<(), Instant> {
-  SESSION_START_INSTANT.set(instant)
+  let mut start = SESSION_START_INSTANT
+    .write()
+    .unwrap_or_else(|poisoned| poisoned.into_inner());
+  if start.is_some() {
+    return Err(instant);
+  }
+  *start = Some(instant);
+  Ok(())
+}
+
+pub fn get_session_start_instant() -> Option<Instant> {
+  *SESSION_START_INSTANT
+    .read()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Clears the state of the default session so a new one can be started in the same process.
+///
+/// Events of the ended session that are still tracked for the crash-time flush are discarded, and events
+/// emitted afterwards are rejected until the next session start.
+pub fn reset_session() {
+  *SESSION_START_INSTANT
+    .write()
+    .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+  *SESSION_TOKEN.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+  drain_pending_events();
+
+  #[cfg(target_family = "wasm")]
+  crate::web::clear_unload_flushed();
+}
+
+/// Sets the token of the currently active session, replacing any previous one (e.g. after renewal).
+pub fn set_session_token(token: impl Into<String>) {
+  *SESSION_TOKEN.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(token.into());
+}
+
+/// Returns the token of the currently active session, if any.
+pub fn get_session_token() -> Option<String> {
+  SESSION_TOKEN
+    .read()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+    .clone()
+}
+
+/// Registers an additional session, making [`enqueue_for`] accept events for its handle.
+///
+/// Relative event timestamps of the session are measured from `start`.
+pub fn register_session(handle: SessionHandle, start: Instant) {
+  SESSION_STARTS
+    .write()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+    .insert(handle, start);
+}
+
+/// Unregisters an additional session; later events for its handle are rejected.
+pub fn unregister_session(handle: SessionHandle) {
+  SESSION_STARTS
+    .write()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+    .remove(&handle);
+}
+
+/// Returns the start instant of the session behind `handle`, if it is active.
+pub fn get_session_start(handle: SessionHandle) -> Option<Instant> {
+  if handle.is_default() {
+    return get_session_start_instant();
+  }
+
+  SESSION_STARTS
+    .read()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+    .get(&handle)
+    .copied()
+}
+
+/// Sets the minimum level of events accepted by [`enqueue`]; less severe events are dropped.
+///
+/// Defaults to [`IndigaugeLogLevel::Trace`], so every event is accepted. [`IndigaugeLogLevel::Silent`] drops all events.
+pub fn set_min_event_level(level: IndigaugeLogLevel) {
+  *MIN_EVENT_LEVEL.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = level;
 }
 
-pub fn get_session_start_instant() -> Option<&'static Instant> {
-  SESSION_START_INSTANT.get()
+/// Returns the minimum level of events accepted by [`enqueue`].
+pub fn min_event_level() -> IndigaugeLogLevel {
+  *MIN_EVENT_LEVEL.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Returns the number of events dropped by [`enqueue`] because the event queue was full.
+pub fn dropped_event_count() -> usize {
+  DROPPED_EVENTS.load(Ordering::Relaxed)
 }
 
 #[inline]
 /// Queues a validated event in the global sender if a session is active.
 pub fn enqueue(
-  level: &'static str,
+  level: IndigaugeLogLevel,
+  event_type: &str,
+  metadata: Option<serde_json::Value>,
+  file: &'static str,
+  line: u32,
+  module: &'static str,
+) -> bool {
+  enqueue_for(SessionHandle::DEFAULT, level, event_type, metadata, file, line, module)
+}
+
+/// Queues a validated event for the session behind `session` if it is active and allowed by the player consent.
+///
+/// Only events of the default session are tracked for the crash-time flush.
+pub fn enqueue_for(
+  session: SessionHandle,
+  level: IndigaugeLogLevel,
   event_type: &str,
   metadata: Option<serde_json::Value>,
   file: &'static str,
@@ -88,11 +240,19 @@ pub fn enqueue(
     None => return false,
   };
 
-  if let Some(start_instant) = SESSION_START_INSTANT.get() {
-    let elapsed_ms = Instant::now().duration_since(*start_instant).as_millis();
-    let module = if module.is_empty() { None } else { Some(module) };
+  if level < min_event_level() || !effective_consent().allows_level(level) {
+    return false;
+  }
+
+  if let Some(start_instant) = get_session_start(session) {
+    let elapsed_ms = Instant::now().duration_since(start_instant).as_millis();
+    let metadata = metadata.map(|mut metadata| {
+      redact_metadata(&mut metadata);
+      metadata
+    });
+    let module = if module.is_empty() { None } else { Some(module.into()) };
 
-    let context = matches!(level, "warn" | "error").then(|| EventPayloadCtx {
+    let context = (level >= IndigaugeLogLevel::Warn).then(|| EventPayloadCtx {
       file: file.to_string(),
       line,
       module,
@@ -100,9 +260,11 @@ pub fn enqueue(
 
     let payload = EventPayload::new(event_type, level, metadata, elapsed_ms).with_context(context);
 
-    let queued_event = QueuedEvent::new(payload);
+    let queued_event = QueuedEvent::new(payload).with_session(session);
     let sent = tx.try_send(queued_event.clone()).is_ok();
-    if sent {
+    if !sent {
+      DROPPED_EVENTS.fetch_add(1, Ordering::Relaxed);
+    } else if session.is_default() {
       track_pending_event(queued_event);
     }
     sent
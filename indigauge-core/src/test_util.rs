@@ -0,0 +1,112 @@
+//! Test support for the SDK crates, enabled with the `test-util` feature.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Request received by the [`StubIngest`].
+#[derive(Clone, Debug)]
+pub struct StubRequest {
+  pub path: String,
+  pub api_key: String,
+  pub body: serde_json::Value,
+}
+
+/// Minimal HTTP/1.1 ingest stand-in that answers each request through a handler and records it.
+pub struct StubIngest {
+  pub api_base: String,
+  requests: Arc<Mutex<Vec<StubRequest>>>,
+}
+
+impl StubIngest {
+  /// Spawns the stub. `respond` returns the status line (e.g. `"200 OK"`) and JSON body for a request.
+  pub fn spawn(mut respond: impl FnMut(&StubRequest) -> (&'static str, String) + Send + 'static) -> Self {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind stub ingest");
+    let addr = listener.local_addr().expect("stub address");
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+
+    thread::spawn(move || {
+      for stream in listener.incoming().flatten() {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        let _ = reader.read_line(&mut request_line);
+        let mut content_length = 0;
+        let mut api_key = String::new();
+        loop {
+          let mut header = String::new();
+          if reader.read_line(&mut header).unwrap_or(0) == 0 || header == "\r\n" {
+            break;
+          }
+          if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+              content_length = value.trim().parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("x-indigauge-key") {
+              api_key = value.trim().to_string();
+            }
+          }
+        }
+        let mut body = vec![0; content_length];
+        let _ = reader.read_exact(&mut body);
+
+        let request = StubRequest {
+          path: request_line.split_whitespace().nth(1).unwrap_or_default().to_string(),
+          api_key,
+          body: serde_json::from_slice(&body).unwrap_or_default(),
+        };
+        let (status, body) = respond(&request);
+        recorded
+          .lock()
+          .unwrap_or_else(|poisoned| poisoned.into_inner())
+          .push(request);
+
+        let mut stream = reader.into_inner();
+        let response = format!(
+          "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+          body.len()
+        );
+        let _ = stream.write_all(response.as_bytes());
+      }
+    });
+
+    Self {
+      api_base: format!("http://{addr}"),
+      requests,
+    }
+  }
+
+  /// Spawns a stub that answers every request with `200 OK` and `body`.
+  pub fn ok(body: impl Into<String>) -> Self {
+    let body = body.into();
+    Self::spawn(move |_| ("200 OK", body.clone()))
+  }
+
+  /// Returns all requests received so far.
+  pub fn requests(&self) -> Vec<StubRequest> {
+    self.lock().clone()
+  }
+
+  /// Returns and forgets all requests received so far.
+  pub fn take_requests(&self) -> Vec<StubRequest> {
+    std::mem::take(&mut *self.lock())
+  }
+
+  /// Returns the requests received so far for `path`.
+  pub fn requests_to(&self, path: &str) -> Vec<StubRequest> {
+    self
+      .requests()
+      .into_iter()
+      .filter(|request| request.path == path)
+      .collect()
+  }
+
+  /// Returns the paths of all requests received so far.
+  pub fn paths(&self) -> Vec<String> {
+    self.lock().iter().map(|request| request.path.clone()).collect()
+  }
+
+  fn lock(&self) -> std::sync::MutexGuard<'_, Vec<StubRequest>> {
+    self.requests.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
@@ -2,33 +2,29 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use indigauge_types::prelude::IndigaugeLogLevel;
 use serde_json::{Value, json};
-use tracing::{Event, Level, Subscriber, field::Field};
+use tracing::{Event, Level, Subscriber, field::Field, span};
 use tracing_subscriber::{Layer, field::Visit, layer::Context, registry::LookupSpan};
 
-use crate::event::validate_event_type;
-
-const EVENT_TYPE_FIELDS: &[&str] = &["ig", "event_type"];
-
-/// Sink abstraction used by the tracing layer to emit events.
-pub trait IndigaugeSink: Send + Sync + 'static {
-  /// Emits a structured tracing event.
-  fn log(
-    &self,
-    level: &'static str,
-    event_type: &str,
-    metadata: Option<serde_json::Value>,
-    file: &'static str,
-    line: u32,
-    module: &'static str,
-  );
-}
+pub use crate::event::IndigaugeSink;
+use crate::event::{EVENT_TYPE_FIELDS, event_naming_policy};
+use crate::filter::{FilterHandle, LOG_ENV_VAR, LevelDirectives};
+use crate::utils::Instant;
+
+const DEFAULT_SPAN_DEPTH: usize = 0;
+/// Metadata key of the enclosing spans attached with [`IndigaugeLayer::with_span_depth`].
+pub const SPAN_CONTEXT_KEY: &str = "_spans";
+/// Event type of the timing events emitted when a span closes.
+pub const SPAN_TIMING_EVENT_TYPE: &str = "timing.span";
 
 /// Tracing subscriber layer that forwards compatible events to an [`IndigaugeSink`].
 pub struct IndigaugeLayer {
-  filters: Vec<String>,
-  levels: Vec<IndigaugeLogLevel>,
-  event_type_required: bool,
-  sink: Arc<dyn IndigaugeSink>,
+  pub(crate) filters: Vec<String>,
+  pub(crate) levels: Vec<IndigaugeLogLevel>,
+  pub(crate) event_type_required: bool,
+  pub(crate) directives: FilterHandle,
+  span_depth: usize,
+  span_timings: bool,
+  pub(crate) sink: Arc<dyn IndigaugeSink>,
 }
 
 impl IndigaugeLayer {
@@ -42,6 +38,9 @@ impl IndigaugeLayer {
         IndigaugeLogLevel::Error,
       ],
       event_type_required: false,
+      directives: FilterHandle::default(),
+      span_depth: DEFAULT_SPAN_DEPTH,
+      span_timings: false,
       sink,
     }
   }
@@ -66,6 +65,96 @@ impl IndigaugeLayer {
     self.levels = levels;
     self
   }
+
+  /// Sets per-target minimum levels, e.g. `my_game::ai=warn,bevy_render=off`.
+  ///
+  /// Targets matched by a directive ignore [`with_levels`](Self::with_levels); module filters still apply.
+  pub fn with_directives(self, directives: LevelDirectives) -> Self {
+    self.directives.reload(directives);
+    self
+  }
+
+  /// Applies the directives of the `INDIGAUGE_LOG` environment variable, if it is set.
+  ///
+  /// Invalid directives are ignored with a warning on stderr, since the subscriber is not installed yet.
+  pub fn with_env_directives(self) -> Self {
+    match LevelDirectives::from_env() {
+      Some(Ok(directives)) => self.with_directives(directives),
+      Some(Err(error)) => {
+        eprintln!("Ignoring {LOG_ENV_VAR}: {error}");
+        self
+      },
+      None => self,
+    }
+  }
+
+  /// Returns a handle that changes the directives of this layer at runtime.
+  pub fn filter_handle(&self) -> FilterHandle {
+    self.directives.clone()
+  }
+
+  /// Attaches up to `span_depth` of the innermost enclosing spans to events as [`SPAN_CONTEXT_KEY`] metadata, unless
+  /// the event has a field of that name. Defaults to `0`, which disables span context.
+  pub fn with_span_depth(mut self, span_depth: usize) -> Self {
+    self.span_depth = span_depth;
+    self
+  }
+
+  /// Emits a [`SPAN_TIMING_EVENT_TYPE`] event with the busy and idle time of each span when it closes.
+  ///
+  /// Timing events pass the same module filters, directives and levels as regular events, at the span's level.
+  pub fn with_span_timings(mut self, span_timings: bool) -> Self {
+    self.span_timings = span_timings;
+    self
+  }
+
+  fn is_enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+    let module = metadata.module_path().unwrap_or_default();
+    if self.filters.iter().any(|filter| module.starts_with(filter)) {
+      return false;
+    }
+
+    let level = level_to_log_level(metadata.level());
+    self
+      .directives
+      .enabled(metadata.target(), level)
+      .unwrap_or_else(|| self.levels.contains(&level))
+  }
+
+  fn span_context<S>(&self, event: &Event<'_>, ctx: &Context<'_, S>) -> Option<Value>
+  where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+  {
+    if self.span_depth == 0 {
+      return None;
+    }
+
+    let mut spans = ctx
+      .event_scope(event)?
+      .take(self.span_depth)
+      .map(|span| {
+        let fields = span
+          .extensions()
+          .get::<SpanFields>()
+          .map(|fields| fields.0.clone())
+          .unwrap_or_default();
+        json!({ "name": span.name(), "fields": fields })
+      })
+      .collect::<Vec<_>>();
+    // Outermost span first, like the `fmt` layer.
+    spans.reverse();
+    Some(Value::Array(spans))
+  }
+}
+
+/// Recorded fields of a span, stored in its extensions.
+struct SpanFields(BTreeMap<String, Value>);
+
+/// Busy and idle time of a span, stored in its extensions when span timings are enabled.
+struct SpanTiming {
+  busy: std::time::Duration,
+  idle: std::time::Duration,
+  last: Instant,
 }
 
 fn level_to_log_level(level: &Level) -> IndigaugeLogLevel {
@@ -74,7 +163,7 @@ fn level_to_log_level(level: &Level) -> IndigaugeLogLevel {
     Level::WARN => IndigaugeLogLevel::Warn,
     Level::INFO => IndigaugeLogLevel::Info,
     Level::DEBUG => IndigaugeLogLevel::Debug,
-    Level::TRACE => IndigaugeLogLevel::Silent,
+    Level::TRACE => IndigaugeLogLevel::Trace,
   }
 }
 
@@ -82,14 +171,26 @@ fn level_to_log_level(level: &Level) -> IndigaugeLogLevel {
 struct FieldVisitor {
   event_type: Option<String>,
   fields: BTreeMap<String, Value>,
+  /// Records `ig`/`event_type` as regular fields, used for span fields.
+  keep_event_type: bool,
+}
+
+impl FieldVisitor {
+  fn for_span(fields: BTreeMap<String, Value>) -> Self {
+    Self {
+      event_type: None,
+      fields,
+      keep_event_type: true,
+    }
+  }
 }
 
 impl Visit for FieldVisitor {
   fn record_str(&mut self, field: &Field, value: &str) {
     let name = field.name();
 
-    if self.event_type.is_none() && EVENT_TYPE_FIELDS.contains(&name) {
-      if validate_event_type(value).is_ok() {
+    if !self.keep_event_type && self.event_type.is_none() && EVENT_TYPE_FIELDS.contains(&name) {
+      if event_naming_policy().validate(value).is_ok() {
         self.event_type = Some(value.to_string());
       }
     } else {
@@ -136,20 +237,107 @@ impl Visit for FieldVisitor {
 }
 
 impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for IndigaugeLayer {
-  fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
-    let metadata = event.metadata();
-    let module = metadata.module_path().unwrap_or_default();
+  fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+    let Some(span) = ctx.span(id) else {
+      return;
+    };
+    let mut extensions = span.extensions_mut();
 
-    if self.filters.iter().any(|filter| module.starts_with(filter)) {
+    // Span fields are attached to events as span context and to timing events.
+    if self.span_depth > 0 || self.span_timings {
+      let mut visitor = FieldVisitor::for_span(BTreeMap::new());
+      attrs.record(&mut visitor);
+      extensions.insert(SpanFields(visitor.fields));
+    }
+    if self.span_timings {
+      extensions.insert(SpanTiming {
+        busy: std::time::Duration::ZERO,
+        idle: std::time::Duration::ZERO,
+        last: Instant::now(),
+      });
+    }
+  }
+
+  fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+    let Some(span) = ctx.span(id) else {
       return;
+    };
+    let mut extensions = span.extensions_mut();
+
+    if let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() {
+      let mut visitor = FieldVisitor::for_span(std::mem::take(fields));
+      values.record(&mut visitor);
+      *fields = visitor.fields;
     }
+  }
 
-    let level = level_to_log_level(metadata.level());
+  fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+    if let Some(span) = ctx.span(id)
+      && let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>()
+    {
+      let now = Instant::now();
+      timing.idle += now.duration_since(timing.last);
+      timing.last = now;
+    }
+  }
+
+  fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+    if let Some(span) = ctx.span(id)
+      && let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>()
+    {
+      let now = Instant::now();
+      timing.busy += now.duration_since(timing.last);
+      timing.last = now;
+    }
+  }
+
+  fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+    if !self.span_timings {
+      return;
+    }
+    let Some(span) = ctx.span(&id) else {
+      return;
+    };
+    let metadata = span.metadata();
+    if !self.is_enabled(metadata) {
+      return;
+    }
 
-    if !self.levels.contains(&level) {
+    let extensions = span.extensions();
+    let Some(timing) = extensions.get::<SpanTiming>() else {
       return;
+    };
+    let idle = timing.idle + Instant::now().duration_since(timing.last);
+    let mut timing_metadata = json!({
+      "span": span.name(),
+      "busy_ms": timing.busy.as_secs_f64() * 1000.0,
+      "idle_ms": idle.as_secs_f64() * 1000.0,
+    });
+    if let Some(SpanFields(fields)) = extensions.get::<SpanFields>()
+      && !fields.is_empty()
+    {
+      timing_metadata["fields"] = json!(fields);
     }
 
+    self.sink.log(
+      level_to_log_level(metadata.level()),
+      SPAN_TIMING_EVENT_TYPE,
+      Some(timing_metadata),
+      metadata.file().unwrap_or("unknown file"),
+      metadata.line().unwrap_or_default(),
+      metadata.module_path().unwrap_or_default(),
+    );
+  }
+
+  fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+    let metadata = event.metadata();
+    let module = metadata.module_path().unwrap_or_default();
+
+    if !self.is_enabled(metadata) {
+      return;
+    }
+    let level = level_to_log_level(metadata.level());
+
     let mut visitor = FieldVisitor::default();
     event.record(&mut visitor);
 
@@ -157,22 +345,21 @@ impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for IndigaugeLayer {
       return;
     }
 
-    let level_str: &'static str = match level {
-      IndigaugeLogLevel::Debug => "debug",
-      IndigaugeLogLevel::Info => "info",
-      IndigaugeLogLevel::Warn => "warn",
-      IndigaugeLogLevel::Error => "error",
-      IndigaugeLogLevel::Silent => "silent",
-    };
-    let event_type = visitor.event_type.unwrap_or_else(|| format!("tracing.{}", level_str));
+    if !visitor.fields.contains_key(SPAN_CONTEXT_KEY)
+      && let Some(spans) = self.span_context(event, &ctx)
+    {
+      visitor.fields.insert(SPAN_CONTEXT_KEY.to_string(), spans);
+    }
+
+    let event_type = visitor
+      .event_type
+      .unwrap_or_else(|| format!("tracing.{}", level.as_str()));
     let event_metadata =
       (!visitor.fields.is_empty()).then(|| serde_json::to_value(&visitor.fields).unwrap_or_default());
     let file = metadata.file().unwrap_or("unknown file");
     let line = metadata.line().unwrap_or_default();
 
-    self
-      .sink
-      .log(level_str, &event_type, event_metadata, file, line, module);
+    self.sink.log(level, &event_type, event_metadata, file, line, module);
   }
 }
 
@@ -203,15 +390,15 @@ mod tests {
   impl IndigaugeSink for TestSink {
     fn log(
       &self,
-      level: &'static str,
+      level: IndigaugeLogLevel,
       event_type: &str,
       metadata: Option<serde_json::Value>,
       file: &'static str,
       line: u32,
       module: &'static str,
     ) {
-      let module = if module.is_empty() { None } else { Some(module) };
-      let context = matches!(level, "warn" | "error").then(|| EventPayloadCtx {
+      let module = if module.is_empty() { None } else { Some(module.into()) };
+      let context = (level >= IndigaugeLogLevel::Warn).then(|| EventPayloadCtx {
         file: file.to_string(),
         line,
         module,
@@ -239,6 +426,9 @@ mod tests {
         IndigaugeLogLevel::Error,
       ],
       event_type_required: false,
+      directives: FilterHandle::default(),
+      span_depth: DEFAULT_SPAN_DEPTH,
+      span_timings: false,
     };
 
     let _guard = with_indigauge_layer(layer);
@@ -254,12 +444,12 @@ mod tests {
     assert_eq!(events.len(), 6);
 
     let expected = [
-      ("info", "tracing.info", "Test default info event type", false),
-      ("warn", "tracing.warn", "Test default warn event type", true),
-      ("error", "tracing.error", "Test default error event type", true),
-      ("info", "custom.event", "Test set event type", false),
-      ("info", "custom.event", "Test set ig", false),
-      ("info", "custom.event", "", false),
+      (IndigaugeLogLevel::Info, "tracing.info", "Test default info event type", false),
+      (IndigaugeLogLevel::Warn, "tracing.warn", "Test default warn event type", true),
+      (IndigaugeLogLevel::Error, "tracing.error", "Test default error event type", true),
+      (IndigaugeLogLevel::Info, "custom.event", "Test set event type", false),
+      (IndigaugeLogLevel::Info, "custom.event", "Test set ig", false),
+      (IndigaugeLogLevel::Info, "custom.event", "", false),
     ];
 
     events
@@ -281,11 +471,146 @@ mod tests {
         if *has_context {
           let ctx = event.context().expect("Context");
           assert!(ctx.file.ends_with("tracing.rs"));
-          assert!(ctx.module.unwrap_or_default().starts_with("indigauge_core::tracing"));
+          assert!(
+            ctx
+              .module
+              .as_deref()
+              .unwrap_or_default()
+              .starts_with("indigauge_core::tracing")
+          );
         }
       });
   }
 
+  #[test]
+  fn trace_events_keep_their_level() {
+    let sink = Arc::new(TestSink::new());
+    let layer = IndigaugeLayer {
+      sink: sink.clone(),
+      filters: vec![], // The default filters will filter out test events
+      levels: vec![IndigaugeLogLevel::Trace],
+      event_type_required: false,
+      directives: FilterHandle::default(),
+      span_depth: DEFAULT_SPAN_DEPTH,
+      span_timings: false,
+    };
+
+    let _guard = with_indigauge_layer(layer);
+
+    tracing::trace!(message = "Test default trace event type");
+    tracing::debug!(message = "Test default debug event type");
+
+    let events = sink.take_events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].level(), IndigaugeLogLevel::Trace);
+    assert_eq!(events[0].event_type(), "tracing.trace");
+  }
+
+  #[test]
+  fn directives_override_levels_per_target() {
+    let sink = Arc::new(TestSink::new());
+    let layer = IndigaugeLayer {
+      sink: sink.clone(),
+      filters: vec![], // The default filters will filter out test events
+      levels: vec![IndigaugeLogLevel::Info],
+      event_type_required: false,
+      directives: FilterHandle::default(),
+      span_depth: DEFAULT_SPAN_DEPTH,
+      span_timings: false,
+    }
+    .with_directives(LevelDirectives::parse("indigauge_core::tracing=error").unwrap());
+    let handle = layer.filter_handle();
+
+    let _guard = with_indigauge_layer(layer);
+
+    tracing::warn!(message = "Dropped by the directive");
+    tracing::info!(target: "other_crate", message = "Forwarded by the default levels");
+    handle
+      .reload_str("indigauge_core::tracing=off,other_crate=warn")
+      .unwrap();
+    tracing::error!(message = "Dropped after reload");
+    tracing::info!(target: "other_crate", message = "Dropped after reload");
+    tracing::warn!(target: "other_crate::net", message = "Forwarded after reload");
+
+    let events = sink.take_events();
+    let messages = events
+      .iter()
+      .map(|event| event.metadata().and_then(|metadata| metadata["message"].as_str()))
+      .collect::<Vec<_>>();
+    assert_eq!(messages, [Some("Forwarded by the default levels"), Some("Forwarded after reload")]);
+  }
+
+  #[test]
+  fn events_capture_enclosing_spans() {
+    let sink = Arc::new(TestSink::new());
+    let layer = IndigaugeLayer {
+      sink: sink.clone(),
+      filters: vec![], // The default filters will filter out test events
+      levels: vec![IndigaugeLogLevel::Error],
+      event_type_required: false,
+      directives: FilterHandle::default(),
+      span_depth: 2,
+      span_timings: false,
+    };
+
+    let _guard = with_indigauge_layer(layer);
+
+    let session = tracing::info_span!("session", ig = "ignored.here");
+    let _session = session.enter();
+    let load = tracing::info_span!("load_level", level = 3, asset = tracing::field::Empty);
+    let _load = load.enter();
+    load.record("asset", "level_3.gltf");
+    let parse = tracing::debug_span!("parse");
+    let _parse = parse.enter();
+    tracing::error!(message = "Missing mesh", spans = 3);
+    tracing::error!(message = "Reserved key", _spans = "kept");
+
+    let events = sink.take_events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+      events[0].metadata(),
+      Some(&json!({
+        "message": "Missing mesh",
+        "spans": 3,
+        "_spans": [
+          { "name": "load_level", "fields": { "level": 3, "asset": "level_3.gltf" } },
+          { "name": "parse", "fields": {} },
+        ]
+      }))
+    );
+    assert_eq!(events[1].metadata(), Some(&json!({ "message": "Reserved key", "_spans": "kept" })));
+  }
+
+  #[test]
+  fn closed_spans_emit_timing_events() {
+    let sink = Arc::new(TestSink::new());
+    let layer = IndigaugeLayer {
+      sink: sink.clone(),
+      filters: vec![], // The default filters will filter out test events
+      levels: vec![IndigaugeLogLevel::Info],
+      event_type_required: false,
+      directives: FilterHandle::default(),
+      span_depth: DEFAULT_SPAN_DEPTH,
+      span_timings: true,
+    };
+
+    let _guard = with_indigauge_layer(layer);
+
+    tracing::info_span!("load_level", level = 3).in_scope(|| std::thread::sleep(std::time::Duration::from_millis(5)));
+    tracing::debug_span!("filtered").in_scope(|| {});
+
+    let events = sink.take_events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type(), SPAN_TIMING_EVENT_TYPE);
+    assert_eq!(events[0].level(), IndigaugeLogLevel::Info);
+
+    let metadata = events[0].metadata().expect("Timing metadata");
+    assert_eq!(metadata["span"], "load_level");
+    assert_eq!(metadata["fields"], json!({ "level": 3 }));
+    assert!(metadata["busy_ms"].as_f64().unwrap_or_default() >= 5.0);
+    assert!(metadata["idle_ms"].as_f64().is_some());
+  }
+
   #[test]
   fn events_are_filtered_out_by_module() {
     let sink = Arc::new(TestSink::new());
@@ -311,6 +636,9 @@ mod tests {
       filters: vec![], // The default filters will filter out test events
       levels: vec![IndigaugeLogLevel::Error],
       event_type_required: false,
+      directives: FilterHandle::default(),
+      span_depth: DEFAULT_SPAN_DEPTH,
+      span_timings: false,
     };
 
     let _guard = with_indigauge_layer(layer);
@@ -324,7 +652,7 @@ mod tests {
     let events = sink.take_events();
     assert_eq!(events.len(), 1);
 
-    assert_eq!(events[0].level(), "error");
+    assert_eq!(events[0].level(), IndigaugeLogLevel::Error);
     assert_eq!(events[0].event_type(), "tracing.error");
     assert_eq!(
       events[0].metadata(),
@@ -346,6 +674,9 @@ mod tests {
         IndigaugeLogLevel::Error,
       ],
       event_type_required: true,
+      directives: FilterHandle::default(),
+      span_depth: DEFAULT_SPAN_DEPTH,
+      span_timings: false,
     };
 
     let _guard = with_indigauge_layer(layer);
@@ -358,7 +689,7 @@ mod tests {
     let events = sink.take_events();
     assert_eq!(events.len(), 1);
 
-    assert_eq!(events[0].level(), "info");
+    assert_eq!(events[0].level(), IndigaugeLogLevel::Info);
     assert_eq!(events[0].event_type(), "custom.event");
     assert_eq!(
       events[0].metadata(),
@@ -380,6 +711,9 @@ mod tests {
         IndigaugeLogLevel::Error,
       ],
       event_type_required: false,
+      directives: FilterHandle::default(),
+      span_depth: DEFAULT_SPAN_DEPTH,
+      span_timings: false,
     };
 
     let _guard = with_indigauge_layer(layer);
@@ -389,7 +723,7 @@ mod tests {
     let events = sink.take_events();
     assert_eq!(events.len(), 1);
 
-    assert_eq!(events[0].level(), "info");
+    assert_eq!(events[0].level(), IndigaugeLogLevel::Info);
     assert_eq!(events[0].event_type(), "tracing.info");
     assert_eq!(
       events[0].metadata(),
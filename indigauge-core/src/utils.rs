@@ -1,4 +1,27 @@
+#[cfg(not(target_family = "wasm"))]
+pub use std::time::Instant;
+#[cfg(target_family = "wasm")]
+pub use web_time::Instant;
+
 /// Generic selector helper.
 pub fn select<T>(true_case: T, false_case: T, condition: bool) -> T {
   if condition { true_case } else { false_case }
 }
+
+#[cfg(not(target_family = "wasm"))]
+static DATA_DIR: std::sync::RwLock<Option<std::path::PathBuf>> = std::sync::RwLock::new(None);
+
+#[cfg(not(target_family = "wasm"))]
+/// Keeps local SDK state (player id, consent, caches) in per-game folders under `dir` instead of the platform
+/// preference storage, e.g. a temporary folder in tests or a portable install folder.
+pub fn set_data_dir(dir: impl Into<std::path::PathBuf>) {
+  *DATA_DIR.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(dir.into());
+}
+
+#[cfg(not(target_family = "wasm"))]
+/// Returns the per-game folder where local SDK state is kept, in platform preference storage unless
+/// [`set_data_dir`] was called.
+pub fn game_data_dir(game_name: &str) -> Option<std::path::PathBuf> {
+  let data_dir = DATA_DIR.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+  data_dir.or_else(dirs::preference_dir).map(|dir| dir.join(game_name))
+}
@@ -0,0 +1,558 @@
+//! Browser backend used on `target_family = "wasm"`: `localStorage` persistence, an IndexedDB offline event queue and
+//! delivery that survives the page being hidden or closed.
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use indigauge_types::prelude::{BatchEventPayload, EventPayload, IndigaugeConfig, StartSessionResponse};
+use js_sys::{Array, Promise};
+use serde::Serialize;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{JsFuture, spawn_local};
+use web_sys::{
+  IdbDatabase, IdbKeyRange, IdbObjectStoreParameters, IdbRequest, IdbTransactionMode, RequestInit, Response, Storage,
+};
+
+use crate::consent::effective_consent;
+use crate::state::{get_session_token, pending_events};
+
+/// Key of the offline queue used when IndexedDB is unavailable.
+const OFFLINE_EVENTS_KEY: &str = "offline_events";
+/// Object store of the offline queue in the per-game IndexedDB database.
+const OFFLINE_EVENTS_STORE: &str = "offline_events";
+/// Total size of the `keepalive` request bodies browsers allow in flight while a page unloads.
+const KEEPALIVE_QUOTA: usize = 64 * 1024;
+
+/// Idempotency keys of the tracked events delivered (or stored offline) by the unload flush that the SDK's event
+/// buffer has not skipped yet.
+static UNLOAD_FLUSHED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+fn local_storage() -> Option<Storage> {
+  web_sys::window()?.local_storage().ok()?
+}
+
+fn storage_key(game_name: &str, key: &str) -> String {
+  format!("indigauge:{game_name}:{key}")
+}
+
+/// Reads a per-game value from `localStorage`.
+pub fn read_item(game_name: &str, key: &str) -> Option<String> {
+  local_storage()?.get_item(&storage_key(game_name, key)).ok()?
+}
+
+/// Writes a per-game value to `localStorage`.
+pub fn write_item(game_name: &str, key: &str, value: &str) -> std::io::Result<()> {
+  let storage = local_storage().ok_or(std::io::ErrorKind::Unsupported)?;
+  storage
+    .set_item(&storage_key(game_name, key), value)
+    .map_err(|error| std::io::Error::other(format!("{error:?}")))
+}
+
+/// Removes a per-game value from `localStorage`.
+pub fn remove_item(game_name: &str, key: &str) {
+  if let Some(storage) = local_storage() {
+    let _ = storage.remove_item(&storage_key(game_name, key));
+  }
+}
+
+/// Generates a random v4 UUID with the browser's `crypto.randomUUID`, or with `Math.random` where it is unavailable
+/// (e.g. outside secure contexts).
+pub fn random_uuid() -> String {
+  web_sys::window()
+    .and_then(|window| window.crypto().ok())
+    .map(|crypto| crypto.random_uuid())
+    .unwrap_or_else(|| format_uuid_v4(random_u64(), random_u64()))
+}
+
+fn random_u64() -> u64 {
+  // `Math.random` has 53 bits of precision, so each half is drawn separately.
+  let random_u32 = || (js_sys::Math::random() * f64::from(u32::MAX)) as u64;
+  (random_u32() << 32) | random_u32()
+}
+
+/// Formats 128 random bits as a hyphenated v4 UUID, setting its version and variant bits.
+fn format_uuid_v4(high: u64, low: u64) -> String {
+  let high = (high & !0xf000) | 0x4000;
+  let low = (low & !(0b11 << 62)) | (0b10 << 62);
+  format!(
+    "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+    high >> 32,
+    (high >> 16) & 0xffff,
+    high & 0xffff,
+    low >> 48,
+    low & 0xffff_ffff_ffff
+  )
+}
+
+/// Builds a JSON `POST` request authenticated with the session token.
+fn json_request(session_token: &str, body: String, keepalive: bool) -> Option<RequestInit> {
+  let headers = js_sys::Object::new();
+  let init = RequestInit::new();
+  let headers_set = js_sys::Reflect::set(&headers, &"Content-Type".into(), &"application/json".into()).is_ok()
+    && js_sys::Reflect::set(&headers, &"X-Indigauge-Key".into(), &session_token.into()).is_ok();
+  // `RequestInit` has no typed setter for `keepalive` yet.
+  if !headers_set || (keepalive && js_sys::Reflect::set(&init, &"keepalive".into(), &JsValue::TRUE).is_err()) {
+    return None;
+  }
+  init.set_method("POST");
+  init.set_headers(&headers);
+  init.set_body(&body.into());
+  Some(init)
+}
+
+/// Sends a JSON request with `keepalive`, so it completes even if the page is closed right after.
+///
+/// The request is fire-and-forget; returns `false` if it could not be issued. Browsers reject `keepalive` requests
+/// once the bodies in flight exceed 64 KiB.
+pub fn send_keepalive<T: Serialize>(config: &IndigaugeConfig, path: &str, session_token: &str, payload: &T) -> bool {
+  let (Some(window), Ok(body)) = (web_sys::window(), serde_json::to_string(payload)) else {
+    return false;
+  };
+  let Some(init) = json_request(session_token, body, true) else {
+    return false;
+  };
+
+  // Dropping the promise does not cancel the request.
+  let _ = window.fetch_with_str_and_init(&config.api_url(path), &init);
+  true
+}
+
+/// Sends a JSON request and resolves once the server accepted it with a success status.
+async fn send_json<T: Serialize>(
+  config: &IndigaugeConfig,
+  path: &str,
+  session_token: &str,
+  payload: &T,
+) -> Result<(), JsValue> {
+  let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+  let body = serde_json::to_string(payload).map_err(|error| JsValue::from_str(&error.to_string()))?;
+  let init =
+    json_request(session_token, body, false).ok_or_else(|| JsValue::from_str("failed to build the request"))?;
+
+  let response = JsFuture::from(window.fetch_with_str_and_init(&config.api_url(path), &init))
+    .await?
+    .unchecked_into::<Response>();
+  if response.ok() {
+    Ok(())
+  } else {
+    Err(JsValue::from_str(&format!("request failed with status {}", response.status())))
+  }
+}
+
+/// Delivers the tracked events of the default session that are not delivered yet with `keepalive` requests, or stores
+/// them for the next session when offline or when they exceed the `keepalive` quota.
+///
+/// Called when the page is hidden or unloaded. The events stay tracked and buffered, so the game keeps sending the
+/// rest of its events if the page becomes visible again; the SDK's event buffer skips the delivered ones via
+/// [`take_unload_flushed`]. Events that fail validation or that the player consent no longer allows are not sent.
+pub fn flush_on_unload(config: &IndigaugeConfig) {
+  let consent = effective_consent();
+  let mut flushed = UNLOAD_FLUSHED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+  let events = pending_events()
+    .into_iter()
+    .filter(|event| {
+      event.session().is_default()
+        && event.validate().is_ok()
+        && consent.allows_level(event.payload().level())
+        && !flushed.contains(event.payload().idempotency_key())
+    })
+    .map(|event| event.into_inner())
+    .collect::<Vec<_>>();
+  if events.is_empty() {
+    return;
+  }
+
+  match get_session_token() {
+    Some(token) if token == StartSessionResponse::dev().session_token => return,
+    Some(token) => {
+      let mut quota = KEEPALIVE_QUOTA;
+      let mut unsent = Vec::new();
+      for batch in events.chunks(config.batch_size()) {
+        let batch = BatchEventPayload { events: batch.to_vec() };
+        let size = serde_json::to_string(&batch).map_or(usize::MAX, |body| body.len());
+        if size <= quota && send_keepalive(config, "events/batch", &token, &batch) {
+          quota -= size;
+        } else {
+          unsent.extend(batch.events);
+        }
+      }
+      store_offline_events(config, &unsent);
+    },
+    None => store_offline_events(config, &events),
+  }
+  flushed.extend(events.iter().map(|event| event.idempotency_key().to_string()));
+}
+
+/// Returns `true` if the event with this idempotency key was handled by [`flush_on_unload`], in which case the caller
+/// must not send it again. Each key is only reported once.
+pub fn take_unload_flushed(idempotency_key: &str) -> bool {
+  UNLOAD_FLUSHED
+    .lock()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+    .remove(idempotency_key)
+}
+
+/// Forgets the events handled by [`flush_on_unload`], e.g. when the session is reset.
+pub fn clear_unload_flushed() {
+  UNLOAD_FLUSHED
+    .lock()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+    .clear();
+}
+
+/// Registers `visibilitychange` and `pagehide` listeners that run [`flush_on_unload`] when the page becomes hidden or
+/// is unloaded. Only the first call has an effect.
+pub fn install_unload_flush(config: IndigaugeConfig) {
+  static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+  let Some(window) = web_sys::window() else {
+    return;
+  };
+  let Some(document) = window.document() else {
+    return;
+  };
+  if INSTALLED.swap(true, Ordering::Relaxed) {
+    return;
+  }
+
+  let visibility_config = config.clone();
+  let visibility_document = document.clone();
+  let on_visibility_change = Closure::<dyn FnMut(web_sys::Event)>::new(move |_| {
+    // Also fired when the page becomes visible again, e.g. when switching back to the tab.
+    if visibility_document.visibility_state() == web_sys::VisibilityState::Hidden {
+      flush_on_unload(&visibility_config);
+    }
+  });
+  let on_page_hide = Closure::<dyn FnMut(web_sys::Event)>::new(move |_| flush_on_unload(&config));
+
+  let _ = document.add_event_listener_with_callback("visibilitychange", on_visibility_change.as_ref().unchecked_ref());
+  let _ = window.add_event_listener_with_callback("pagehide", on_page_hide.as_ref().unchecked_ref());
+
+  // The listeners live as long as the page.
+  on_visibility_change.forget();
+  on_page_hide.forget();
+}
+
+/// Appends events to the offline queue, keeping at most [`IndigaugeConfig::max_queue`] events.
+///
+/// The queue is stored in IndexedDB, or in `localStorage` if IndexedDB is unavailable (e.g. in some private
+/// browsing modes). The IndexedDB write completes in the background.
+pub fn store_offline_events(config: &IndigaugeConfig, events: &[EventPayload]) {
+  let events = events
+    .iter()
+    .filter_map(|event| serde_json::to_string(event).ok())
+    .collect::<Vec<_>>();
+  if events.is_empty() {
+    return;
+  }
+
+  if indexed_db_available() {
+    let game_name = config.game_name().to_string();
+    let max_queue = config.max_queue();
+    spawn_local(async move {
+      if let Err(error) = idb_store_events(&game_name, &events, max_queue).await {
+        web_sys::console::warn_2(&"Indigauge: failed to store offline events".into(), &error);
+      }
+    });
+  } else {
+    let mut stored = local_storage_offline_events(config.game_name());
+    stored.extend(events);
+    let overflow = stored.len().saturating_sub(config.max_queue());
+    stored.drain(..overflow);
+    write_local_storage_offline_events(config.game_name(), &stored);
+  }
+}
+
+/// Sends the events stored by a page that was closed while offline to the given session and removes the delivered
+/// ones from the queue. Events the current player consent no longer allows are dropped.
+///
+/// The queue is read and sent in the background; `on_replayed` receives the number of delivered events. Batches the
+/// server does not accept stay queued for the next session.
+pub fn replay_offline_events(config: &IndigaugeConfig, session_token: &str, on_replayed: impl FnOnce(usize) + 'static) {
+  let config = config.clone();
+  let session_token = session_token.to_string();
+  spawn_local(async move {
+    let replayed = replay_stored_events(&config, &session_token).await;
+    on_replayed(replayed);
+  });
+}
+
+/// Deletes the offline queue of a game, e.g. when the player revokes consent.
+///
+/// The IndexedDB queue is cleared in the background.
+pub fn clear_offline_events(game_name: &str) {
+  remove_item(game_name, OFFLINE_EVENTS_KEY);
+  if indexed_db_available() {
+    let game_name = game_name.to_string();
+    spawn_local(async move {
+      if let Err(error) = idb_clear_events(&game_name).await {
+        web_sys::console::warn_2(&"Indigauge: failed to clear offline events".into(), &error);
+      }
+    });
+  }
+}
+
+/// Returns the events in the offline queue of a game, oldest first.
+pub async fn load_offline_events(game_name: &str) -> Vec<EventPayload> {
+  let mut stored = local_storage_offline_events(game_name);
+  if indexed_db_available() {
+    stored.extend(
+      idb_load_events(game_name)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(_, event)| event),
+    );
+  }
+  stored
+    .iter()
+    .filter_map(|event| serde_json::from_str(event).ok())
+    .collect()
+}
+
+async fn replay_stored_events(config: &IndigaugeConfig, session_token: &str) -> usize {
+  let mut replayed = 0;
+
+  let stored = local_storage_offline_events(config.game_name());
+  if !stored.is_empty() {
+    let (delivered, unsent) = replay_entries(config, session_token, stored.iter().cloned().enumerate()).await;
+    replayed += delivered;
+    let unsent = unsent
+      .into_iter()
+      .map(|index| stored[index].clone())
+      .collect::<Vec<_>>();
+    write_local_storage_offline_events(config.game_name(), &unsent);
+  }
+
+  if indexed_db_available() {
+    let result = async {
+      let stored = idb_load_events(config.game_name()).await?;
+      let keys = stored.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>();
+      let (delivered, unsent) = replay_entries(config, session_token, stored.into_iter()).await;
+      replayed += delivered;
+      // Removes every event but the unsent ones, which are kept in the same order.
+      let mut unsent = unsent.into_iter().peekable();
+      let removed = keys
+        .into_iter()
+        .filter(|key| unsent.next_if_eq(key).is_none())
+        .collect::<Vec<_>>();
+      idb_delete_events(config.game_name(), &removed).await
+    };
+    if let Err(error) = result.await {
+      web_sys::console::warn_2(&"Indigauge: failed to replay offline events".into(), &error);
+    }
+  }
+
+  replayed
+}
+
+/// Sends the stored events the current consent allows in batches, stopping at the first batch that is not accepted.
+///
+/// Returns the number of delivered events and the keys of the allowed events that were not delivered, oldest first.
+async fn replay_entries<K: Clone>(
+  config: &IndigaugeConfig,
+  session_token: &str,
+  stored: impl Iterator<Item = (K, String)>,
+) -> (usize, Vec<K>) {
+  let consent = effective_consent();
+  let replayable = stored
+    .filter_map(|(key, event)| Some((key, serde_json::from_str::<EventPayload>(&event).ok()?)))
+    .filter(|(_, event)| consent.allows_level(event.level()))
+    .collect::<Vec<_>>();
+
+  let mut delivered = 0;
+  for batch in replayable.chunks(config.batch_size()) {
+    let payload = BatchEventPayload {
+      events: batch.iter().map(|(_, event)| event.clone()).collect(),
+    };
+    if let Err(error) = send_json(config, "events/batch", session_token, &payload).await {
+      web_sys::console::warn_2(&"Indigauge: failed to replay offline events".into(), &error);
+      break;
+    }
+    delivered += batch.len();
+  }
+  let unsent = replayable[delivered..].iter().map(|(key, _)| key.clone()).collect();
+  (delivered, unsent)
+}
+
+fn local_storage_offline_events(game_name: &str) -> Vec<String> {
+  read_item(game_name, OFFLINE_EVENTS_KEY)
+    .and_then(|stored| serde_json::from_str::<Vec<serde_json::Value>>(&stored).ok())
+    .map(|events| events.iter().map(ToString::to_string).collect())
+    .unwrap_or_default()
+}
+
+/// Replaces the `localStorage` offline queue with the given JSON events, or removes it if there are none.
+fn write_local_storage_offline_events(game_name: &str, events: &[String]) {
+  if events.is_empty() {
+    remove_item(game_name, OFFLINE_EVENTS_KEY);
+  } else {
+    let _ = write_item(game_name, OFFLINE_EVENTS_KEY, &format!("[{}]", events.join(",")));
+  }
+}
+
+fn indexed_db_available() -> bool {
+  web_sys::window().is_some_and(|window| window.indexed_db().ok().flatten().is_some())
+}
+
+/// Resolves with the result of an IndexedDB request, or rejects with its error.
+async fn idb_request(request: &IdbRequest) -> Result<JsValue, JsValue> {
+  let promise = Promise::new(&mut |resolve, reject| {
+    let succeeded = request.clone();
+    let on_success = Closure::once_into_js(move |_: web_sys::Event| {
+      let _ = resolve.call1(&JsValue::UNDEFINED, &succeeded.result().unwrap_or(JsValue::UNDEFINED));
+    });
+    let failed = request.clone();
+    let on_error = Closure::once_into_js(move |_: web_sys::Event| {
+      let error = failed.error().ok().flatten().map_or(JsValue::UNDEFINED, JsValue::from);
+      let _ = reject.call1(&JsValue::UNDEFINED, &error);
+    });
+    request.set_onsuccess(Some(on_success.unchecked_ref()));
+    request.set_onerror(Some(on_error.unchecked_ref()));
+  });
+  JsFuture::from(promise).await
+}
+
+/// Opens the per-game database, creating the offline queue store on first use.
+async fn idb_open(game_name: &str) -> Result<IdbDatabase, JsValue> {
+  let factory = web_sys::window()
+    .and_then(|window| window.indexed_db().ok().flatten())
+    .ok_or_else(|| JsValue::from_str("IndexedDB is unavailable"))?;
+  let request = factory.open_with_u32(&storage_key(game_name, "events"), 1)?;
+
+  let upgraded = request.clone();
+  let on_upgrade_needed = Closure::once_into_js(move |_: web_sys::Event| {
+    if let Ok(database) = upgraded.result() {
+      let parameters = IdbObjectStoreParameters::new();
+      parameters.set_auto_increment(true);
+      let _ = database
+        .unchecked_into::<IdbDatabase>()
+        .create_object_store_with_optional_parameters(OFFLINE_EVENTS_STORE, &parameters);
+    }
+  });
+  request.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+
+  Ok(idb_request(&request).await?.unchecked_into())
+}
+
+async fn idb_store_events(game_name: &str, events: &[String], max_queue: usize) -> Result<(), JsValue> {
+  let database = idb_open(game_name).await?;
+  let store = database
+    .transaction_with_str_and_mode(OFFLINE_EVENTS_STORE, IdbTransactionMode::Readwrite)?
+    .object_store(OFFLINE_EVENTS_STORE)?;
+  for event in events {
+    store.add(&JsValue::from_str(event))?;
+  }
+
+  let count = idb_request(&store.count()?).await?.as_f64().unwrap_or_default() as usize;
+  let overflow = count.saturating_sub(max_queue);
+  if overflow > 0 {
+    let keys = idb_request(&store.get_all_keys_with_key_and_limit(&JsValue::UNDEFINED, overflow as u32)?).await?;
+    let newest_dropped = Array::from(&keys).get(overflow as u32 - 1);
+    idb_request(&store.delete(&IdbKeyRange::upper_bound(&newest_dropped)?.into())?).await?;
+  }
+  database.close();
+  Ok(())
+}
+
+/// Returns the IndexedDB keys and the events of the offline queue, oldest first.
+async fn idb_load_events(game_name: &str) -> Result<Vec<(JsValue, String)>, JsValue> {
+  let database = idb_open(game_name).await?;
+  let store = database
+    .transaction_with_str_and_mode(OFFLINE_EVENTS_STORE, IdbTransactionMode::Readonly)?
+    .object_store(OFFLINE_EVENTS_STORE)?;
+  let keys = idb_request(&store.get_all_keys()?).await?;
+  let events = idb_request(&store.get_all()?).await?;
+  database.close();
+  Ok(
+    Array::from(&keys)
+      .iter()
+      .zip(Array::from(&events).iter())
+      .filter_map(|(key, event)| Some((key, event.as_string()?)))
+      .collect(),
+  )
+}
+
+async fn idb_delete_events(game_name: &str, keys: &[JsValue]) -> Result<(), JsValue> {
+  if keys.is_empty() {
+    return Ok(());
+  }
+  let database = idb_open(game_name).await?;
+  let store = database
+    .transaction_with_str_and_mode(OFFLINE_EVENTS_STORE, IdbTransactionMode::Readwrite)?
+    .object_store(OFFLINE_EVENTS_STORE)?;
+  let mut deleted = None;
+  for key in keys {
+    deleted = Some(store.delete(key)?);
+  }
+  // Requests of a transaction complete in order.
+  if let Some(deleted) = deleted {
+    idb_request(&deleted).await?;
+  }
+  database.close();
+  Ok(())
+}
+
+async fn idb_clear_events(game_name: &str) -> Result<(), JsValue> {
+  let database = idb_open(game_name).await?;
+  let store = database
+    .transaction_with_str_and_mode(OFFLINE_EVENTS_STORE, IdbTransactionMode::Readwrite)?
+    .object_store(OFFLINE_EVENTS_STORE)?;
+  idb_request(&store.clear()?).await?;
+  database.close();
+  Ok(())
+}
+
+/// Logs a panic to the browser console, like `console_error_panic_hook`.
+pub fn log_panic(info: &std::panic::PanicHookInfo) {
+  web_sys::console::error_1(&info.to_string().into());
+}
+
+/// Tests that need no browser storage and run headlessly in Node; the storage tests live in `tests/web_test.rs`.
+#[cfg(test)]
+mod tests {
+  use indigauge_types::prelude::IndigaugeLogLevel;
+  use wasm_bindgen_test::wasm_bindgen_test;
+
+  use super::*;
+  use crate::state::{enqueue, init, set_session_start_instant, set_session_token};
+  use crate::utils::Instant;
+
+  fn config(game_name: &str) -> IndigaugeConfig {
+    IndigaugeConfig::new(game_name, "public-key", "1.0.0").with_api_base("http://127.0.0.1:9")
+  }
+
+  #[wasm_bindgen_test]
+  fn unload_flush_marks_valid_events_without_draining_them() {
+    let _receiver = init(16).expect("first init");
+    set_session_start_instant(Instant::now()).expect("session start");
+    set_session_token("web-token");
+    assert!(enqueue(IndigaugeLogLevel::Info, "ui.click", None, file!(), line!(), module_path!()));
+    assert!(enqueue(IndigaugeLogLevel::Info, "Not Valid", None, file!(), line!(), module_path!()));
+
+    flush_on_unload(&config("web unload test"));
+    flush_on_unload(&config("web unload test"));
+
+    // The events stay tracked for the game, which skips the delivered one and still rejects the invalid one.
+    let pending = pending_events();
+    assert_eq!(pending.len(), 2);
+    assert!(take_unload_flushed(pending[0].payload().idempotency_key()));
+    assert!(!take_unload_flushed(pending[0].payload().idempotency_key()));
+    assert!(!take_unload_flushed(pending[1].payload().idempotency_key()));
+  }
+
+  #[wasm_bindgen_test]
+  fn fallback_uuids_are_formatted_as_v4_uuids() {
+    assert_eq!(format_uuid_v4(u64::MAX, 0), "ffffffff-ffff-4fff-8000-000000000000");
+    assert_eq!(format_uuid_v4(0, u64::MAX), "00000000-0000-4000-bfff-ffffffffffff");
+
+    // Node has no `window`, so this uses the fallback.
+    let uuid = random_uuid();
+    let groups = uuid.split('-').map(str::len).collect::<Vec<_>>();
+    assert_eq!(groups, [8, 4, 4, 4, 12]);
+    assert_eq!(uuid.as_bytes()[14], b'4');
+    assert!(matches!(uuid.as_bytes()[19], b'8' | b'9' | b'a' | b'b'));
+  }
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "derive")]
+
+use std::sync::Mutex;
+
+use indigauge_core::event::set_event_dispatcher;
+use indigauge_core::prelude::*;
+use serde_json::{Value, json};
+
+#[derive(IndigaugeEvent)]
+#[ig(event_type = "player.death", level = "warn")]
+struct PlayerDeath {
+  cause: String,
+  #[ig(rename = "lvl")]
+  level: u32,
+  killer: Option<String>,
+  #[ig(skip)]
+  #[allow(dead_code)]
+  frame: u64,
+}
+
+mod events {
+  use indigauge_core::prelude::*;
+
+  #[derive(IndigaugeEvent)]
+  #[ig(event_type = "game.start")]
+  pub struct GameStart;
+}
+
+use events::GameStart;
+
+type Dispatched = (IndigaugeLogLevel, String, Option<Value>, &'static str);
+
+static DISPATCHED: Mutex<Vec<Dispatched>> = Mutex::new(Vec::new());
+
+fn dispatcher(
+  level: IndigaugeLogLevel,
+  event_type: &str,
+  metadata: Option<Value>,
+  _: &'static str,
+  _: u32,
+  module: &'static str,
+) -> bool {
+  DISPATCHED
+    .lock()
+    .unwrap()
+    .push((level, event_type.to_string(), metadata, module));
+  true
+}
+
+#[test]
+fn derived_events_emit_fields_as_metadata() {
+  set_event_dispatcher(dispatcher);
+
+  ig_emit!(PlayerDeath {
+    cause: "lava".to_string(),
+    level: 3,
+    killer: None,
+    frame: 120,
+  });
+  assert!(GameStart.emit());
+
+  let dispatched = DISPATCHED.lock().unwrap();
+  assert_eq!(
+    dispatched[0],
+    (IndigaugeLogLevel::Warn, "player.death".to_string(), Some(json!({ "cause": "lava", "lvl": 3 })), module_path!())
+  );
+  // `emit` reports the module defining the event.
+  assert_eq!(dispatched[1], (IndigaugeLogLevel::Info, "game.start".to_string(), Some(json!({})), GameStart::MODULE));
+  assert_eq!(GameStart::MODULE, concat!(module_path!(), "::events"));
+}
+
+#[test]
+fn derived_events_generate_json_schema() {
+  assert_eq!(
+    PlayerDeath::schema(),
+    json!({
+      "$schema": "https://json-schema.org/draft/2020-12/schema",
+      "title": "player.death",
+      "type": "object",
+      "properties": {
+        "cause": { "type": "string" },
+        "lvl": { "type": "integer", "minimum": 0 },
+        "killer": { "type": "string" },
+      },
+      "required": ["cause", "lvl"],
+      "additionalProperties": false,
+    })
+  );
+}
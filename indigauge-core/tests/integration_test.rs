@@ -1,6 +1,13 @@
+#![cfg(not(target_family = "wasm"))]
+
 use std::time::Instant;
 
-use indigauge_core::state::{enqueue, init, set_session_start_instant};
+use indigauge_core::ig_info_for;
+use indigauge_core::state::{
+  SessionHandle, enqueue, enqueue_for, get_session_token, init, register_session, reset_session, set_min_event_level,
+  set_session_start_instant, set_session_token, unregister_session,
+};
+use indigauge_core::types::IndigaugeLogLevel;
 use serde_json::json;
 
 #[test]
@@ -12,7 +19,7 @@ fn test_core_event_flow() {
   set_session_start_instant(Instant::now()).expect("Failed to set start instant");
 
   // 3. Enqueue an event
-  let queued = enqueue("info", "test.event", Some(json!({"foo": "bar"})), "test.rs", 10, "test_mod");
+  let queued = enqueue(IndigaugeLogLevel::Info, "test.event", Some(json!({"foo": "bar"})), "test.rs", 10, "test_mod");
   assert!(queued, "Event should have been queued");
 
   // 4. consume event
@@ -20,5 +27,89 @@ fn test_core_event_flow() {
   let payload = event.into_inner();
 
   assert_eq!(payload.event_type(), "test.event");
-  assert_eq!(payload.level(), "info");
+  assert_eq!(payload.level(), IndigaugeLogLevel::Info);
+
+  // 5. Route events to an additional session
+  let player = SessionHandle::next();
+  assert!(!enqueue_for(player, IndigaugeLogLevel::Info, "player.join", None, "test.rs", 20, "test_mod"));
+
+  register_session(player, Instant::now());
+  ig_info_for!(player, "player.join", { "slot": 2 });
+  let event = rx.try_recv().expect("Should have received player event");
+  assert_eq!(event.session(), player);
+  assert_eq!(event.into_inner().event_type(), "player.join");
+
+  unregister_session(player);
+  assert!(!enqueue_for(player, IndigaugeLogLevel::Info, "player.leave", None, "test.rs", 30, "test_mod"));
+  assert!(rx.try_recv().is_err());
+
+  // 6. End the session and start a new one in the same process
+  set_session_token("first-token");
+  assert!(set_session_start_instant(Instant::now()).is_err());
+
+  reset_session();
+  assert!(get_session_token().is_none());
+  assert!(!enqueue(IndigaugeLogLevel::Info, "test.ended", None, "test.rs", 40, "test_mod"));
+
+  set_session_start_instant(Instant::now()).expect("Restart should set a new start instant");
+  assert!(enqueue(IndigaugeLogLevel::Info, "test.restarted", None, "test.rs", 50, "test_mod"));
+  let event = rx
+    .try_recv()
+    .expect("Should have received event of the restarted session");
+  assert_eq!(event.into_inner().event_type(), "test.restarted");
+
+  // 7. Drop events below the minimum level
+  set_min_event_level(IndigaugeLogLevel::Warn);
+  assert!(!enqueue(IndigaugeLogLevel::Trace, "test.verbose", None, "test.rs", 60, "test_mod"));
+  assert!(!enqueue(IndigaugeLogLevel::Info, "test.verbose", None, "test.rs", 61, "test_mod"));
+  assert!(enqueue(IndigaugeLogLevel::Warn, "test.warning", None, "test.rs", 62, "test_mod"));
+  set_min_event_level(IndigaugeLogLevel::Trace);
+  let payload = rx.try_recv().expect("Should have received warning").into_inner();
+  assert_eq!(payload.level(), IndigaugeLogLevel::Warn);
+  assert!(payload.context().is_some());
+  assert!(rx.try_recv().is_err());
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod final_flush {
+  use std::time::Duration;
+
+  use indigauge_core::runtime::IndigaugeBlockingRuntimeClient;
+  use indigauge_core::test_util::StubIngest;
+  use indigauge_core::types::{EventPayload, IndigaugeConfig, IndigaugeLogLevel};
+
+  fn events(count: usize) -> Vec<EventPayload> {
+    (0..count)
+      .map(|i| EventPayload::new("test.event", IndigaugeLogLevel::Info, None, i as u128))
+      .collect()
+  }
+
+  #[test]
+  fn sends_remaining_batches_then_ends_session() {
+    let ingest = StubIngest::ok("{}");
+    let config = IndigaugeConfig::new("game", "key", "1.0.0").with_api_base(&ingest.api_base);
+    let batch_size = config.batch_size();
+    let client = IndigaugeBlockingRuntimeClient::new(config);
+
+    let report = client.flush_and_end_session("token", events(batch_size + 1), "ended", Duration::from_secs(5));
+
+    assert_eq!(report.sent_events, batch_size + 1);
+    assert_eq!(report.dropped_events, 0);
+    assert!(report.session_ended);
+    assert_eq!(ingest.paths(), ["/v1/events/batch", "/v1/events/batch", "/v1/sessions/end"]);
+  }
+
+  #[test]
+  fn expired_deadline_drops_everything_without_blocking() {
+    let ingest = StubIngest::ok("{}");
+    let config = IndigaugeConfig::new("game", "key", "1.0.0").with_api_base(&ingest.api_base);
+    let client = IndigaugeBlockingRuntimeClient::new(config);
+
+    let report = client.flush_and_end_session("token", events(3), "ended", Duration::ZERO);
+
+    assert_eq!(report.sent_events, 0);
+    assert_eq!(report.dropped_events, 3);
+    assert!(!report.session_ended);
+    assert!(ingest.requests().is_empty());
+  }
 }
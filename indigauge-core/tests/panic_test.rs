@@ -0,0 +1,26 @@
+#![cfg(all(feature = "panic_handler", not(target_family = "wasm")))]
+
+use std::time::Instant;
+
+use indigauge_core::panic::panic_handler_with_config;
+use indigauge_core::test_util::StubIngest;
+use indigauge_core::types::IndigaugeConfig;
+
+#[test]
+fn crash_event_is_sent_before_the_session_ends() {
+  let ingest = StubIngest::ok("{}");
+  let config = IndigaugeConfig::new("game", "key", "1.0.0").with_api_base(&ingest.api_base);
+
+  std::panic::set_hook(Box::new(panic_handler_with_config(config, "token".to_string(), Instant::now())));
+  let result = std::panic::catch_unwind(|| panic!("lost {} saves", 3));
+  let _ = std::panic::take_hook();
+  assert!(result.is_err());
+
+  assert_eq!(ingest.paths(), ["/v1/events/batch", "/v1/sessions/end"]);
+  let batch = &ingest.requests_to("/v1/events/batch")[0];
+  assert_eq!(batch.api_key, "token");
+  let events = batch.body["events"].as_array().unwrap();
+  assert_eq!(events.len(), 1);
+  assert_eq!(events[0]["eventType"], "game.crash");
+  assert_eq!(events[0]["metadata"]["message"], "lost 3 saves");
+}
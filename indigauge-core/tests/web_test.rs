@@ -0,0 +1,88 @@
+#![cfg(target_family = "wasm")]
+
+use indigauge_core::consent::set_consent;
+use indigauge_core::types::{EventPayload, IndigaugeConfig, IndigaugeLogLevel, TelemetryConsent};
+use indigauge_core::web::{clear_offline_events, load_offline_events, replay_offline_events, store_offline_events};
+use js_sys::Promise;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+// `localStorage` and IndexedDB are only available in browsers.
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn config(game_name: &str) -> IndigaugeConfig {
+  // Nothing listens on the discard port, so every replay fails.
+  IndigaugeConfig::new(game_name, "public-key", "1.0.0")
+    .with_api_base("http://127.0.0.1:9")
+    .with_max_queue(2)
+}
+
+fn events(event_types: &[&str]) -> Vec<EventPayload> {
+  event_types
+    .iter()
+    .map(|&event_type| EventPayload::new(event_type, IndigaugeLogLevel::Info, None, 0))
+    .collect()
+}
+
+async fn sleep(ms: i32) {
+  let promise = Promise::new(&mut |resolve, _| {
+    let window = web_sys::window().expect("window");
+    let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms);
+  });
+  let _ = JsFuture::from(promise).await;
+}
+
+/// Waits until the offline queue, which is written in the background, has `count` events.
+async fn offline_events(game_name: &str, count: usize) -> Vec<EventPayload> {
+  for _ in 0..50 {
+    let events = load_offline_events(game_name).await;
+    if events.len() == count {
+      return events;
+    }
+    sleep(20).await;
+  }
+  load_offline_events(game_name).await
+}
+
+async fn replay(config: &IndigaugeConfig) -> usize {
+  let promise = Promise::new(&mut |resolve, _| {
+    replay_offline_events(config, "web-token", move |replayed| {
+      let _ = resolve.call1(&JsValue::UNDEFINED, &JsValue::from(replayed as f64));
+    });
+  });
+  JsFuture::from(promise)
+    .await
+    .ok()
+    .and_then(|replayed| replayed.as_f64())
+    .unwrap_or_default() as usize
+}
+
+#[wasm_bindgen_test]
+async fn offline_events_are_trimmed_and_kept_until_delivered() {
+  let config = config("web offline test");
+  store_offline_events(&config, &events(&["ui.first", "ui.second", "ui.third"]));
+
+  let stored = offline_events(config.game_name(), 2).await;
+  assert_eq!(stored.iter().map(EventPayload::event_type).collect::<Vec<_>>(), ["ui.second", "ui.third"]);
+
+  // The server is unreachable, so nothing is delivered and nothing is removed.
+  assert_eq!(replay(&config).await, 0);
+  assert_eq!(load_offline_events(config.game_name()).await.len(), 2);
+
+  // Events the consent no longer allows are dropped instead of replayed.
+  set_consent(TelemetryConsent::Essential);
+  assert_eq!(replay(&config).await, 0);
+  set_consent(TelemetryConsent::Granted);
+  assert!(load_offline_events(config.game_name()).await.is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn clearing_the_offline_queue_deletes_stored_events() {
+  let config = config("web offline clear test");
+  store_offline_events(&config, &events(&["ui.first"]));
+  assert_eq!(offline_events(config.game_name(), 1).await.len(), 1);
+
+  clear_offline_events(config.game_name());
+  assert!(offline_events(config.game_name(), 0).await.is_empty());
+}
@@ -0,0 +1,158 @@
+#![doc = include_str!("../README.md")]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr, Path, parse_macro_input};
+
+/// Level names accepted by `#[ig(level = "...")]` and the matching `IndigaugeLogLevel` variants.
+const LEVELS: [(&str, &str); 5] = [
+  ("trace", "Trace"),
+  ("debug", "Debug"),
+  ("info", "Info"),
+  ("warn", "Warn"),
+  ("error", "Error"),
+];
+
+/// Derives `IndigaugeEvent` for a struct with named fields, turning each field into a metadata key.
+///
+/// See the crate documentation for the supported `#[ig(...)]` attributes.
+#[proc_macro_derive(IndigaugeEvent, attributes(ig))]
+pub fn derive_indigauge_event(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+struct EventAttrs {
+  event_type: LitStr,
+  level: syn::Ident,
+  krate: Path,
+}
+
+struct EventField {
+  member: syn::Ident,
+  key: LitStr,
+  ty: syn::Type,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+  let EventAttrs {
+    event_type,
+    level,
+    krate,
+  } = parse_event_attrs(input)?;
+  let fields = parse_fields(input)?;
+
+  let ident = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+  let members = fields.iter().map(|field| &field.member);
+  let keys = fields.iter().map(|field| &field.key).collect::<Vec<_>>();
+  let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+
+  Ok(quote! {
+    const _: &str = #krate::event::validate_event_type_compile_time(#event_type);
+
+    impl #impl_generics #krate::event::IndigaugeEvent for #ident #ty_generics #where_clause {
+      const EVENT_TYPE: &'static str = #event_type;
+      const LEVEL: #krate::types::IndigaugeLogLevel = #krate::types::IndigaugeLogLevel::#level;
+      const MODULE: &'static str = module_path!();
+
+      fn metadata(&self) -> #krate::schema::Value {
+        let mut metadata = #krate::schema::Map::new();
+        #( #krate::schema::insert_field(&mut metadata, #keys, &self.#members); )*
+        #krate::schema::Value::Object(metadata)
+      }
+
+      fn schema() -> #krate::schema::Value {
+        #krate::schema::event_schema(
+          #event_type,
+          &[#( (#keys, <#types as #krate::schema::MetadataSchema>::schema(), <#types as #krate::schema::MetadataSchema>::REQUIRED) ),*],
+        )
+      }
+    }
+  })
+}
+
+fn parse_event_attrs(input: &DeriveInput) -> syn::Result<EventAttrs> {
+  let mut event_type = None;
+  let mut level = None;
+  let mut krate = None;
+
+  for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("ig")) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("event_type") {
+        event_type = Some(meta.value()?.parse::<LitStr>()?);
+      } else if meta.path.is_ident("level") {
+        level = Some(meta.value()?.parse::<LitStr>()?);
+      } else if meta.path.is_ident("crate") {
+        krate = Some(meta.value()?.parse::<LitStr>()?.parse::<Path>()?);
+      } else {
+        return Err(meta.error("unknown `ig` attribute, expected `event_type`, `level` or `crate`"));
+      }
+      Ok(())
+    })?;
+  }
+
+  let event_type = event_type.ok_or_else(|| {
+    syn::Error::new_spanned(&input.ident, "missing `#[ig(event_type = \"namespace.event\")]` attribute")
+  })?;
+
+  let level = level.unwrap_or_else(|| LitStr::new("info", Span::call_site()));
+  let Some((_, variant)) = LEVELS.iter().find(|(name, _)| *name == level.value()) else {
+    return Err(syn::Error::new(
+      level.span(),
+      "invalid level, expected one of `trace`, `debug`, `info`, `warn` or `error`",
+    ));
+  };
+
+  Ok(EventAttrs {
+    event_type,
+    level: syn::Ident::new(variant, level.span()),
+    krate: krate.unwrap_or_else(|| syn::parse_quote!(::indigauge_core)),
+  })
+}
+
+fn parse_fields(input: &DeriveInput) -> syn::Result<Vec<EventField>> {
+  let fields = match &input.data {
+    Data::Struct(data) => match &data.fields {
+      Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+      Fields::Unit => Vec::new(),
+      Fields::Unnamed(_) => {
+        return Err(syn::Error::new_spanned(&input.ident, "`IndigaugeEvent` requires named fields"));
+      },
+    },
+    _ => return Err(syn::Error::new_spanned(&input.ident, "`IndigaugeEvent` can only be derived for structs")),
+  };
+
+  let mut event_fields = Vec::with_capacity(fields.len());
+  for field in fields {
+    let Some(member) = field.ident.clone() else {
+      continue;
+    };
+    let mut key = LitStr::new(&member.to_string(), member.span());
+    let mut skip = false;
+
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("ig")) {
+      attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("rename") {
+          key = meta.value()?.parse::<LitStr>()?;
+        } else if meta.path.is_ident("skip") {
+          skip = true;
+        } else {
+          return Err(meta.error("unknown `ig` field attribute, expected `rename` or `skip`"));
+        }
+        Ok(())
+      })?;
+    }
+
+    if !skip {
+      event_fields.push(EventField {
+        member,
+        key,
+        ty: field.ty.clone(),
+      });
+    }
+  }
+
+  Ok(event_fields)
+}
@@ -1,5 +1,7 @@
 use std::{env, time::Duration};
 
+use serde::{Deserialize, Serialize};
+
 /// Runtime configuration used by SDK clients when talking to Indigauge.
 #[derive(Clone)]
 pub struct IndigaugeConfig {
@@ -11,6 +13,9 @@ pub struct IndigaugeConfig {
   flush_interval: Duration,
   max_queue: usize,
   request_timeout: Duration,
+  shutdown_timeout: Duration,
+  retry_initial_backoff: Duration,
+  retry_max_backoff: Duration,
 }
 
 impl IndigaugeConfig {
@@ -25,9 +30,37 @@ impl IndigaugeConfig {
       flush_interval: Duration::from_secs(10),
       max_queue: 10_000,
       request_timeout: Duration::from_secs(10),
+      shutdown_timeout: Duration::from_secs(2),
+      retry_initial_backoff: Duration::from_secs(1),
+      retry_max_backoff: Duration::from_secs(60),
     }
   }
 
+  /// Overrides the ingest API base URL.
+  pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+    self.api_base = api_base.into();
+    self
+  }
+
+  /// Overrides the maximum number of queued events.
+  pub fn with_max_queue(mut self, max_queue: usize) -> Self {
+    self.max_queue = max_queue;
+    self
+  }
+
+  /// Overrides the total time budget for the final flush performed on exit.
+  pub fn with_shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+    self.shutdown_timeout = shutdown_timeout;
+    self
+  }
+
+  /// Overrides the exponential backoff bounds used when retrying failed requests.
+  pub fn with_retry_backoff(mut self, initial: Duration, max: Duration) -> Self {
+    self.retry_initial_backoff = initial;
+    self.retry_max_backoff = max.max(initial);
+    self
+  }
+
   /// Returns `true` if a non-empty public key is configured.
   pub fn has_public_key(&self) -> bool {
     !self.public_key.trim().is_empty()
@@ -77,6 +110,20 @@ impl IndigaugeConfig {
   pub fn request_timeout(&self) -> Duration {
     self.request_timeout
   }
+
+  /// Returns the total time budget for the final flush performed on exit.
+  pub fn shutdown_timeout(&self) -> Duration {
+    self.shutdown_timeout
+  }
+
+  /// Returns the delay before retry number `attempt` (1-based), doubling per attempt up to the max backoff.
+  pub fn retry_backoff(&self, attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    self
+      .retry_initial_backoff
+      .saturating_mul(factor)
+      .min(self.retry_max_backoff)
+  }
 }
 
 #[cfg(test)]
@@ -98,17 +145,93 @@ mod tests {
 
     assert!(!config.has_public_key());
   }
+
+  #[test]
+  fn retry_backoff_doubles_up_to_max() {
+    let config =
+      IndigaugeConfig::new("game", "key", "1.0.0").with_retry_backoff(Duration::from_secs(1), Duration::from_secs(10));
+
+    assert_eq!(config.retry_backoff(1), Duration::from_secs(1));
+    assert_eq!(config.retry_backoff(2), Duration::from_secs(2));
+    assert_eq!(config.retry_backoff(4), Duration::from_secs(8));
+    assert_eq!(config.retry_backoff(5), Duration::from_secs(10));
+    assert_eq!(config.retry_backoff(u32::MAX), Duration::from_secs(10));
+  }
+
+  #[test]
+  fn unknown_consent_resolves_by_requirement() {
+    assert_eq!(TelemetryConsent::Unknown.resolve(true), TelemetryConsent::Denied);
+    assert_eq!(TelemetryConsent::Unknown.resolve(false), TelemetryConsent::Granted);
+    assert_eq!(TelemetryConsent::Essential.resolve(true), TelemetryConsent::Essential);
+
+    assert!(TelemetryConsent::Essential.allows_level(IndigaugeLogLevel::Error));
+    assert!(!TelemetryConsent::Essential.allows_level(IndigaugeLogLevel::Info));
+    assert!(!TelemetryConsent::Essential.allows_player_id());
+    assert!(!TelemetryConsent::Denied.allows_session());
+    assert_eq!(TelemetryConsent::parse("essential\n"), Some(TelemetryConsent::Essential));
+  }
+
+  #[test]
+  fn log_levels_are_ordered_and_round_trip() {
+    assert!(IndigaugeLogLevel::Trace < IndigaugeLogLevel::Debug);
+    assert!(IndigaugeLogLevel::Error < IndigaugeLogLevel::Fatal);
+    assert!(IndigaugeLogLevel::Fatal < IndigaugeLogLevel::Silent);
+    assert_eq!(IndigaugeLogLevel::parse("trace"), Some(IndigaugeLogLevel::Trace));
+    assert_eq!(IndigaugeLogLevel::parse(IndigaugeLogLevel::Warn.as_str()), Some(IndigaugeLogLevel::Warn));
+    assert_eq!(IndigaugeLogLevel::parse("verbose"), None);
+  }
 }
 
-#[derive(PartialEq, PartialOrd, Clone, Debug)]
+/// Severity of an event, ordered from the most verbose (`Trace`) to `Silent`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum IndigaugeLogLevel {
+  Trace,
   Debug,
   Info,
   Warn,
   Error,
+  /// Used for crash reports.
+  Fatal,
+  /// Disables logging or event collection when used as a minimum level.
   Silent,
 }
 
+impl IndigaugeLogLevel {
+  /// Returns the lowercase name of the level, as sent to the API.
+  pub const fn as_str(self) -> &'static str {
+    match self {
+      IndigaugeLogLevel::Trace => "trace",
+      IndigaugeLogLevel::Debug => "debug",
+      IndigaugeLogLevel::Info => "info",
+      IndigaugeLogLevel::Warn => "warn",
+      IndigaugeLogLevel::Error => "error",
+      IndigaugeLogLevel::Fatal => "fatal",
+      IndigaugeLogLevel::Silent => "silent",
+    }
+  }
+
+  /// Parses a lowercase level name such as `"warn"`.
+  pub fn parse(value: &str) -> Option<Self> {
+    match value.trim() {
+      "trace" => Some(IndigaugeLogLevel::Trace),
+      "debug" => Some(IndigaugeLogLevel::Debug),
+      "info" => Some(IndigaugeLogLevel::Info),
+      "warn" => Some(IndigaugeLogLevel::Warn),
+      "error" => Some(IndigaugeLogLevel::Error),
+      "fatal" => Some(IndigaugeLogLevel::Fatal),
+      "silent" => Some(IndigaugeLogLevel::Silent),
+      _ => None,
+    }
+  }
+}
+
+impl std::fmt::Display for IndigaugeLogLevel {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
 #[derive(PartialEq, Clone, Debug)]
 pub enum IndigaugeMode {
   /// Live mode sends data to the Indigauge API. Default in release builds.
@@ -137,3 +260,73 @@ impl Default for IndigaugeMode {
     Self::auto()
   }
 }
+
+/// Player consent for telemetry collection.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum TelemetryConsent {
+  /// The player has not decided yet. Treated as `Granted`, or as `Denied` when consent is required.
+  #[default]
+  Unknown,
+  /// All telemetry is allowed.
+  Granted,
+  /// No session is started and nothing is collected or stored.
+  Denied,
+  /// Only warnings, errors and crash reports are sent, without a persistent player id or screenshots.
+  Essential,
+}
+
+impl TelemetryConsent {
+  /// Returns the persisted representation of the consent.
+  pub fn as_str(self) -> &'static str {
+    match self {
+      TelemetryConsent::Unknown => "unknown",
+      TelemetryConsent::Granted => "granted",
+      TelemetryConsent::Denied => "denied",
+      TelemetryConsent::Essential => "essential",
+    }
+  }
+
+  /// Parses the persisted representation of the consent.
+  pub fn parse(value: &str) -> Option<Self> {
+    match value.trim() {
+      "unknown" => Some(TelemetryConsent::Unknown),
+      "granted" => Some(TelemetryConsent::Granted),
+      "denied" => Some(TelemetryConsent::Denied),
+      "essential" => Some(TelemetryConsent::Essential),
+      _ => None,
+    }
+  }
+
+  /// Resolves `Unknown` to the consent that applies while the player has not decided.
+  pub fn resolve(self, consent_required: bool) -> Self {
+    match self {
+      TelemetryConsent::Unknown if consent_required => TelemetryConsent::Denied,
+      TelemetryConsent::Unknown => TelemetryConsent::Granted,
+      consent => consent,
+    }
+  }
+
+  /// Returns `true` if a session may be started.
+  pub fn allows_session(self) -> bool {
+    self != TelemetryConsent::Denied
+  }
+
+  /// Returns `true` if events of the given level may be collected.
+  pub fn allows_level(self, level: IndigaugeLogLevel) -> bool {
+    match self {
+      TelemetryConsent::Unknown | TelemetryConsent::Granted => true,
+      TelemetryConsent::Essential => level >= IndigaugeLogLevel::Warn,
+      TelemetryConsent::Denied => false,
+    }
+  }
+
+  /// Returns `true` if a persistent player id may be created and sent.
+  pub fn allows_player_id(self) -> bool {
+    matches!(self, TelemetryConsent::Unknown | TelemetryConsent::Granted)
+  }
+
+  /// Returns `true` if screenshots may be attached to feedback.
+  pub fn allows_screenshots(self) -> bool {
+    matches!(self, TelemetryConsent::Unknown | TelemetryConsent::Granted)
+  }
+}
@@ -1,6 +1,10 @@
-use serde::Serialize;
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::config::IndigaugeLogLevel;
+
 /// Batch payload for sending multiple events in a single request.
 #[derive(Serialize, Clone, Debug)]
 pub struct BatchEventPayload {
@@ -8,7 +12,9 @@ pub struct BatchEventPayload {
 }
 
 /// Structured event payload sent to Indigauge ingest endpoints.
-#[derive(Serialize, Clone, Debug, PartialEq)]
+///
+/// Deserializing keeps the recorded idempotency key and elapsed time, e.g. to replay recorded sessions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct EventPayload {
   /// The type of the event. Event type must be in the format 'namespace.type'
@@ -16,7 +22,7 @@ pub struct EventPayload {
   /// Metadata associated with the event.
   metadata: Option<serde_json::Value>,
   /// The level of the event.
-  level: &'static str,
+  level: IndigaugeLogLevel,
   /// Defaults to elapsed time since session start
   elapsed_ms: u128,
   idempotency_key: String,
@@ -27,7 +33,7 @@ impl EventPayload {
   /// Creates a new event payload and generates an idempotency key.
   pub fn new(
     event_type: impl Into<String>,
-    level: &'static str,
+    level: IndigaugeLogLevel,
     metadata: Option<serde_json::Value>,
     elapsed_ms: u128,
   ) -> Self {
@@ -52,8 +58,13 @@ impl EventPayload {
     self.metadata.as_ref()
   }
 
+  /// Returns mutable metadata if present.
+  pub fn metadata_mut(&mut self) -> Option<&mut serde_json::Value> {
+    self.metadata.as_mut()
+  }
+
   /// Returns the event severity level.
-  pub fn level(&self) -> &str {
+  pub fn level(&self) -> IndigaugeLogLevel {
     self.level
   }
 
@@ -62,6 +73,16 @@ impl EventPayload {
     &self.event_type
   }
 
+  /// Returns the elapsed time since session start in milliseconds.
+  pub fn elapsed_ms(&self) -> u128 {
+    self.elapsed_ms
+  }
+
+  /// Returns the key the ingest uses to deduplicate retried events.
+  pub fn idempotency_key(&self) -> &str {
+    &self.idempotency_key
+  }
+
   /// Returns context information if present.
   pub fn context(&self) -> Option<&EventPayloadCtx> {
     self.context.as_ref()
@@ -69,9 +90,9 @@ impl EventPayload {
 }
 
 /// Source code context for an event.
-#[derive(Serialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct EventPayloadCtx {
   pub file: String,
   pub line: u32,
-  pub module: Option<&'static str>,
+  pub module: Option<Cow<'static, str>>,
 }
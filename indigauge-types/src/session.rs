@@ -13,6 +13,16 @@ pub struct StartSessionPayload<'a> {
   pub cores: Option<&'a str>,
   pub memory: Option<&'a str>,
   pub gpu: Option<&'a String>,
+  /// Token of an expired session that this session continues.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub previous_session_token: Option<&'a str>,
+}
+
+/// Payload linking the player of the current session to an account, e.g. after login.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentifyPayload<'a> {
+  pub account_id: &'a str,
 }
 
 /// Response returned by the session start endpoint.
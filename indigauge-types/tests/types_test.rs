@@ -3,12 +3,36 @@ use serde_json::json;
 
 #[test]
 fn test_event_payload_serialization() {
-  let payload = EventPayload::new("test.foo", "info", Some(json!({"x": 1})), 123);
+  let payload = EventPayload::new("test.foo", IndigaugeLogLevel::Info, Some(json!({"x": 1})), 123);
   let json = serde_json::to_string(&payload).expect("Serialize");
 
   assert!(json.contains(r#""eventType":"test.foo""#));
   assert!(json.contains(r#""level":"info""#));
   assert!(json.contains(r#""x":1"#));
+
+  let trace = EventPayload::new("test.bar", IndigaugeLogLevel::Trace, None, 0);
+  assert!(
+    serde_json::to_string(&trace)
+      .expect("Serialize")
+      .contains(r#""level":"trace""#)
+  );
+}
+
+#[test]
+fn test_event_payload_round_trip() {
+  let payload = EventPayload::new("test.foo", IndigaugeLogLevel::Warn, Some(json!({"x": 1})), 123).with_context(Some(
+    EventPayloadCtx {
+      file: "src/main.rs".to_string(),
+      line: 7,
+      module: Some("game::ai".into()),
+    },
+  ));
+  let json = serde_json::to_string(&payload).expect("Serialize");
+  let decoded: EventPayload = serde_json::from_str(&json).expect("Deserialize");
+
+  assert_eq!(decoded, payload);
+  assert_eq!(decoded.idempotency_key(), payload.idempotency_key());
+  assert_eq!(decoded.elapsed_ms(), 123);
 }
 
 #[test]
@@ -23,9 +47,11 @@ fn test_session_start_serialization() {
     cores: None,
     memory: None,
     gpu: None,
+    previous_session_token: None,
   };
 
   let json = serde_json::to_string(&session).expect("Serialize");
   assert!(json.contains(r#""clientVersion":"1.0.0""#));
   assert!(json.contains(r#""sdkVersion":"2.0.0""#));
+  assert!(!json.contains("previousSessionToken"));
 }